@@ -1,6 +1,9 @@
 use cgmath::Deg;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use luminet_blackhole_lib::{plotting::generate_flux_image, BlackHole};
+use luminet_blackhole_lib::{
+    plotting::{generate_flux_image, FluxImageOptions},
+    BlackHole,
+};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("generate_flux_image width=256 samples=5000", |b| {
@@ -12,7 +15,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
                 black_box(5000),
                 black_box(256),
                 black_box(135),
-                None,
+                &FluxImageOptions::default(),
             )
             .unwrap();
         })
@@ -1,6 +1,8 @@
-use cgmath::Deg;
+use cgmath::{Deg, Rad};
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use luminet_blackhole_lib::BlackHole;
+use luminet_blackhole_lib::{
+    calc_impact_parameter_checked, BlackHole, ImpactParameterCache, SolverConfig,
+};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("sample_flux_at_points num_points=1000 order=0", |b| {
@@ -24,6 +26,61 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             );
         })
     });
+
+    // A repeated workload of solving for the same grid of emission points, with the same solver
+    // both with and without the cache
+    let blackhole = BlackHole::default();
+    let inclination = Rad::from(Deg(80.0));
+    let config = SolverConfig::default();
+    let grid = (0..10)
+        .flat_map(|i| (0..100).map(move |j| (6.0 + 4.0 * f64::from(i), f64::from(j) * 0.0628)))
+        .collect::<Vec<(f64, f64)>>();
+
+    c.bench_function("impact parameter grid points=1000 uncached", |b| {
+        b.iter(|| {
+            for &(radius, alpha) in &grid {
+                let _ = calc_impact_parameter_checked(
+                    radius,
+                    black_box(inclination),
+                    Rad(alpha),
+                    blackhole.mass,
+                    0,
+                    &config,
+                );
+            }
+        })
+    });
+
+    let cache = ImpactParameterCache::new();
+    c.bench_function("impact parameter grid points=1000 cached", |b| {
+        b.iter(|| {
+            for &(radius, alpha) in &grid {
+                let _ = cache.calc_impact_parameter(
+                    radius,
+                    black_box(inclination),
+                    Rad(alpha),
+                    blackhole.mass,
+                    0,
+                    &config,
+                );
+            }
+        })
+    });
+
+    c.bench_function(
+        "sample_flux_at_points_cached num_points=1000 order=0",
+        |b| {
+            let cache = ImpactParameterCache::new();
+            b.iter(|| {
+                let _ = blackhole.sample_flux_at_points_cached(
+                    black_box(Deg(80.0)),
+                    black_box(1000),
+                    black_box(0),
+                    Some(&cache),
+                );
+            })
+        },
+    );
 }
 
 criterion_group!(benches, criterion_benchmark);
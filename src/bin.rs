@@ -1,6 +1,15 @@
 use cgmath::{Deg, Rad};
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+    io::Write,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// Direct (order = 0) radii drawn by `--overlay-isoradials`.
+const OVERLAY_DIRECT_RADII: [f64; 4] = [6.0, 10.0, 20.0, 30.0];
+/// Ghost (order = 1) radii drawn by `--overlay-isoradials`.
+const OVERLAY_GHOST_RADII: [f64; 4] = [6.0, 10.0, 30.0, 10000.0];
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -9,6 +18,106 @@ struct Cli {
     command: Command,
 }
 
+/// Periastron solver parameters, each overriding the value from the solver config file if given.
+#[derive(Args)]
+struct SolverArgs {
+    /// JSON file of periastron solver parameters, with any of the fields `tolerance`,
+    /// `max_iters`, `min_periastron`, `max_periastron` and `method`.
+    #[cfg(feature = "serde")]
+    #[arg(long)]
+    solver_config: Option<PathBuf>,
+
+    /// Solution tolerance for each photon's periastron, in units of black hole mass.
+    #[arg(long)]
+    solver_tolerance: Option<f64>,
+
+    /// Maximum number of iterations of the periastron solver.
+    #[arg(long)]
+    solver_max_iters: Option<usize>,
+
+    /// Minimum periastron to solve for, in units of black hole mass.
+    #[arg(long)]
+    min_periastron: Option<f64>,
+
+    /// Maximum periastron to solve for, as a multiple of the emission radius.
+    #[arg(long)]
+    max_periastron: Option<f64>,
+
+    /// Method used to solve for each photon's periastron.
+    #[arg(long)]
+    solver_method: Option<SolverMethodArg>,
+}
+
+/// Command line values of [`luminet_blackhole_lib::SolverMethod`].
+#[derive(Copy, Clone, ValueEnum)]
+enum SolverMethodArg {
+    /// Bisection.
+    Bisection,
+    /// Newton-Raphson iterations safeguarded by bisection.
+    Newton,
+}
+
+impl From<SolverMethodArg> for luminet_blackhole_lib::SolverMethod {
+    fn from(method: SolverMethodArg) -> Self {
+        match method {
+            SolverMethodArg::Bisection => Self::Bisection,
+            SolverMethodArg::Newton => Self::Newton,
+        }
+    }
+}
+
+impl SolverArgs {
+    /// The solver configuration from the config file, or the defaults, with any parameters given
+    /// on the command line overriding it.
+    fn solver_config(
+        &self,
+    ) -> Result<luminet_blackhole_lib::SolverConfig, Box<dyn std::error::Error>> {
+        #[cfg(feature = "serde")]
+        let mut config = match &self.solver_config {
+            Some(path) => serde_json::from_reader(std::io::BufReader::new(
+                std::fs::File::open(path).map_err(|err| {
+                    format!("failed to open solver config {}: {err}", path.display())
+                })?,
+            ))?,
+            None => luminet_blackhole_lib::SolverConfig::default(),
+        };
+        #[cfg(not(feature = "serde"))]
+        let mut config = luminet_blackhole_lib::SolverConfig::default();
+        if let Some(tolerance) = self.solver_tolerance {
+            config.tolerance = tolerance;
+        }
+        if let Some(max_iters) = self.solver_max_iters {
+            config.max_iters = max_iters;
+        }
+        if let Some(min_periastron) = self.min_periastron {
+            config.min_periastron = min_periastron;
+        }
+        if let Some(max_periastron) = self.max_periastron {
+            config.max_periastron = max_periastron;
+        }
+        if let Some(method) = self.solver_method {
+            config.method = method.into();
+        }
+
+        if !(config.tolerance.is_finite() && config.tolerance > 0.0) {
+            return Err(format!(
+                "solver tolerance must be positive, got {}",
+                config.tolerance
+            )
+            .into());
+        }
+        // The periastron equations have no real solution within twice the black hole's mass
+        if !(config.min_periastron > 2.0 && config.max_periastron > 0.0) {
+            return Err(format!(
+                "periastron range must be above 2 black hole masses, got {} to {}",
+                config.min_periastron, config.max_periastron
+            )
+            .into());
+        }
+        Ok(config)
+    }
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// Generate plots of isoradial curves.
@@ -26,13 +135,26 @@ enum Command {
         ghost_radii: Vec<f64>,
 
         /// Black hole's accretion rate.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE,
+            value_parser = parse_positive
+        )]
         accretion_rate: f64,
 
         /// Black hole's accretion disk outer edge.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE,
+            value_parser = parse_positive
+        )]
         disk_outer_edge: f64,
 
+        /// Factor to supersample the plot by in each dimension, to antialias the curves, from 1 to
+        /// 8.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..=8))]
+        supersample: u32,
+
         /// Output file path.
         path: PathBuf,
     },
@@ -56,14 +178,78 @@ enum Command {
         height: u32,
 
         /// Black hole's accretion rate.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE,
+            value_parser = parse_positive
+        )]
         accretion_rate: f64,
 
         /// Black hole's accretion disk outer edge.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE,
+            value_parser = parse_positive
+        )]
         disk_outer_edge: f64,
 
-        /// Output file path.
+        /// Render the accretion disk as optically thin, so the ghost image shows through it.
+        #[arg(long)]
+        transparent: bool,
+
+        /// Draw isoradial curves over the image.
+        #[arg(long)]
+        overlay_isoradials: bool,
+
+        /// Draw the photon ring, the boundary of the black hole's shadow, over the image.
+        #[arg(long)]
+        show_photon_ring: bool,
+
+        /// Luma value, from 0 to 65535, to draw the photon ring in.
+        #[arg(long, default_value_t = u16::MAX)]
+        photon_ring_color: u16,
+
+        /// Fraction of the maximum flux below which pixels are set to black.
+        #[arg(long, default_value_t = 0.0)]
+        min_flux: f64,
+
+        /// CSV file of `position,r,g,b` colormap stops, to output a color image.
+        #[arg(long)]
+        colormap_file: Option<PathBuf>,
+
+        /// Draw a colorbar labelled with the flux range to the right of the image.
+        #[arg(long)]
+        colorbar: bool,
+
+        /// Absolute flux mapped to white, instead of the maximum flux of the samples, so that
+        /// brightness is comparable between renders.
+        #[arg(long, visible_alias = "normalize-to")]
+        flux_max: Option<f64>,
+
+        /// Luma value, from 0 to 65535, of pixels where no image of the disk lies.
+        #[arg(long, default_value_t = 0)]
+        background: u16,
+
+        /// Render and write the image in horizontal strips of this many rows, to bound memory use
+        /// for very large images. Only supported for PNG output without overlays.
+        #[arg(long)]
+        tile_rows: Option<u32>,
+
+        /// Highest image order to render. Orders 2 and up add ever thinner rings just outside the
+        /// black hole's shadow.
+        #[arg(long, default_value_t = 1)]
+        order: u32,
+
+        /// Number of samples of the ghost image and higher orders, as a multiple of `--samples`,
+        /// to sample their thin rings more densely.
+        #[arg(long, default_value_t = 1.0, value_parser = parse_non_negative)]
+        ghost_sample_multiplier: f64,
+
+        #[command(flatten)]
+        solver: SolverArgs,
+
+        /// Output file path, or `-` to write PNG bytes to stdout. Paths ending in `.fits` are
+        /// written as 16-bit FITS files.
         path: PathBuf,
     },
 
@@ -94,14 +280,22 @@ enum Command {
         height: u32,
 
         /// Black hole's accretion rate.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_ACCRETION_RATE,
+            value_parser = parse_positive
+        )]
         accretion_rate: f64,
 
         /// Black hole's accretion disk outer edge.
-        #[arg(long, default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE)]
+        #[arg(
+            long,
+            default_value_t = luminet_blackhole_lib::DEFAULT_DISK_OUTER_EDGE,
+            value_parser = parse_positive
+        )]
         disk_outer_edge: f64,
 
-        /// Output directory path.
+        /// Output directory path (created if it does not exist).
         directory: PathBuf,
 
         /// Output filename prefix.
@@ -120,6 +314,137 @@ enum Command {
         /// Output image path.
         output_path: PathBuf,
     },
+
+    /// Dither an image with every algorithm, and tile the results for comparison.
+    DitherCompare {
+        /// Input image path.
+        input_path: PathBuf,
+
+        /// Output image path.
+        output_path: PathBuf,
+    },
+}
+
+/// Construct a unit mass black hole, reporting any invalid parameters.
+fn new_blackhole(
+    accretion_rate: f64,
+    disk_outer_edge: f64,
+) -> Result<luminet_blackhole_lib::BlackHole, String> {
+    luminet_blackhole_lib::BlackHole::try_new(1.0, accretion_rate, disk_outer_edge)
+        .map_err(|err| err.to_string())
+}
+
+/// The flux range mapped to black and white for a fixed maximum flux, if given.
+fn flux_range_from_max(flux_max: Option<f64>) -> Result<Option<RangeInclusive<f64>>, String> {
+    match flux_max {
+        Some(flux_max) if !(flux_max.is_finite() && flux_max > 0.0) => {
+            Err(format!("maximum flux must be positive, got {flux_max}"))
+        }
+        flux_max => Ok(flux_max.map(|flux_max| 0.0..=flux_max)),
+    }
+}
+
+/// Parse a finite, positive number from a command line argument.
+fn parse_positive(arg: &str) -> Result<f64, String> {
+    let value = arg.parse::<f64>().map_err(|err| err.to_string())?;
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("must be finite and positive, got {value}"))
+    }
+}
+
+/// Parse a finite, non-negative number from a command line argument.
+fn parse_non_negative(arg: &str) -> Result<f64, String> {
+    let value = arg.parse::<f64>().map_err(|err| err.to_string())?;
+    if value.is_finite() && value >= 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("must be finite and non-negative, got {value}"))
+    }
+}
+
+/// Save an image to the given path, or write it to stdout as a PNG if the path is `-`.
+fn save_image<P>(
+    img: &image::ImageBuffer<P, Vec<P::Subpixel>>,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: image::EncodableLayout,
+{
+    if path == Path::new("-") {
+        let mut stdout = std::io::stdout().lock();
+        write_png(img, &mut stdout)?;
+        stdout.flush()?;
+    } else {
+        img.save(path)?;
+    }
+    Ok(())
+}
+
+/// Render an image of the observed flux in strips of `tile_rows` rows, writing each to the given
+/// writer in PNG format as it's rendered, and colored by the colormap if given.
+fn write_tiled_png<W: Write>(
+    interpolator: &luminet_blackhole_lib::plotting::FluxInterpolator,
+    image_width: u32,
+    image_height: u32,
+    tile_rows: u32,
+    options: &luminet_blackhole_lib::plotting::FluxImageOptions,
+    colormap: Option<&luminet_blackhole_lib::plotting::Colormap>,
+    writer: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if tile_rows == 0 {
+        return Err("tile rows must be positive".into());
+    }
+    let mut encoder = png::Encoder::new(writer, image_width, image_height);
+    encoder.set_color(if colormap.is_some() {
+        png::ColorType::Rgb
+    } else {
+        png::ColorType::Grayscale
+    });
+    encoder.set_depth(png::BitDepth::Sixteen);
+    let mut png_writer = encoder.write_header()?;
+    let mut stream = png_writer.stream_writer()?;
+    for first_row in (0..image_height).step_by(tile_rows as usize) {
+        let mut strip = luminet_blackhole_lib::plotting::Luma16Image::new(
+            image_width,
+            tile_rows.min(image_height - first_row),
+        );
+        luminet_blackhole_lib::plotting::render_rows_into(
+            interpolator,
+            &mut strip,
+            image_width,
+            image_height,
+            first_row,
+            options,
+        )?;
+        let values = match colormap {
+            Some(colormap) => colormap.apply(&strip).into_raw(),
+            None => strip.into_raw(),
+        };
+        // PNG stores 16-bit samples big-endian
+        let bytes = values
+            .iter()
+            .flat_map(|value| value.to_be_bytes())
+            .collect::<Vec<u8>>();
+        stream.write_all(&bytes)?;
+    }
+    stream.finish()?;
+    png_writer.finish()?;
+    Ok(())
+}
+
+/// Write an image to the given writer in PNG format.
+fn write_png<P, W: Write>(
+    img: &image::ImageBuffer<P, Vec<P::Subpixel>>,
+    writer: W,
+) -> image::ImageResult<()>
+where
+    P: image::PixelWithColorType,
+    [P::Subpixel]: image::EncodableLayout,
+{
+    img.write_with_encoder(image::codecs::png::PngEncoder::new(writer))
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -132,10 +457,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ghost_radii,
             accretion_rate,
             disk_outer_edge,
+            supersample,
             path,
         } => {
-            let blackhole =
-                luminet_blackhole_lib::BlackHole::new(1.0, accretion_rate, disk_outer_edge);
+            let blackhole = new_blackhole(accretion_rate, disk_outer_edge)?;
             let radii = direct_radii
                 .iter()
                 .map(|&r| (r, 0))
@@ -145,6 +470,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 &blackhole,
                 Deg(inclination),
                 &radii,
+                supersample,
                 path,
             )?;
         }
@@ -155,19 +481,175 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             height,
             accretion_rate,
             disk_outer_edge,
+            transparent,
+            overlay_isoradials,
+            show_photon_ring,
+            photon_ring_color,
+            min_flux,
+            colormap_file,
+            colorbar,
+            flux_max,
+            background,
+            tile_rows,
+            order,
+            ghost_sample_multiplier,
+            solver,
             path,
         } => {
-            let blackhole =
-                luminet_blackhole_lib::BlackHole::new(1.0, accretion_rate, disk_outer_edge);
-            let img = luminet_blackhole_lib::plotting::generate_flux_image(
+            let blackhole = new_blackhole(accretion_rate, disk_outer_edge)?;
+            let options = luminet_blackhole_lib::plotting::FluxImageOptions {
+                flux_range: flux_range_from_max(flux_max)?,
+                opacity: if transparent {
+                    luminet_blackhole_lib::plotting::DiskOpacity::Transparent
+                } else {
+                    luminet_blackhole_lib::plotting::DiskOpacity::Opaque
+                },
+                min_flux_fraction: min_flux,
+                background,
+                solver: solver.solver_config()?,
+                max_order: order,
+                ghost_sample_multiplier,
+                ..Default::default()
+            };
+            if colorbar {
+                if overlay_isoradials
+                    || show_photon_ring
+                    || tile_rows.is_some()
+                    || path.extension().is_some_and(|ext| ext == "fits")
+                {
+                    return Err(
+                        "--colorbar can't be combined with overlays, tiling or FITS output".into(),
+                    );
+                }
+                let colormap = match colormap_file {
+                    Some(colormap_file) => {
+                        luminet_blackhole_lib::plotting::Colormap::from_csv(colormap_file)?
+                    }
+                    None => luminet_blackhole_lib::plotting::FluxColormap::Grayscale
+                        .colormap()
+                        .expect("grayscale has a colormap"),
+                };
+                let img = luminet_blackhole_lib::plotting::generate_flux_image_with_colorbar(
+                    &blackhole,
+                    Deg(inclination),
+                    samples,
+                    width,
+                    height,
+                    &colormap,
+                    &options,
+                )?;
+                save_image(&img, &path)?;
+                return Ok(());
+            }
+            if let Some(tile_rows) = tile_rows {
+                if overlay_isoradials
+                    || show_photon_ring
+                    || path.extension().is_some_and(|ext| ext == "fits")
+                {
+                    return Err("--tile-rows can't be combined with overlays or FITS output".into());
+                }
+                let colormap = colormap_file
+                    .map(luminet_blackhole_lib::plotting::Colormap::from_csv)
+                    .transpose()?;
+                let inclination = Rad::from(Deg(inclination));
+                let sampling_options = luminet_blackhole_lib::SamplingOptions {
+                    solver: options.solver,
+                    ..Default::default()
+                };
+                let mut order_samples = (0..=order)
+                    .map(|order| {
+                        Ok(blackhole.sample_flux_at_points_with_options(
+                            inclination,
+                            options.order_sample_count(samples, order)?,
+                            order,
+                            &sampling_options,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+                let interpolator = luminet_blackhole_lib::plotting::FluxInterpolator::with_orders(
+                    &blackhole,
+                    inclination,
+                    order_samples.iter_mut().map(Vec::as_mut_slice).collect(),
+                    options.max_impact_parameter,
+                )?;
+                if path == Path::new("-") {
+                    let mut stdout = std::io::stdout().lock();
+                    write_tiled_png(
+                        &interpolator,
+                        width,
+                        height,
+                        tile_rows,
+                        &options,
+                        colormap.as_ref(),
+                        &mut stdout,
+                    )?;
+                    stdout.flush()?;
+                } else {
+                    write_tiled_png(
+                        &interpolator,
+                        width,
+                        height,
+                        tile_rows,
+                        &options,
+                        colormap.as_ref(),
+                        std::io::BufWriter::new(std::fs::File::create(&path)?),
+                    )?;
+                }
+                return Ok(());
+            }
+            let mut img = luminet_blackhole_lib::plotting::generate_flux_image(
                 &blackhole,
                 Deg(inclination),
                 samples,
                 width,
                 height,
-                None,
+                &options,
             )?;
-            img.save(path)?;
+            let units_per_pixel = luminet_blackhole_lib::plotting::image_units_per_pixel(
+                &blackhole,
+                Deg(inclination),
+                width,
+            );
+            if overlay_isoradials {
+                let radii = OVERLAY_DIRECT_RADII
+                    .iter()
+                    .map(|&r| (r, 0))
+                    .chain(OVERLAY_GHOST_RADII.iter().map(|&r| (r, 1)))
+                    .collect::<Vec<(f64, u32)>>();
+                luminet_blackhole_lib::plotting::overlay_isoradials(
+                    &mut img,
+                    &blackhole,
+                    Deg(inclination),
+                    &radii,
+                    units_per_pixel,
+                    u16::MAX / 2,
+                );
+            }
+            if show_photon_ring {
+                luminet_blackhole_lib::plotting::overlay_photon_ring(
+                    &mut img,
+                    &blackhole,
+                    units_per_pixel,
+                    photon_ring_color,
+                );
+            }
+            if let Some(colormap_file) = colormap_file {
+                let colormap = luminet_blackhole_lib::plotting::Colormap::from_csv(colormap_file)?;
+                save_image(&colormap.apply(&img), &path)?;
+            } else if path.extension().is_some_and(|ext| ext == "fits") {
+                luminet_blackhole_lib::plotting::save_fits(
+                    &img,
+                    &path,
+                    &[
+                        ("INCLIN".to_string(), inclination.to_string()),
+                        ("MASS".to_string(), blackhole.mass.to_string()),
+                        ("ACCRATE".to_string(), accretion_rate.to_string()),
+                        ("DISKOUT".to_string(), disk_outer_edge.to_string()),
+                    ],
+                )?;
+            } else {
+                save_image(&img, &path)?;
+            }
         }
         Command::FluxRange {
             start,
@@ -181,7 +663,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             directory,
             filename_prefix,
         } => {
-            assert!(directory.is_dir(), "`directory` must be a directory");
+            std::fs::create_dir_all(&directory).map_err(|err| {
+                format!(
+                    "failed to create output directory {}: {err}",
+                    directory.display()
+                )
+            })?;
 
             let inclinations = {
                 let mut inclinations = Vec::new();
@@ -192,8 +679,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
                 inclinations
             };
-            let blackhole =
-                luminet_blackhole_lib::BlackHole::new(1.0, accretion_rate, disk_outer_edge);
+            let blackhole = new_blackhole(accretion_rate, disk_outer_edge)?;
             let images = luminet_blackhole_lib::plotting::generate_flux_images_inclinations(
                 &blackhole,
                 samples,
@@ -221,7 +707,243 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             luminet_blackhole_lib::plotting::dither(algorithm, &mut img);
             img.save(&output_path)?;
         }
+        Command::DitherCompare {
+            input_path,
+            output_path,
+        } => {
+            let dynamic_img = image::io::Reader::open(input_path)?.decode()?;
+            let img = dynamic_img.to_luma16();
+            let sheet = luminet_blackhole_lib::plotting::dither_contact_sheet(&img)?;
+            sheet.save(&output_path)?;
+        }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{flux_range_from_max, write_png, write_tiled_png, Cli, Command, SolverArgs};
+    use cgmath::{Deg, Rad};
+    use clap::Parser;
+    use luminet_blackhole_lib::{
+        plotting::{
+            generate_flux_image_from_samples, render_into, FluxImageOptions, FluxInterpolator,
+            Luma16Image,
+        },
+        BlackHole, IsoRadial, SolverConfig, SolverMethod, DEFAULT_DISK_OUTER_EDGE,
+    };
+
+    /// Parse the solver arguments of a `flux` command with the given options.
+    fn parse_solver_args(options: &[&str]) -> SolverArgs {
+        let args = ["luminet_blackhole", "flux"]
+            .iter()
+            .chain(options)
+            .chain(&["out.png"]);
+        match Cli::try_parse_from(args).unwrap().command {
+            Command::Flux { solver, .. } => solver,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_flux_max_fixes_brightness() {
+        assert_eq!(flux_range_from_max(None), Ok(None));
+        assert!(flux_range_from_max(Some(0.0)).is_err());
+
+        let inclination = Deg(80.0).into();
+        let dim = BlackHole::default();
+        let bright = BlackHole::new(1.0, 2.0 * dim.accretion_rate, DEFAULT_DISK_OUTER_EDGE);
+        let flux_max = 4.0
+            * bright
+                .sample_flux_at_points_seeded(inclination, 1000, 0, 0)
+                .iter()
+                .map(|sample| sample.observed_flux)
+                .fold(0.0, f64::max);
+        let render = |blackhole: &BlackHole, options: &FluxImageOptions| {
+            generate_flux_image_from_samples(
+                blackhole,
+                inclination,
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 1000, 0, 1),
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 1000, 1, 2),
+                48,
+                24,
+                options,
+            )
+            .unwrap()
+        };
+        let total_brightness =
+            |img: &Luma16Image| img.pixels().map(|p| f64::from(p.0[0])).sum::<f64>();
+
+        // Normalizing each render to its own maximum hides the difference in accretion rate
+        let options = FluxImageOptions::default();
+        assert_eq!(render(&dim, &options), render(&bright, &options));
+
+        // A fixed maximum keeps brightness proportional to the absolute flux
+        let options = FluxImageOptions {
+            flux_range: flux_range_from_max(Some(flux_max)).unwrap(),
+            ..Default::default()
+        };
+        let ratio = total_brightness(&render(&bright, &options))
+            / total_brightness(&render(&dim, &options));
+        assert!((ratio - 2.0).abs() < 1e-2, "brightness ratio {ratio}");
+    }
+
+    #[test]
+    fn test_tiled_render_matches_full_render() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(80.0).into();
+        let mut direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 1000, 0, 0);
+        let mut ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 1000, 1, 1);
+        let interpolator = FluxInterpolator::new(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )
+        .unwrap();
+        let options = FluxImageOptions::default();
+        let (width, height) = (48, 30);
+        let mut full = Luma16Image::new(width, height);
+        render_into(&interpolator, &mut full, width, height, &options).unwrap();
+
+        // Strips which don't evenly divide the image height
+        let mut bytes = Vec::new();
+        write_tiled_png(&interpolator, width, height, 7, &options, None, &mut bytes).unwrap();
+        let tiled = image::load_from_memory(&bytes).unwrap().into_luma16();
+        assert_eq!(tiled, full);
+
+        assert!(
+            write_tiled_png(&interpolator, width, height, 0, &options, None, Vec::new()).is_err()
+        );
+    }
+
+    #[test]
+    fn test_write_png() {
+        let img = Luma16Image::from_fn(8, 4, |col, row| image::Luma([(col * row) as u16 * 1000]));
+        let mut bytes = Vec::new();
+        write_png(&img, &mut bytes).unwrap();
+        assert!(bytes.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let decoded = image::load_from_memory(&bytes).unwrap().into_luma16();
+        assert_eq!(decoded, img);
+    }
+
+    #[test]
+    fn test_blackhole_args() {
+        for command in [
+            &["isoradials", "out.png"][..],
+            &["flux", "out.png"],
+            &["flux-range", "out", "flux"],
+        ] {
+            for option in ["--accretion-rate", "--disk-outer-edge"] {
+                let parse = |value: &str| {
+                    let arg = format!("{option}={value}");
+                    Cli::try_parse_from(
+                        ["luminet_blackhole", command[0], &arg]
+                            .into_iter()
+                            .chain(command[1..].iter().copied()),
+                    )
+                };
+                assert!(parse("10").is_ok());
+                for value in ["0", "-1", "NaN", "inf"] {
+                    assert!(parse(value).is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_supersample_arg() {
+        let parse = |supersample: &str| {
+            let arg = format!("--supersample={supersample}");
+            Cli::try_parse_from(["luminet_blackhole", "isoradials", &arg, "out.png"])
+        };
+        assert!(parse("1").is_ok());
+        assert!(parse("8").is_ok());
+        for supersample in ["0", "9", "4294967295", "-1"] {
+            assert!(parse(supersample).is_err());
+        }
+    }
+
+    #[test]
+    fn test_ghost_sample_multiplier_arg() {
+        let parse = |multiplier: &str| {
+            let arg = format!("--ghost-sample-multiplier={multiplier}");
+            Cli::try_parse_from(["luminet_blackhole", "flux", &arg, "out.png"]).map(|cli| match cli
+                .command
+            {
+                Command::Flux {
+                    ghost_sample_multiplier,
+                    ..
+                } => ghost_sample_multiplier,
+                _ => unreachable!(),
+            })
+        };
+        assert_eq!(parse("2.5").unwrap(), 2.5);
+        assert_eq!(parse("0").unwrap(), 0.0);
+        for multiplier in ["-1", "NaN", "inf", "many"] {
+            assert!(parse(multiplier).is_err());
+        }
+    }
+
+    #[test]
+    fn test_solver_args() {
+        assert_eq!(
+            parse_solver_args(&[]).solver_config().unwrap(),
+            SolverConfig::default()
+        );
+        assert_eq!(
+            parse_solver_args(&["--solver-method", "newton"])
+                .solver_config()
+                .unwrap()
+                .method,
+            SolverMethod::Newton
+        );
+
+        // A coarser tolerance shows up in the solved impact parameters
+        let config = parse_solver_args(&["--solver-tolerance", "1e-2"])
+            .solver_config()
+            .unwrap();
+        assert_eq!(config.tolerance, 1e-2);
+        let isoradial = IsoRadial::new(&BlackHole::default(), 20.0, 0);
+        let inclination = Rad::from(Deg(80.0));
+        let coarse = isoradial.calculate_coordinates(inclination, 8, &config);
+        let fine = isoradial.calculate_coordinates(inclination, 8, &SolverConfig::default());
+        assert_ne!(coarse, fine);
+        for (coarse, fine) in coarse.iter().zip(&fine) {
+            assert!((coarse.x - fine.x).abs() < 2e-2 && (coarse.y - fine.y).abs() < 2e-2);
+        }
+
+        #[cfg(feature = "serde")]
+        {
+            // Options on the command line override those in the config file
+            let directory = tempfile::tempdir().unwrap();
+            let path = directory.path().join("solver_config.json");
+            std::fs::write(&path, r#"{"max_iters": 5, "method": "newton"}"#).unwrap();
+            let config = parse_solver_args(&[
+                "--solver-config",
+                path.to_str().unwrap(),
+                "--solver-max-iters",
+                "7",
+            ])
+            .solver_config()
+            .unwrap();
+            assert_eq!(
+                config,
+                SolverConfig {
+                    max_iters: 7,
+                    method: SolverMethod::Newton,
+                    ..Default::default()
+                }
+            );
+        }
+
+        assert!(parse_solver_args(&["--solver-tolerance", "0"])
+            .solver_config()
+            .is_err());
+        assert!(parse_solver_args(&["--min-periastron", "1.5"])
+            .solver_config()
+            .is_err());
+    }
+}
@@ -1,16 +1,376 @@
 use crate::{
-    equations::{calc_observed_flux, calc_redshift_factor},
-    solvers::calc_impact_parameter,
-    IsoRadial, Sample,
+    equations::{
+        calc_cos_gamma, calc_doppler_factor, calc_gravitational_redshift, calc_inner_edge_taper,
+        calc_intrinsic_flux, calc_isco_radius, calc_marginally_bound_radius, calc_observed_flux,
+        calc_observed_monochromatic_flux, calc_peak_intrinsic_flux_radius,
+        calc_power_law_intrinsic_flux, calc_redshift_factor, calc_temperature, ellipse,
+    },
+    interpolation::{image_order_at, DiskOpacity, FluxInterpolator},
+    observer_area_weights, plotting,
+    solvers::{
+        calc_impact_parameter, try_calc_impact_parameter_checked, FallbackMode,
+        ImpactParameterCache, ImpactParameterSource, SolverConfig,
+    },
+    Hotspot, IsoRadial, Sample,
 };
-use cgmath::Rad;
+use cgmath::{Angle, Deg, Rad, Vector2};
 use rand::{distributions::Uniform, prelude::*};
 use rayon::prelude::*;
-use std::f64::consts::PI;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, ops::RangeInclusive};
 
 pub const DEFAULT_ACCRETION_RATE: f64 = 10e-8;
 pub const DEFAULT_DISK_OUTER_EDGE: f64 = 50.0;
 
+/// The inclination of the observer in the paper's simulated photograph, 10 degrees above the plane
+/// of the accretion disk.
+pub const LUMINET_1979_INCLINATION: Deg<f64> = Deg(80.0);
+
+/// Number of points sampled from each independently seeded random number generator when sampling
+/// with a fixed seed.
+const SEEDED_SAMPLING_CHUNK_SIZE: usize = 1024;
+
+/// Radial step, in units of black hole mass, used to differentiate the impact parameter when
+/// calculating magnification.
+const MAGNIFICATION_RADIUS_STEP: f64 = 1e-2;
+
+/// Smallest unlensed impact parameter, as a fraction of the radius, used when calculating
+/// magnification, so that it stays finite where the unlensed disk collapses onto a line.
+const MAGNIFICATION_MIN_NEWTONIAN_FRACTION: f64 = 1e-6;
+
+/// Number of points around the edge of a hotspot at which its observed flux is evaluated, in
+/// addition to its centre.
+const HOTSPOT_EDGE_POINTS: usize = 8;
+
+/// Number of angular bins to average over when measuring the width of the photon ring.
+const PHOTON_RING_ANGLE_BINS: usize = 36;
+/// Width, in units of black hole mass, of the band of impact parameters just outside the photon
+/// ring over which the direct image's flux is averaged when measuring the ring's enhancement.
+const PHOTON_RING_ADJACENT_WIDTH: f64 = 2.0;
+
+/// Number of steps to march along each line of sight when testing for self-shadowing.
+const SELF_SHADOWING_STEPS: usize = 512;
+/// Number of bisection iterations when inverting the apparent inner edge for a disk radius.
+const INNERMOST_VISIBLE_RADIUS_ITERS: usize = 60;
+
+/// An error from constructing a black hole with invalid parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlackHoleError {
+    /// The mass is not positive.
+    InvalidMass { mass: f64 },
+    /// The accretion rate is not positive.
+    InvalidAccretionRate { accretion_rate: f64 },
+    /// The outer edge of the accretion disk is not outside its inner edge, in units of black
+    /// hole mass.
+    InvalidDiskOuterEdge {
+        disk_outer_edge: f64,
+        disk_inner_edge: f64,
+    },
+    /// The dimensionless spin is outside `[0, 1)`.
+    InvalidSpin { spin: f64 },
+    /// The inner edge of the accretion disk is inside the marginally bound orbit, in units of black
+    /// hole mass.
+    InvalidDiskInnerEdge {
+        disk_inner_edge: f64,
+        marginally_bound_radius: f64,
+    },
+}
+
+impl std::fmt::Display for BlackHoleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlackHoleError::InvalidMass { mass } => write!(f, "mass ({mass}) must be positive"),
+            BlackHoleError::InvalidAccretionRate { accretion_rate } => {
+                write!(f, "accretion rate ({accretion_rate}) must be positive")
+            }
+            BlackHoleError::InvalidDiskOuterEdge {
+                disk_outer_edge,
+                disk_inner_edge,
+            } => write!(
+                f,
+                "disk outer edge ({disk_outer_edge}) must be greater than the disk inner edge \
+                 ({disk_inner_edge})"
+            ),
+            BlackHoleError::InvalidSpin { spin } => {
+                write!(f, "spin ({spin}) must be at least 0 and less than 1")
+            }
+            BlackHoleError::InvalidDiskInnerEdge {
+                disk_inner_edge,
+                marginally_bound_radius,
+            } => write!(
+                f,
+                "disk inner edge ({disk_inner_edge}) must not be inside the marginally bound orbit \
+                 ({marginally_bound_radius})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BlackHoleError {}
+
+/// A builder for a [`BlackHole`], starting from the defaults of [`BlackHole::default`] and
+/// validating the parameters on [`build`](BlackHoleBuilder::build).
+///
+/// This is also the serialized form of a [`BlackHole`], with any missing parameters taking their
+/// defaults.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct BlackHoleBuilder {
+    mass: f64,
+    accretion_rate: f64,
+    disk_outer_edge: f64,
+    spin: f64,
+    disk_inner_edge: Option<f64>,
+}
+
+impl Default for BlackHoleBuilder {
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            accretion_rate: DEFAULT_ACCRETION_RATE,
+            disk_outer_edge: DEFAULT_DISK_OUTER_EDGE,
+            spin: 0.0,
+            disk_inner_edge: None,
+        }
+    }
+}
+
+impl BlackHoleBuilder {
+    /// Set the black hole's mass.
+    #[must_use]
+    pub fn mass(mut self, mass: f64) -> Self {
+        self.mass = mass;
+        self
+    }
+
+    /// Set the accretion rate.
+    #[must_use]
+    pub fn accretion_rate(mut self, accretion_rate: f64) -> Self {
+        self.accretion_rate = accretion_rate;
+        self
+    }
+
+    /// Set the outer edge of the accretion disk, in units of black hole mass.
+    #[must_use]
+    pub fn disk_outer_edge(mut self, disk_outer_edge: f64) -> Self {
+        self.disk_outer_edge = disk_outer_edge;
+        self
+    }
+
+    /// Set the dimensionless spin `a/M`, as per [`BlackHole::new_kerr`].
+    #[must_use]
+    pub fn spin(mut self, spin: f64) -> Self {
+        self.spin = spin;
+        self
+    }
+
+    /// Set the inner edge of the accretion disk, in units of black hole mass, instead of the
+    /// innermost stable circular orbit, for a truncated disk.
+    ///
+    /// The disk only radiates outside the innermost stable circular orbit, so an inner edge inside
+    /// it adds a dark band to the disk. It may not be inside the marginally bound orbit, `4M` for a
+    /// non-rotating black hole.
+    #[must_use]
+    pub fn disk_inner_edge(mut self, disk_inner_edge: f64) -> Self {
+        self.disk_inner_edge = Some(disk_inner_edge);
+        self
+    }
+
+    /// Construct the black hole, as per [`BlackHole::try_new_kerr`], also checking that the disk
+    /// inner edge, if set, is valid.
+    pub fn build(self) -> Result<BlackHole, BlackHoleError> {
+        BlackHole::try_new_with_inner_edge(
+            self.mass,
+            self.accretion_rate,
+            self.disk_outer_edge,
+            self.spin,
+            self.disk_inner_edge,
+        )
+    }
+}
+
+/// The radial profile of the disk's intrinsic flux.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub enum EmissionModel {
+    /// The analytic thin disk profile of eqn 15, or of Page & Thorne for a rotating black hole.
+    #[default]
+    NovikovThorne,
+    /// A phenomenological power law `F_s ∝ r^-index` outside `r_in`, in units of black hole mass,
+    /// as often fitted to observations. It is normalized to match the peak of the thin disk profile
+    /// at `r_in`.
+    PowerLaw { index: f64, r_in: f64 },
+}
+
+impl EmissionModel {
+    /// The intrinsic flux `F_s` of the disk at the given radius, for a disk whose thin disk profile
+    /// peaks at `peak_flux_radius`.
+    #[must_use]
+    pub fn intrinsic_flux(
+        self,
+        radius: f64,
+        accretion_rate: f64,
+        mass: f64,
+        spin: f64,
+        peak_flux_radius: f64,
+    ) -> f64 {
+        match self {
+            EmissionModel::NovikovThorne => calc_intrinsic_flux(radius, accretion_rate, mass, spin),
+            EmissionModel::PowerLaw { index, r_in } => calc_power_law_intrinsic_flux(
+                radius,
+                accretion_rate,
+                mass,
+                spin,
+                peak_flux_radius,
+                index,
+                r_in * mass,
+            ),
+        }
+    }
+}
+
+/// How the points at which the disk is sampled are distributed over its radius and angle `alpha`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SamplingStrategy {
+    /// Independent uniformly random points, which leave clumps and voids.
+    #[default]
+    Random,
+    /// A lattice of cells over radius and angle, with one point jittered randomly within each. The
+    /// lattice has as many cells as fit in the number of points, so a few fewer points may be
+    /// sampled.
+    StratifiedGrid,
+    /// The first points of the two dimensional Sobol low-discrepancy sequence, which is
+    /// deterministic.
+    Sobol,
+}
+
+impl SamplingStrategy {
+    /// Generate up to `num_points` points as `(radius, alpha)` within the given ranges.
+    fn points<R: Rng>(
+        self,
+        num_points: usize,
+        radius_range: &RangeInclusive<f64>,
+        alpha_range: &RangeInclusive<f64>,
+        rng: &mut R,
+    ) -> Vec<(f64, f64)> {
+        let (radius_start, radius_span) = (
+            *radius_range.start(),
+            radius_range.end() - radius_range.start(),
+        );
+        let (alpha_start, alpha_span) = (
+            *alpha_range.start(),
+            alpha_range.end() - alpha_range.start(),
+        );
+        let to_point =
+            |u: f64, v: f64| (radius_start + u * radius_span, alpha_start + v * alpha_span);
+        match self {
+            SamplingStrategy::Random => (0..num_points)
+                .map(|_| to_point(rng.gen(), rng.gen()))
+                .collect(),
+            SamplingStrategy::StratifiedGrid => {
+                #[allow(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    clippy::cast_precision_loss
+                )]
+                let radial_cells = ((num_points as f64).sqrt() as usize).max(1);
+                let angular_cells = num_points / radial_cells;
+                (0..radial_cells)
+                    .flat_map(|i| (0..angular_cells).map(move |j| (i, j)))
+                    .map(|(i, j)| {
+                        to_point(
+                            (i as f64 + rng.gen::<f64>()) / radial_cells as f64,
+                            (j as f64 + rng.gen::<f64>()) / angular_cells as f64,
+                        )
+                    })
+                    .collect()
+            }
+            SamplingStrategy::Sobol => (0..num_points)
+                .map(|index| {
+                    let (u, v) = sobol_2d(index as u32);
+                    to_point(u, v)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The range of angles `alpha` spanned by the wedge of the disk with the given azimuth range, as
+/// per [`SamplingOptions::azimuth_range`]. The range starts within `[0, 2 pi)` and is at most `2 pi`
+/// wide, so its end may be beyond `2 pi`.
+fn azimuth_wedge(azimuth_range: Option<&RangeInclusive<f64>>) -> RangeInclusive<f64> {
+    match azimuth_range {
+        Some(range) if range.start().is_finite() && range.end().is_finite() => {
+            let start = range.start().rem_euclid(2.0 * PI);
+            let width = range.end() - range.start();
+            let width = if width >= 2.0 * PI {
+                2.0 * PI
+            } else {
+                width.rem_euclid(2.0 * PI)
+            };
+            start..=start + width
+        }
+        _ => 0.0..=2.0 * PI,
+    }
+}
+
+/// The point at the given index of the first two dimensions of the Sobol sequence, in `[0, 1)^2`.
+///
+/// The first dimension is the base 2 van der Corput sequence, and the second uses the primitive
+/// polynomial `x + 1`, whose direction numbers are each the previous one XORed with itself
+/// shifted right by one.
+fn sobol_2d(index: u32) -> (f64, f64) {
+    let (mut x, mut y) = (0_u32, 0_u32);
+    let mut direction = 1_u32 << 31;
+    for bit in 0..32 {
+        if index & (1 << bit) != 0 {
+            x ^= 1 << (31 - bit);
+            y ^= direction;
+        }
+        direction ^= direction >> 1;
+    }
+    let scale = 2.0_f64.powi(-32);
+    (f64::from(x) * scale, f64::from(y) * scale)
+}
+
+/// Options for sampling the observed flux from the accretion disk.
+#[derive(Debug, Default, Clone)]
+pub struct SamplingOptions {
+    /// What to do when no periastron solution can be found for a sample. Samples without a
+    /// solution are dropped under `FallbackMode::None`.
+    pub fallback: FallbackMode,
+    /// The half-thickness of the accretion disk as a fraction of radius, so that the disk flares
+    /// outwards with its emitting surface at height `disk_half_thickness * r`. The disk is still
+    /// imaged as thin, so this only has an effect with `self_shadowing`.
+    pub disk_half_thickness: f64,
+    /// Zero the flux of direct image samples whose line of sight to the observer is blocked by
+    /// nearer disk material, or whose emitting surface faces away from the observer.
+    pub self_shadowing: bool,
+    /// The width, in units of black hole mass, of a band just outside the inner edge of the disk
+    /// over which its emission ramps linearly up from zero. Zero gives a hard inner edge.
+    pub inner_edge_taper: f64,
+    /// The configuration of the solver for each sample's periastron.
+    pub solver: SolverConfig,
+    /// The range of angles `alpha`, in radians, to sample the disk over, so that only a wedge of
+    /// it is imaged. The wedge runs from the start of the range in the direction of increasing
+    /// `alpha` to its end, wrapping through `2 pi`, so e.g. `3 pi / 2..=pi / 2` covers the half of
+    /// the disk either side of `alpha = 0`. If `None`, or either bound isn't finite, the whole disk
+    /// is sampled.
+    pub azimuth_range: Option<RangeInclusive<f64>>,
+    /// Leave Doppler beaming out of the observed flux, so that only the gravitational redshift
+    /// applies and the approaching and receding sides of the disk are equally bright. The samples'
+    /// redshift factors are then the gravitational redshift alone.
+    pub no_doppler_beaming: bool,
+    /// The radial profile of the disk's intrinsic flux, which also sets the samples' temperatures.
+    pub emission: EmissionModel,
+    /// How the sampled points are distributed over the disk.
+    pub strategy: SamplingStrategy,
+}
+
 /// A black hole with with a thin accretion disk.
 pub struct BlackHole {
     /// Black hole mass.
@@ -19,26 +379,149 @@ pub struct BlackHole {
     pub accretion_rate: f64,
     /// The outer edge of the accretion disk, in units of black hole mass.
     disk_outer_edge: f64,
+    /// The inner edge of the accretion disk, in units of black hole mass. This is the innermost
+    /// stable circular orbit unless set otherwise with [`BlackHoleBuilder::disk_inner_edge`].
+    disk_inner_edge: f64,
+    /// Dimensionless spin `a/M`, with the disk orbiting in the same direction.
+    spin: f64,
+    /// The radius at which the intrinsic flux of the disk peaks, in units of black hole mass,
+    /// found on construction as it depends on the spin.
+    peak_flux_radius: f64,
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for BlackHole {
+    /// Serialize the black hole's parameters as those of its builder, so that they're validated
+    /// on deserialization.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BlackHoleBuilder {
+            mass: self.mass,
+            accretion_rate: self.accretion_rate,
+            disk_outer_edge: self.disk_outer_edge,
+            spin: self.spin,
+            disk_inner_edge: Some(self.disk_inner_edge),
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for BlackHole {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BlackHoleBuilder::deserialize(deserializer)?
+            .build()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 impl Default for BlackHole {
     fn default() -> Self {
-        Self {
-            mass: 1.0,
-            accretion_rate: DEFAULT_ACCRETION_RATE,
-            disk_outer_edge: DEFAULT_DISK_OUTER_EDGE,
-        }
+        BlackHole::new(1.0, DEFAULT_ACCRETION_RATE, DEFAULT_DISK_OUTER_EDGE)
     }
 }
 
 impl BlackHole {
     #[must_use]
     pub fn new(mass: f64, accretion_rate: f64, disk_outer_edge: f64) -> Self {
+        BlackHole::new_kerr(mass, accretion_rate, disk_outer_edge, 0.0)
+    }
+
+    /// Construct a rotating black hole with the given dimensionless spin `a/M`, in `[0, 1)`, with
+    /// the accretion disk orbiting in the same direction and extending inwards to the innermost
+    /// stable circular orbit.
+    ///
+    /// Only the disk depends on spin: its inner edge at the innermost stable circular orbit, its
+    /// intrinsic flux and the redshift of its orbiting material. This is not Kerr ray tracing, as
+    /// photons still follow Schwarzschild paths: `Q` and the modulus of the elliptic integrals don't
+    /// depend on spin, and frame dragging of the photons isn't modelled. The image is therefore
+    /// approximate for rapidly rotating black holes. A spin of zero is the same as
+    /// [`BlackHole::new`].
+    #[must_use]
+    pub fn new_kerr(mass: f64, accretion_rate: f64, disk_outer_edge: f64, spin: f64) -> Self {
         BlackHole {
             mass,
             accretion_rate,
             disk_outer_edge,
+            disk_inner_edge: calc_isco_radius(mass, spin) / mass,
+            spin,
+            peak_flux_radius: calc_peak_intrinsic_flux_radius(1.0, spin),
+        }
+    }
+
+    /// Construct the black hole from the paper's simulated photograph, to be viewed from
+    /// [`LUMINET_1979_INCLINATION`].
+    ///
+    /// The paper works in units of the black hole's mass, with the disk extending outwards from the
+    /// last stable orbit at `6M`. Since the photograph shows normalized flux, the accretion rate
+    /// only sets the overall scale.
+    #[must_use]
+    pub fn luminet_1979_preset() -> Self {
+        BlackHole::new(1.0, DEFAULT_ACCRETION_RATE, DEFAULT_DISK_OUTER_EDGE)
+    }
+
+    /// Start building a black hole, with validation of its parameters.
+    #[must_use]
+    pub fn builder() -> BlackHoleBuilder {
+        BlackHoleBuilder::default()
+    }
+
+    /// Construct a black hole, checking that its mass and accretion disk are valid.
+    pub fn try_new(
+        mass: f64,
+        accretion_rate: f64,
+        disk_outer_edge: f64,
+    ) -> Result<Self, BlackHoleError> {
+        BlackHole::try_new_kerr(mass, accretion_rate, disk_outer_edge, 0.0)
+    }
+
+    /// Construct a rotating black hole as per [`BlackHole::new_kerr`], checking that the mass, spin
+    /// and accretion disk are valid.
+    pub fn try_new_kerr(
+        mass: f64,
+        accretion_rate: f64,
+        disk_outer_edge: f64,
+        spin: f64,
+    ) -> Result<Self, BlackHoleError> {
+        BlackHole::try_new_with_inner_edge(mass, accretion_rate, disk_outer_edge, spin, None)
+    }
+
+    /// Construct a rotating black hole as per [`BlackHole::try_new_kerr`], with the disk's inner
+    /// edge at the given radius, in units of black hole mass, or at the innermost stable circular
+    /// orbit if None.
+    fn try_new_with_inner_edge(
+        mass: f64,
+        accretion_rate: f64,
+        disk_outer_edge: f64,
+        spin: f64,
+        disk_inner_edge: Option<f64>,
+    ) -> Result<Self, BlackHoleError> {
+        if !(mass.is_finite() && mass > 0.0) {
+            return Err(BlackHoleError::InvalidMass { mass });
+        }
+        if !(accretion_rate.is_finite() && accretion_rate > 0.0) {
+            return Err(BlackHoleError::InvalidAccretionRate { accretion_rate });
+        }
+        if !(0.0..1.0).contains(&spin) {
+            return Err(BlackHoleError::InvalidSpin { spin });
         }
+        let mut blackhole = BlackHole::new_kerr(mass, accretion_rate, disk_outer_edge, spin);
+        if let Some(disk_inner_edge) = disk_inner_edge {
+            let marginally_bound_radius = calc_marginally_bound_radius(mass, spin) / mass;
+            if !(disk_inner_edge.is_finite() && disk_inner_edge >= marginally_bound_radius) {
+                return Err(BlackHoleError::InvalidDiskInnerEdge {
+                    disk_inner_edge,
+                    marginally_bound_radius,
+                });
+            }
+            blackhole.disk_inner_edge = disk_inner_edge;
+        }
+        if disk_outer_edge.is_nan() || blackhole.disk_outer_edge() <= blackhole.disk_inner_edge() {
+            return Err(BlackHoleError::InvalidDiskOuterEdge {
+                disk_outer_edge,
+                disk_inner_edge: blackhole.disk_inner_edge() / mass,
+            });
+        }
+        Ok(blackhole)
     }
 
     /// Value of the critical impact parameter for this black hole.
@@ -47,16 +530,49 @@ impl BlackHole {
         3.0 * 3.0_f64.sqrt() * self.mass
     }
 
+    /// Points on the photon ring, the circle of radius equal to the critical impact parameter that
+    /// bounds the black hole's shadow, evenly spaced in angle in the observer's frame.
+    #[must_use]
+    pub fn photon_ring_coordinates(&self, num_angles: usize) -> Vec<Vector2<f64>> {
+        let radius = self.critical_impact_parameter();
+        (0..num_angles)
+            .map(|i| {
+                let angle = Rad(i as f64 / num_angles as f64 * 2.0 * PI);
+                Vector2::new(radius * angle.cos(), radius * angle.sin())
+            })
+            .collect()
+    }
+
+    /// Angular radius, in radians, of the Einstein ring formed by a point source directly behind the
+    /// black hole, in the weak-field limit.
+    ///
+    /// `source_distance` is the distance from the black hole to the source and `observer_distance`
+    /// the distance from the observer to the black hole, both in the same units as the mass. Using
+    /// the thin lens equation, the radius is `sqrt(4M D_ls / (D_l D_s))`.
+    #[must_use]
+    pub fn einstein_ring_radius(&self, source_distance: f64, observer_distance: f64) -> f64 {
+        (4.0 * self.mass * source_distance
+            / (observer_distance * (observer_distance + source_distance)))
+            .sqrt()
+    }
+
     /// The radius of the outer edge of the accretion disk.
     #[must_use]
     pub fn disk_outer_edge(&self) -> f64 {
         self.disk_outer_edge * self.mass
     }
 
-    /// The radius of the inner edge of the accretion disk.
+    /// The radius of the inner edge of the accretion disk, at the innermost stable circular orbit
+    /// unless the black hole was built with another inner edge.
     #[must_use]
     pub fn disk_inner_edge(&self) -> f64 {
-        6.0 * self.mass
+        self.disk_inner_edge * self.mass
+    }
+
+    /// The dimensionless spin `a/M` of the black hole.
+    #[must_use]
+    pub fn spin(&self) -> f64 {
+        self.spin
     }
 
     /// Construct an isoradial forming the apparent inner edge of the accretion disk.
@@ -85,6 +601,620 @@ impl BlackHole {
             .get_impact_parameter_from_alpha(inclination, alpha)
     }
 
+    /// Estimate the disk radius whose direct (order 0) image lies at the apparent inner edge of the
+    /// disk at the given angle, by inverting the impact parameter of the direct image with a
+    /// bisection over the radii of the disk.
+    ///
+    /// The apparent inner edge is the image of the disk's inner edge, so this recovers
+    /// [`disk_inner_edge`](BlackHole::disk_inner_edge) up to the solver's tolerance: no part of a
+    /// thin disk inside its inner edge is visible at any angle, however the lensing distorts it.
+    #[must_use]
+    pub fn innermost_visible_radius(&self, inclination: Rad<f64>, alpha: Rad<f64>) -> f64 {
+        let target = self.apparent_inner_edge_radius(inclination, alpha);
+        // The impact parameter of the direct image grows with radius at any fixed angle
+        let (mut low, mut high) = (self.disk_inner_edge(), self.disk_outer_edge());
+        for _ in 0..INNERMOST_VISIBLE_RADIUS_ITERS {
+            let mid = (low + high) / 2.0;
+            let impact_parameter =
+                IsoRadial::new(self, mid, 0).get_impact_parameter_from_alpha(inclination, alpha);
+            if impact_parameter < target {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    /// Calculate the full lengths of the major and minor axes of the apparent outer edge of the
+    /// accretion disk, along the image's horizontal (`alpha = ±pi/2`) and vertical (`alpha = 0, pi`)
+    /// axes respectively, returned as `(major, minor)`.
+    #[must_use]
+    pub fn apparent_disk_axes<A: Into<Rad<f64>>>(&self, inclination: A) -> (f64, f64) {
+        let inclination: Rad<f64> = inclination.into();
+        let axis_length = |alpha: f64| {
+            self.apparent_outer_edge_radius(inclination, Rad(alpha))
+                + self.apparent_outer_edge_radius(inclination, Rad(alpha + PI))
+        };
+        (axis_length(PI / 2.0), axis_length(0.0))
+    }
+
+    /// Calculate the eccentricity of the apparent outer edge of the accretion disk, from its extent
+    /// along the major (`alpha = pi/2`) and minor (`alpha = 0`) image axes.
+    #[must_use]
+    pub fn apparent_disk_eccentricity<A: Into<Rad<f64>>>(&self, inclination: A) -> f64 {
+        let inclination: Rad<f64> = inclination.into();
+        let minor_axis = self.apparent_outer_edge_radius(inclination, Rad(0.0));
+        let major_axis = self.apparent_outer_edge_radius(inclination, Rad(PI / 2.0));
+        let axis_ratio = minor_axis.min(major_axis) / minor_axis.max(major_axis);
+        (1.0 - axis_ratio.powi(2)).sqrt()
+    }
+
+    /// Calculate the lensing magnification of the point on the accretion disk at the given radius
+    /// and angle in the observer's frame.
+    ///
+    /// This is the Jacobian of the map from `(radius, alpha)` to the point's position in the
+    /// observer's frame, relative to that of the unlensed (Newtonian) projection of the disk. Since
+    /// the point is at polar coordinates `(b, alpha)`, the Jacobian is `b * db/dr`, with the
+    /// derivative found by finite differences.
+    #[must_use]
+    pub fn magnification_at<A: Into<Rad<f64>>>(
+        &self,
+        radius: f64,
+        alpha: Rad<f64>,
+        inclination: A,
+        order: u32,
+    ) -> f64 {
+        let inclination: Rad<f64> = inclination.into();
+        let step = MAGNIFICATION_RADIUS_STEP * self.mass;
+        let impact_parameter_at = |radius: f64| {
+            calc_impact_parameter(
+                radius,
+                inclination,
+                alpha,
+                self.mass,
+                order,
+                &SolverConfig::default(),
+            )
+        };
+        let impact_parameter_derivative = (impact_parameter_at(radius + step)
+            - impact_parameter_at(radius - step))
+            / (2.0 * step);
+        let lensed_jacobian = impact_parameter_at(radius) * impact_parameter_derivative;
+
+        // The Newtonian impact parameter is linear in radius, and vanishes along the line of sight
+        // when edge-on
+        let newtonian_impact_parameter =
+            ellipse(radius, alpha, inclination).max(MAGNIFICATION_MIN_NEWTONIAN_FRACTION * radius);
+        let newtonian_jacobian = newtonian_impact_parameter.powi(2) / radius;
+
+        (lensed_jacobian / newtonian_jacobian).abs()
+    }
+
+    /// Estimate the fraction of the disk that is lensed into view from behind the black hole, by
+    /// sampling the direct image.
+    ///
+    /// A sample counts if it lies on the far side of the black hole (`cos(gamma) < 0`), its
+    /// Newtonian projection falls within the shadow so it would be hidden without lensing, and yet
+    /// its lensed image lies outside the shadow.
+    #[must_use]
+    pub fn lensed_fraction<A: Into<Rad<f64>>>(&self, inclination: A, sample_count: usize) -> f64 {
+        let inclination: Rad<f64> = inclination.into();
+        let samples = self.sample_flux_at_points(inclination, sample_count, 0);
+        if samples.is_empty() {
+            return 0.0;
+        }
+        let critical_impact_parameter = self.critical_impact_parameter();
+        let lensed_count = samples
+            .iter()
+            .filter(|sample| {
+                calc_cos_gamma(sample.alpha, inclination) < 0.0
+                    && ellipse(sample.radius, sample.alpha, inclination) < critical_impact_parameter
+                    && sample.impact_parameter > critical_impact_parameter
+            })
+            .count();
+        lensed_count as f64 / samples.len() as f64
+    }
+
+    /// Estimate the apparent width of the photon ring, in units of impact parameter.
+    ///
+    /// The photon ring is formed by the ghost image, which is sampled and split into angular bins.
+    /// Within each bin, the width is the range of impact parameters over which the observed flux
+    /// exceeds `threshold_fraction` of that bin's peak flux. The result is the mean width over all
+    /// bins with samples, or None if no samples land in the ring.
+    #[must_use]
+    pub fn photon_ring_width<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+        threshold_fraction: f64,
+    ) -> Option<f64> {
+        let samples = self.sample_flux_at_points(inclination, sample_count, 1);
+
+        let mut bins: Vec<Vec<&Sample>> = vec![Vec::new(); PHOTON_RING_ANGLE_BINS];
+        for sample in samples.iter().filter(|sample| sample.is_finite()) {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let bin = ((sample.alpha.0.rem_euclid(2.0 * PI) / (2.0 * PI))
+                * PHOTON_RING_ANGLE_BINS as f64) as usize;
+            bins[bin.min(PHOTON_RING_ANGLE_BINS - 1)].push(sample);
+        }
+
+        let widths = bins
+            .iter()
+            .filter(|bin| !bin.is_empty())
+            .map(|bin| {
+                let peak_flux = bin.iter().map(|s| s.observed_flux).fold(0.0, f64::max);
+                let (min_b, max_b) = bin
+                    .iter()
+                    .filter(|s| s.observed_flux >= threshold_fraction * peak_flux)
+                    .fold((f64::MAX, f64::MIN), |(min_b, max_b), s| {
+                        (min_b.min(s.impact_parameter), max_b.max(s.impact_parameter))
+                    });
+                max_b - min_b
+            })
+            .collect::<Vec<f64>>();
+        if widths.is_empty() {
+            return None;
+        }
+        Some(widths.iter().sum::<f64>() / widths.len() as f64)
+    }
+
+    /// Estimate how much brighter the photon ring is than the disk beside it, as the ratio of the
+    /// peak observed flux of the ring to the mean observed flux of the disk just outside it.
+    ///
+    /// The photon ring is formed by the ghost image, which is sampled along with the direct image.
+    /// The disk just outside the ring is the direct image within `2M` of impact parameter beyond the
+    /// ring's outermost sample. Returns None if no ghost samples are found, or if the disk within
+    /// that band has no samples or no flux.
+    #[must_use]
+    pub fn photon_ring_enhancement<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+    ) -> Option<f64> {
+        let inclination: Rad<f64> = inclination.into();
+        let ring_samples = self.sample_flux_at_points(inclination, sample_count, 1);
+        let disk_samples = self.sample_flux_at_points(inclination, sample_count, 0);
+
+        if !ring_samples.iter().any(Sample::is_finite) {
+            return None;
+        }
+        let (peak_flux, ring_outer_edge) = ring_samples
+            .iter()
+            .filter(|sample| sample.is_finite())
+            .fold((0.0, 0.0), |(peak_flux, outer_edge), sample| {
+                (
+                    f64::max(peak_flux, sample.observed_flux),
+                    f64::max(outer_edge, sample.impact_parameter),
+                )
+            });
+        let adjacent_band =
+            ring_outer_edge..=ring_outer_edge + PHOTON_RING_ADJACENT_WIDTH * self.mass;
+        let (total_flux, count) = disk_samples
+            .iter()
+            .filter(|sample| sample.is_finite() && adjacent_band.contains(&sample.impact_parameter))
+            .fold((0.0, 0_usize), |(total_flux, count), sample| {
+                (total_flux + sample.observed_flux, count + 1)
+            });
+        if count == 0 || total_flux <= 0.0 {
+            return None;
+        }
+        Some(peak_flux / (total_flux / count as f64))
+    }
+
+    /// Calculate the observed flux of an orbiting hotspot at each of the given orbital phases, in
+    /// radians, summed over the direct and ghost images.
+    ///
+    /// The flux of each image is the hotspot's intrinsic flux, boosted by the lensing
+    /// magnification and reduced by the redshift factor `(1 + z)^4`. These are averaged over the
+    /// centre of the hotspot and points around its edge, so larger hotspots smooth the light curve.
+    #[must_use]
+    pub fn hotspot_light_curve<A: Into<Rad<f64>>>(
+        &self,
+        hotspot: &Hotspot,
+        inclination: A,
+        phases: &[f64],
+    ) -> Vec<f64> {
+        let inclination: Rad<f64> = inclination.into();
+        let intrinsic_flux = hotspot.brightness * PI * hotspot.size.powi(2);
+
+        phases
+            .par_iter()
+            .map(|&phase| {
+                let centre_alpha = hotspot.alpha_at_phase(phase);
+                let points = std::iter::once((hotspot.radius, centre_alpha))
+                    .chain((0..HOTSPOT_EDGE_POINTS).map(|i| {
+                        // Offset from the centre of the hotspot, in the plane of the disk
+                        let angle = 2.0 * PI * i as f64 / HOTSPOT_EDGE_POINTS as f64;
+                        let (x, y) = (
+                            hotspot.radius + hotspot.size * angle.cos(),
+                            hotspot.size * angle.sin(),
+                        );
+                        (x.hypot(y), centre_alpha + Rad(y.atan2(x)))
+                    }))
+                    .collect::<Vec<(f64, Rad<f64>)>>();
+
+                let mean_gain = (0..=1)
+                    .flat_map(|order| points.iter().map(move |&point| (order, point)))
+                    .map(|(order, (radius, alpha))| {
+                        self.image_flux_gain(radius, alpha, inclination, order)
+                    })
+                    .sum::<f64>()
+                    / points.len() as f64;
+                intrinsic_flux * mean_gain
+            })
+            .collect()
+    }
+
+    /// Render an image of the accretion disk with an orbiting hotspot, averaged over one orbital
+    /// period of the hotspot at `phase_steps` evenly spaced phases.
+    ///
+    /// The hotspot's observed flux, its brightness reduced by the redshift factor `(1 + z)^4`, is
+    /// added to that of the disk wherever it lies. Each image order is sampled at `sample_count`
+    /// points over the whole disk, and as many again over the annulus the hotspot orbits through.
+    /// Every phase is rendered with the same flux range, up to the maximum flux over the orbit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `phase_steps` is zero, or the samples can't be triangulated.
+    pub fn time_averaged_flux_image<A: Into<Rad<f64>>>(
+        &self,
+        hotspot: &Hotspot,
+        inclination: A,
+        sample_count: usize,
+        image_width: u32,
+        image_height: u32,
+        phase_steps: usize,
+    ) -> Result<plotting::Luma16Image, Box<dyn std::error::Error>> {
+        if phase_steps == 0 {
+            return Err("at least one phase step is needed to average over".into());
+        }
+        let inclination: Rad<f64> = inclination.into();
+        let samples = [0, 1].map(|order| {
+            let mut samples = self.sample_flux_at_points(inclination, sample_count, order);
+            samples.extend(self.sample_flux_at_random_points(
+                inclination,
+                sample_count,
+                order,
+                FallbackMode::default(),
+                &SolverConfig::default(),
+                (hotspot.radius - hotspot.size).max(self.disk_inner_edge())
+                    ..=(hotspot.radius + hotspot.size).min(self.disk_outer_edge()),
+                0.0..=2.0 * PI,
+            ));
+            samples
+        });
+        let hotspot_flux = |sample: &Sample| hotspot.brightness / sample.redshift_factor.powi(4);
+
+        // The hotspot passes over every sample in its annulus at some phase
+        let max_flux = samples
+            .iter()
+            .flatten()
+            .filter(|sample| sample.is_finite())
+            .map(|sample| {
+                if (sample.radius - hotspot.radius).abs() < hotspot.size {
+                    sample.observed_flux + hotspot_flux(sample)
+                } else {
+                    sample.observed_flux
+                }
+            })
+            .fold(0.0, f64::max);
+        let options = plotting::FluxImageOptions {
+            flux_range: Some(0.0..=max_flux),
+            ..Default::default()
+        };
+
+        let mut total_luma = vec![0.0; image_width as usize * image_height as usize];
+        for step in 0..phase_steps {
+            let centre_alpha = hotspot.alpha_at_phase(2.0 * PI * step as f64 / phase_steps as f64);
+            let [mut direct_samples, mut ghost_samples] = samples.clone().map(|samples| {
+                samples
+                    .into_iter()
+                    .map(|sample| {
+                        let distance_squared = sample.radius.powi(2) + hotspot.radius.powi(2)
+                            - 2.0
+                                * sample.radius
+                                * hotspot.radius
+                                * (sample.alpha - centre_alpha).cos();
+                        if distance_squared < hotspot.size.powi(2) {
+                            Sample {
+                                observed_flux: sample.observed_flux + hotspot_flux(&sample),
+                                ..sample
+                            }
+                        } else {
+                            sample
+                        }
+                    })
+                    .collect::<Vec<Sample>>()
+            });
+            let interpolator =
+                FluxInterpolator::new(self, inclination, &mut direct_samples, &mut ghost_samples)?;
+            let mut frame = plotting::Luma16Image::new(image_width, image_height);
+            plotting::render_into(
+                &interpolator,
+                &mut frame,
+                image_width,
+                image_height,
+                &options,
+            )?;
+            for (total, &luma) in total_luma.iter_mut().zip(frame.iter()) {
+                *total += f64::from(luma);
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let lumas = total_luma
+            .iter()
+            .map(|total| (total / phase_steps as f64).round() as u16)
+            .collect();
+        Ok(
+            plotting::Luma16Image::from_raw(image_width, image_height, lumas)
+                .expect("one luma per pixel"),
+        )
+    }
+
+    /// Render a series of images of a slowly precessing accretion disk at the given times, with the
+    /// disk's position angle advanced by `precession_rate`, in radians per unit time, at each.
+    ///
+    /// The precession is taken to be slow enough that the disk is steady at each time, so the same
+    /// samples are used for every image and the flux values are normalized across the series, as
+    /// for [`plotting::generate_flux_images_position_angles`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the samples can't be triangulated.
+    pub fn precession_series<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        precession_rate: f64,
+        times: &[f64],
+        sample_count: usize,
+        image_width: u32,
+        image_height: u32,
+    ) -> Result<Vec<plotting::Luma16Image>, Box<dyn std::error::Error>> {
+        let position_angles = times
+            .iter()
+            .map(|time| Rad(precession_rate * time))
+            .collect::<Vec<Rad<f64>>>();
+        plotting::generate_flux_images_position_angles(
+            self,
+            inclination,
+            &position_angles,
+            sample_count,
+            image_width,
+            image_height,
+        )
+    }
+
+    /// Estimate the total observed flux of the direct and ghost images of the accretion disk, by
+    /// sampling each image and integrating the observed flux over the observer's photographic
+    /// plate with [`observer_area_weights`].
+    #[must_use]
+    pub fn integrated_observed_flux<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+    ) -> f64 {
+        let inclination: Rad<f64> = inclination.into();
+        [0, 1]
+            .iter()
+            .map(|&order| {
+                let samples = self
+                    .sample_flux_at_points(inclination, sample_count, order)
+                    .into_iter()
+                    .filter(Sample::is_finite)
+                    .collect::<Vec<Sample>>();
+                let areas = observer_area_weights(self, inclination, &samples)
+                    .expect("samples have finite positions");
+                samples
+                    .iter()
+                    .zip(areas)
+                    .map(|(sample, area)| sample.observed_flux * area)
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+
+    /// Find which of the given inclinations the accretion disk appears brightest from, comparing
+    /// the [`integrated_observed_flux`](Self::integrated_observed_flux) at each.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no candidate inclinations are given.
+    #[must_use]
+    pub fn brightest_inclination(&self, sample_count: usize, candidates: &[Rad<f64>]) -> Rad<f64> {
+        candidates
+            .iter()
+            .map(|&inclination| {
+                (
+                    inclination,
+                    self.integrated_observed_flux(inclination, sample_count),
+                )
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(inclination, _)| inclination)
+            .expect("at least one candidate inclination")
+    }
+
+    /// Calculate the ratio of the observed flux of a small patch of the accretion disk in the
+    /// ghost (order = 1) image to that in the direct (order = 0) image.
+    ///
+    /// This accounts for both the different redshifts of the two images and the different
+    /// magnifications of the patch, so is the ratio of the total flux received in each image rather
+    /// than of their surface brightnesses.
+    #[must_use]
+    pub fn image_flux_ratio<A: Into<Rad<f64>>>(
+        &self,
+        radius: f64,
+        alpha: Rad<f64>,
+        inclination: A,
+    ) -> f64 {
+        let inclination: Rad<f64> = inclination.into();
+        self.image_flux_gain(radius, alpha, inclination, 1)
+            / self.image_flux_gain(radius, alpha, inclination, 0)
+    }
+
+    /// The factor by which the flux of a small patch of the accretion disk is changed in the
+    /// image of the given order, from lensing magnification and the redshift factor `(1 + z)^4`.
+    fn image_flux_gain(
+        &self,
+        radius: f64,
+        alpha: Rad<f64>,
+        inclination: Rad<f64>,
+        order: u32,
+    ) -> f64 {
+        let impact_parameter = calc_impact_parameter(
+            radius,
+            inclination,
+            alpha,
+            self.mass,
+            order,
+            &SolverConfig::default(),
+        );
+        let redshift_factor = calc_redshift_factor(
+            radius,
+            alpha,
+            inclination,
+            self.mass,
+            self.spin,
+            impact_parameter,
+        );
+        self.magnification_at(radius, alpha, inclination, order) / redshift_factor.powi(4)
+    }
+
+    /// The radius at which the intrinsic flux of the disk peaks.
+    #[must_use]
+    pub fn peak_flux_radius(&self) -> f64 {
+        self.peak_flux_radius * self.mass
+    }
+
+    /// Sample the observed flux of the direct image, and average it azimuthally within equal-width
+    /// radius bins spanning the disk.
+    ///
+    /// Returns the radius at the centre of each bin along with the mean observed flux of the
+    /// samples within it. Bins without any samples are omitted, so the profile is empty if there
+    /// are no radius bins.
+    #[must_use]
+    pub fn radial_flux_profile<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+        radius_bins: usize,
+    ) -> Vec<(f64, f64)> {
+        if radius_bins == 0 {
+            return Vec::new();
+        }
+        let samples = self.sample_flux_at_points(inclination, sample_count, 0);
+
+        let bin_width = (self.disk_outer_edge() - self.disk_inner_edge()) / radius_bins as f64;
+        let mut totals = vec![(0.0, 0_usize); radius_bins];
+        for sample in &samples {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let bin = ((sample.radius - self.disk_inner_edge()) / bin_width) as usize;
+            let (total_flux, count) = &mut totals[bin.min(radius_bins - 1)];
+            *total_flux += sample.observed_flux;
+            *count += 1;
+        }
+
+        totals
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, count))| *count > 0)
+            .map(|(bin, (total_flux, count))| {
+                let radius = self.disk_inner_edge() + (bin as f64 + 0.5) * bin_width;
+                (radius, total_flux / *count as f64)
+            })
+            .collect()
+    }
+
+    /// Calculate the observed monochromatic flux of a sample at the given frequency, in units of
+    /// `k T / h` for the peak temperature of the disk.
+    #[must_use]
+    pub fn observed_monochromatic_flux(&self, sample: &Sample, frequency: f64) -> f64 {
+        calc_observed_monochromatic_flux(
+            sample.radius,
+            self.accretion_rate,
+            self.mass,
+            self.spin,
+            self.peak_flux_radius(),
+            sample.redshift_factor,
+            frequency,
+        )
+    }
+
+    /// Calculate the observed color temperature of a sample, the local black body temperature
+    /// divided by the redshift factor, in units of the peak temperature of the disk.
+    #[must_use]
+    pub fn observed_color_temperature(&self, sample: &Sample) -> f64 {
+        sample.temperature / sample.redshift_factor
+    }
+
+    /// Calculate the observed flux at a set of points in the observer's frame, in the same
+    /// orientation as the flux images, by interpolating the given number of samples. Points where
+    /// no image of the disk lies, such as inside the black hole's shadow, give None.
+    ///
+    /// Returns an error if the samples can't be triangulated, e.g. if there are none.
+    pub fn flux_at_observer_points<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+        points: &[Vector2<f64>],
+    ) -> Result<Vec<Option<f64>>, Box<dyn std::error::Error>> {
+        let inclination: Rad<f64> = inclination.into();
+        let mut direct_samples = self.sample_flux_at_points(inclination, sample_count, 0);
+        let mut ghost_samples = self.sample_flux_at_points(inclination, sample_count, 1);
+        Ok(
+            FluxInterpolator::new(self, inclination, &mut direct_samples, &mut ghost_samples)?
+                .observed_flux_at_points(points, DiskOpacity::Opaque),
+        )
+    }
+
+    /// The order of the image of the accretion disk at a point in the observer's frame, in the same
+    /// orientation as the flux images, or None if the point isn't on either image.
+    ///
+    /// This matches which image an opaque disk shows in rendered flux images: the direct image
+    /// between its apparent inner and outer edges, and the ghost image elsewhere within its own
+    /// edges.
+    #[must_use]
+    pub fn is_point_on_disk<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        point: Vector2<f64>,
+    ) -> Option<u32> {
+        image_order_at(self, inclination.into(), point)
+    }
+
+    /// Calculate the observed flux along the major axis of the image (`y = 0`), by interpolating the
+    /// given number of samples at evenly spaced points spanning the apparent outer edge of the disk.
+    ///
+    /// Returns the `x` coordinate of each point along with its observed flux. Points where no image
+    /// of the disk lies, such as inside the black hole's shadow, have zero flux. Returns an error if
+    /// the samples can't be triangulated, as per
+    /// [`flux_at_observer_points`](BlackHole::flux_at_observer_points).
+    pub fn major_axis_profile<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        sample_count: usize,
+        num_points: usize,
+    ) -> Result<Vec<(f64, f64)>, Box<dyn std::error::Error>> {
+        let inclination: Rad<f64> = inclination.into();
+        let half_width = self
+            .apparent_outer_edge_radius(inclination, Rad(PI / 2.0))
+            .max(self.apparent_outer_edge_radius(inclination, Rad(3.0 * PI / 2.0)));
+        let points = (0..num_points)
+            .map(|i| {
+                let t = i as f64 / (num_points.max(2) - 1) as f64;
+                Vector2::new(half_width * (2.0 * t - 1.0), 0.0)
+            })
+            .collect::<Vec<_>>();
+        Ok(self
+            .flux_at_observer_points(inclination, sample_count, &points)?
+            .into_iter()
+            .zip(&points)
+            .map(|(flux, point)| (point.x, flux.unwrap_or(0.0)))
+            .collect())
+    }
+
     /// Sample the observed flux from the accretion disk at a number of random points.
     #[must_use]
     pub fn sample_flux_at_points<A: Into<Rad<f64>>>(
@@ -92,34 +1222,1390 @@ impl BlackHole {
         inclination: A,
         num_points: usize,
         order: u32,
+    ) -> Vec<Sample> {
+        self.sample_flux_at_points_with_options(
+            inclination,
+            num_points,
+            order,
+            &SamplingOptions::default(),
+        )
+    }
+
+    /// Sample the observed flux from the accretion disk at a number of points distributed by the
+    /// given strategy, as per [`sample_flux_at_points`](BlackHole::sample_flux_at_points).
+    #[must_use]
+    pub fn sample_flux_at_points_with<A: Into<Rad<f64>>>(
+        &self,
+        strategy: SamplingStrategy,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+    ) -> Vec<Sample> {
+        self.sample_flux_at_points_with_options(
+            inclination,
+            num_points,
+            order,
+            &SamplingOptions {
+                strategy,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Sample the observed flux from the accretion disk at a number of random points, with the
+    /// given sampling options.
+    ///
+    /// Fewer than `num_points` samples may be returned, depending on the options.
+    #[must_use]
+    pub fn sample_flux_at_points_with_options<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+        options: &SamplingOptions,
+    ) -> Vec<Sample> {
+        let inclination: Rad<f64> = inclination.into();
+        let radius_range = self.disk_inner_edge()..=self.disk_outer_edge();
+        let alpha_range = azimuth_wedge(options.azimuth_range.as_ref());
+        let mut samples = match options.strategy {
+            // Random points are generated in parallel, each thread with its own generator
+            SamplingStrategy::Random => self.sample_flux_at_random_points(
+                inclination,
+                num_points,
+                order,
+                options.fallback,
+                &options.solver,
+                radius_range,
+                alpha_range,
+            ),
+            strategy => strategy
+                .points(
+                    num_points,
+                    &radius_range,
+                    &alpha_range,
+                    &mut rand::thread_rng(),
+                )
+                .into_par_iter()
+                .filter_map(|(radius, alpha)| {
+                    self.sample_flux_at_point(
+                        radius,
+                        Rad(alpha.rem_euclid(2.0 * PI)),
+                        inclination,
+                        order,
+                        None,
+                        options.fallback,
+                        &options.solver,
+                    )
+                })
+                .collect::<Vec<Sample>>(),
+        };
+        if options.no_doppler_beaming {
+            for sample in &mut samples {
+                sample.redshift_factor =
+                    calc_gravitational_redshift(sample.radius, self.mass, self.spin);
+                sample.observed_flux = calc_observed_flux(
+                    sample.radius,
+                    self.accretion_rate,
+                    self.mass,
+                    self.spin,
+                    sample.redshift_factor,
+                    1.0,
+                );
+            }
+        }
+        if options.emission != EmissionModel::NovikovThorne {
+            let peak_flux = calc_intrinsic_flux(
+                self.peak_flux_radius(),
+                self.accretion_rate,
+                self.mass,
+                self.spin,
+            );
+            for sample in &mut samples {
+                let intrinsic_flux = options.emission.intrinsic_flux(
+                    sample.radius,
+                    self.accretion_rate,
+                    self.mass,
+                    self.spin,
+                    self.peak_flux_radius(),
+                );
+                sample.temperature = (intrinsic_flux / peak_flux).powf(0.25);
+                sample.observed_flux = if intrinsic_flux == 0.0 {
+                    0.0
+                } else {
+                    intrinsic_flux / sample.redshift_factor.powi(4)
+                };
+            }
+        }
+        if options.inner_edge_taper > 0.0 {
+            for sample in &mut samples {
+                sample.observed_flux *= calc_inner_edge_taper(
+                    sample.radius,
+                    self.disk_inner_edge(),
+                    options.inner_edge_taper * self.mass,
+                );
+            }
+        }
+        if options.self_shadowing && options.disk_half_thickness > 0.0 && order == 0 {
+            for sample in &mut samples {
+                if self.is_self_shadowed(sample, inclination, options.disk_half_thickness) {
+                    sample.observed_flux = 0.0;
+                }
+            }
+        }
+        samples
+    }
+
+    /// Sample the observed flux from the accretion disk at the first `num_points` points of the
+    /// Sobol sequence, solving impact parameters through the given cache, or without caching if
+    /// `None`.
+    ///
+    /// The points are the same on every call, so repeated calls with the same inclination and
+    /// order are solved from the cache.
+    #[must_use]
+    pub fn sample_flux_at_points_cached<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+        cache: Option<&ImpactParameterCache>,
+    ) -> Vec<Sample> {
+        let inclination: Rad<f64> = inclination.into();
+        SamplingStrategy::Sobol
+            .points(
+                num_points,
+                &(self.disk_inner_edge()..=self.disk_outer_edge()),
+                &(0.0..=2.0 * PI),
+                &mut rand::thread_rng(),
+            )
+            .into_par_iter()
+            .filter_map(|(radius, alpha)| {
+                self.sample_flux_at_point(
+                    radius,
+                    Rad(alpha),
+                    inclination,
+                    order,
+                    cache,
+                    FallbackMode::default(),
+                    &SolverConfig::default(),
+                )
+            })
+            .collect()
+    }
+
+    /// Sample the observed flux from the accretion disk at a number of random points, generated
+    /// deterministically from the given seed.
+    ///
+    /// The points are split into fixed-size chunks, each with its own generator seeded from the
+    /// base seed and the chunk index, so the samples are identical regardless of how many threads
+    /// are used.
+    #[must_use]
+    pub fn sample_flux_at_points_seeded<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+        seed: u64,
     ) -> Vec<Sample> {
         let inclination: Rad<f64> = inclination.into();
+        let radius_dist = Uniform::new(self.disk_inner_edge(), self.disk_outer_edge());
+        let alpha_dist = Uniform::new(0.0, 2.0 * PI);
+
+        (0..num_points.div_ceil(SEEDED_SAMPLING_CHUNK_SIZE))
+            .into_par_iter()
+            .flat_map_iter(|chunk| {
+                let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk as u64));
+                let chunk_len =
+                    SEEDED_SAMPLING_CHUNK_SIZE.min(num_points - chunk * SEEDED_SAMPLING_CHUNK_SIZE);
+                (0..chunk_len)
+                    .filter_map(|_| {
+                        let radius = rng.sample(radius_dist);
+                        let alpha = Rad(rng.sample(alpha_dist));
+                        self.sample_flux_at_point(
+                            radius,
+                            alpha,
+                            inclination,
+                            order,
+                            None,
+                            FallbackMode::default(),
+                            &SolverConfig::default(),
+                        )
+                    })
+                    .collect::<Vec<Sample>>()
+            })
+            .collect::<Vec<Sample>>()
+    }
 
+    /// Sample the observed flux from the accretion disk at a number of random points, evaluating the
+    /// same emission points for each of the given image orders.
+    ///
+    /// Returns the samples for each order, in the same order as `orders`. Every point is kept for
+    /// each order, so the samples of different orders pair up by index. A point with no impact
+    /// parameter solution for an order has NaN values in that order's samples, so isn't
+    /// [`Sample::is_finite`].
+    #[must_use]
+    pub fn sample_flux_multi_order<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        num_points: usize,
+        orders: &[u32],
+    ) -> Vec<Vec<Sample>> {
+        let inclination: Rad<f64> = inclination.into();
         let radius_dist = Uniform::new(self.disk_inner_edge(), self.disk_outer_edge());
         let alpha_dist = Uniform::new(0.0, 2.0 * PI);
+        let points = (0..num_points)
+            .into_par_iter()
+            .map_init(rand::thread_rng, |rng, _| {
+                (rng.sample(radius_dist), Rad(rng.sample(alpha_dist)))
+            })
+            .collect::<Vec<(f64, Rad<f64>)>>();
+
+        orders
+            .iter()
+            .map(|&order| {
+                points
+                    .par_iter()
+                    .map(|&(radius, alpha)| {
+                        self.sample_flux_at_point(
+                            radius,
+                            alpha,
+                            inclination,
+                            order,
+                            None,
+                            FallbackMode::default(),
+                            &SolverConfig::default(),
+                        )
+                        .unwrap_or(Sample {
+                            radius,
+                            alpha,
+                            impact_parameter: f64::NAN,
+                            impact_parameter_source: ImpactParameterSource::default(),
+                            order,
+                            redshift_factor: f64::NAN,
+                            observed_flux: f64::NAN,
+                            temperature: f64::NAN,
+                            weight: 1.0,
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn sample_flux_at_random_points(
+        &self,
+        inclination: Rad<f64>,
+        num_points: usize,
+        order: u32,
+        fallback: FallbackMode,
+        solver: &SolverConfig,
+        radius_range: RangeInclusive<f64>,
+        alpha_range: RangeInclusive<f64>,
+    ) -> Vec<Sample> {
+        let radius_dist = Uniform::from(radius_range);
+        let alpha_dist = Uniform::from(alpha_range);
 
         (0..num_points)
             .into_par_iter()
             .map_init(rand::thread_rng, |rng, _| {
                 let radius = rng.sample(radius_dist);
-                let alpha = Rad(rng.sample(alpha_dist));
+                // The range of angles may extend past 2 pi for a wedge wrapping through zero
+                let alpha = Rad(rng.sample(alpha_dist).rem_euclid(2.0 * PI));
+                self.sample_flux_at_point(radius, alpha, inclination, order, None, fallback, solver)
+            })
+            .flatten()
+            .collect::<Vec<Sample>>()
+    }
+
+    /// Whether the emitting surface of a flared disk at the given sample is hidden from the
+    /// observer, treating light as travelling in straight lines.
+    ///
+    /// The ray from the sample's position on the surface towards the observer is marched outwards
+    /// until it leaves the disk, checking whether it passes through disk material on the way.
+    fn is_self_shadowed(
+        &self,
+        sample: &Sample,
+        inclination: Rad<f64>,
+        half_thickness: f64,
+    ) -> bool {
+        // Azimuth in the disk plane, measured from the point nearest the observer (eqn 9)
+        let phi = (sample.alpha.sin() * inclination.cos()).atan2(sample.alpha.cos());
+        let (x, y, z) = (
+            sample.radius * phi.cos(),
+            sample.radius * phi.sin(),
+            half_thickness * sample.radius,
+        );
+        let (sin_i, cos_i) = inclination.sin_cos();
 
-                let impact_parameter =
-                    calc_impact_parameter(radius, inclination, alpha, self.mass, order);
-                let redshift_factor =
-                    calc_redshift_factor(radius, alpha, inclination, self.mass, impact_parameter);
-                let observed_flux =
-                    calc_observed_flux(radius, self.accretion_rate, self.mass, redshift_factor);
+        // The surface faces away from the observer
+        if half_thickness * phi.cos() * sin_i >= cos_i {
+            return true;
+        }
 
-                Sample {
-                    radius,
-                    alpha,
+        let max_distance = sample.radius + self.disk_outer_edge();
+        (1..=SELF_SHADOWING_STEPS).any(|step| {
+            let t = max_distance * step as f64 / SELF_SHADOWING_STEPS as f64;
+            let radius = (x + t * sin_i).hypot(y);
+            radius >= self.disk_inner_edge()
+                && radius <= self.disk_outer_edge()
+                && z + t * cos_i < half_thickness * radius
+        })
+    }
+
+    /// Sample the observed flux from the accretion disk at the given point, returning None if
+    /// there is no solution for the impact parameter with the given fallback.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn sample_flux_at_point(
+        &self,
+        radius: f64,
+        alpha: Rad<f64>,
+        inclination: Rad<f64>,
+        order: u32,
+        cache: Option<&ImpactParameterCache>,
+        fallback: FallbackMode,
+        solver: &SolverConfig,
+    ) -> Option<Sample> {
+        let (impact_parameter, impact_parameter_source) = if let Some(cache) = cache {
+            cache.try_calc_impact_parameter_checked(
+                radius,
+                inclination,
+                alpha,
+                self.mass,
+                order,
+                fallback,
+                solver,
+            )
+        } else {
+            try_calc_impact_parameter_checked(
+                radius,
+                inclination,
+                alpha,
+                self.mass,
+                order,
+                fallback,
+                solver,
+            )
+        }?;
+        let gravitational_redshift = calc_gravitational_redshift(radius, self.mass, self.spin);
+        let doppler_factor = calc_doppler_factor(
+            radius,
+            alpha,
+            inclination,
+            self.mass,
+            self.spin,
+            impact_parameter,
+        );
+        let redshift_factor = doppler_factor * gravitational_redshift;
+        let observed_flux = calc_observed_flux(
+            radius,
+            self.accretion_rate,
+            self.mass,
+            self.spin,
+            gravitational_redshift,
+            doppler_factor,
+        );
+
+        Some(Sample {
+            radius,
+            alpha,
+            impact_parameter,
+            impact_parameter_source,
+            order,
+            redshift_factor,
+            observed_flux,
+            temperature: calc_temperature(
+                radius,
+                self.accretion_rate,
+                self.mass,
+                self.spin,
+                self.peak_flux_radius(),
+            ),
+            weight: 1.0,
+        })
+    }
+}
+
+/// The seed of the random number generator for one chunk of points sampled with the given seed.
+///
+/// The seed is mixed before the chunk index is added, so that the chunks of nearby seeds, such as
+/// those of the direct and ghost images, don't share any points.
+fn chunk_seed(seed: u64, chunk: u64) -> u64 {
+    splitmix64(splitmix64(seed).wrapping_add(chunk))
+}
+
+/// The SplitMix64 finalizer, mixing every bit of the input into every bit of the output.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        azimuth_wedge, BlackHole, BlackHoleError, EmissionModel, SamplingOptions, SamplingStrategy,
+        DEFAULT_ACCRETION_RATE,
+    };
+    use crate::{
+        equations::{
+            calc_doppler_factor, calc_gravitational_redshift, calc_observed_flux,
+            calc_observed_monochromatic_flux, calc_peak_intrinsic_flux_radius,
+        },
+        solvers::try_calc_impact_parameter,
+        FallbackMode, Hotspot, ImpactParameterCache, ImpactParameterSource, IsoRadial, Sample,
+        SolverConfig,
+    };
+    use cgmath::{assert_relative_eq, Angle, Deg, Rad, Vector2};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_sample_flux_at_points_cached() {
+        let blackhole = BlackHole::default();
+        let cache = ImpactParameterCache::new();
+        let uncached = blackhole.sample_flux_at_points_cached(Deg(80.0), 200, 0, None);
+        let first = blackhole.sample_flux_at_points_cached(Deg(80.0), 200, 0, Some(&cache));
+        let cached_solutions = cache.len();
+        assert!(cached_solutions > 0 && cached_solutions <= 200);
+
+        // Repeated calls solve the same points, so are answered from the cache
+        let second = blackhole.sample_flux_at_points_cached(Deg(80.0), 200, 0, Some(&cache));
+        assert_eq!(cache.len(), cached_solutions);
+        let positions = |samples: &[Sample]| {
+            samples
+                .iter()
+                .map(|sample| (sample.radius, sample.alpha, sample.impact_parameter))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(positions(&first), positions(&uncached));
+        assert_eq!(positions(&second), positions(&uncached));
+    }
+
+    #[test]
+    fn test_major_axis_profile_is_asymmetric() {
+        let blackhole = BlackHole::default();
+        let profile = blackhole.major_axis_profile(Deg(80.0), 3000, 101).unwrap();
+        assert_eq!(profile.len(), 101);
+        assert!(profile.windows(2).all(|pair| pair[0].0 < pair[1].0));
+
+        let peak = |side: &dyn Fn(f64) -> bool| {
+            profile
+                .iter()
+                .filter(|(x, _)| side(*x))
+                .map(|&(_, flux)| flux)
+                .fold(0.0, f64::max)
+        };
+        let (left_peak, right_peak) = (peak(&|x| x < 0.0), peak(&|x| x > 0.0));
+        assert!(left_peak > 0.0 && right_peak > 0.0);
+        assert!(left_peak.max(right_peak) > 1.5 * left_peak.min(right_peak));
+    }
+
+    #[test]
+    fn test_einstein_ring_radius() {
+        let (source_distance, observer_distance) = (1e6, 2e6);
+        let radius = BlackHole::default().einstein_ring_radius(source_distance, observer_distance);
+        assert!(
+            (radius - (4.0 * source_distance / (observer_distance * 3e6)).sqrt()).abs() < 1e-15
+        );
+
+        // Scales as the square root of the mass
+        let heavier = BlackHole::new(4.0, DEFAULT_ACCRETION_RATE, 50.0);
+        assert!(
+            (heavier.einstein_ring_radius(source_distance, observer_distance) - 2.0 * radius).abs()
+                < 1e-12
+        );
+
+        // For a distant source, falls off as the square root of the observer's distance
+        let blackhole = BlackHole::default();
+        let near = blackhole.einstein_ring_radius(1e15, 1e4);
+        let far = blackhole.einstein_ring_radius(1e15, 4e4);
+        assert!((near / far - 2.0).abs() < 1e-6);
+
+        // Vanishes as the source approaches the black hole
+        assert_eq!(blackhole.einstein_ring_radius(0.0, 1e4), 0.0);
+    }
+
+    #[test]
+    fn test_lensed_fraction_grows_with_inclination() {
+        let blackhole = BlackHole::default();
+        let fractions =
+            [20.0, 60.0, 85.0].map(|inclination| blackhole.lensed_fraction(Deg(inclination), 1000));
+        assert_eq!(fractions[0], 0.0);
+        assert!(fractions[0] < fractions[1] && fractions[1] < fractions[2]);
+        assert!(fractions[2] < 0.5);
+    }
+
+    #[test]
+    fn test_photon_ring_enhancement_grows_with_inclination() {
+        let blackhole = BlackHole::default();
+        let enhancements = [5.0, 70.0, 85.0].map(|inclination| {
+            blackhole
+                .photon_ring_enhancement(Deg(inclination), 5000)
+                .unwrap()
+        });
+        assert!(enhancements[0] > 1.0);
+        assert!(enhancements[0] < enhancements[1] && enhancements[1] < enhancements[2]);
+
+        // Without any samples there is no ring to compare with the disk
+        assert_eq!(blackhole.photon_ring_enhancement(Deg(70.0), 0), None);
+    }
+
+    #[test]
+    fn test_precession_series() {
+        let blackhole = BlackHole::default();
+        let times = [0.0, 1.0, 2.0];
+        let images = blackhole
+            .precession_series(Deg(70.0), PI / 4.0, &times, 1000, 32, 32)
+            .unwrap();
+        assert_eq!(images.len(), times.len());
+        assert!(images.iter().all(|image| image.dimensions() == (32, 32)));
+        assert_ne!(images[0], images[2]);
+
+        // Without precession the disk's orientation is fixed
+        let images = blackhole
+            .precession_series(Deg(70.0), 0.0, &times, 1000, 32, 32)
+            .unwrap();
+        assert_eq!(images[0], images[2]);
+    }
+
+    #[test]
+    fn test_apparent_disk_axes() {
+        let blackhole = BlackHole::default();
+        let (major, minor) = blackhole.apparent_disk_axes(Deg(0.0));
+        assert!((major - minor).abs() < 1e-6 * major);
+
+        for inclination in [30.0, 60.0, 85.0] {
+            let (major, minor) = blackhole.apparent_disk_axes(Deg(inclination));
+            assert!(major >= minor);
+            assert!(major > 2.0 * blackhole.disk_outer_edge());
+        }
+        let (_, minor_30) = blackhole.apparent_disk_axes(Deg(30.0));
+        let (_, minor_85) = blackhole.apparent_disk_axes(Deg(85.0));
+        assert!(minor_85 < minor_30);
+    }
+
+    #[test]
+    fn test_self_shadowing() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(85.0);
+        let samples = blackhole.sample_flux_at_points(inclination, 500, 0);
+        let options = SamplingOptions {
+            disk_half_thickness: 0.2,
+            self_shadowing: true,
+            ..Default::default()
+        };
+        let shadowed = |sample| blackhole.is_self_shadowed(sample, inclination.into(), 0.2);
+
+        // The near side of the disk hides the inner far side, but the outer far side can be seen
+        // over its rim
+        let far_side = samples
+            .iter()
+            .filter(|sample| sample.alpha.cos() < -0.5)
+            .collect::<Vec<_>>();
+        assert!(far_side
+            .iter()
+            .filter(|sample| sample.radius < 15.0)
+            .all(|sample| shadowed(sample)));
+        assert!(far_side
+            .iter()
+            .filter(|sample| sample.radius > 40.0)
+            .all(|sample| !shadowed(sample)));
+        let shadowed_samples =
+            blackhole.sample_flux_at_points_with_options(inclination, 500, 0, &options);
+        assert!(shadowed_samples
+            .iter()
+            .any(|sample| sample.observed_flux == 0.0));
+
+        // Nothing is shadowed when viewed from well above the disk, or by default
+        let samples = blackhole.sample_flux_at_points_with_options(Deg(30.0), 500, 0, &options);
+        assert!(samples.iter().all(|sample| sample.observed_flux > 0.0));
+        let samples = blackhole.sample_flux_at_points_with_options(
+            inclination,
+            500,
+            0,
+            &SamplingOptions::default(),
+        );
+        assert!(samples.iter().all(|sample| sample.observed_flux > 0.0));
+    }
+
+    #[test]
+    fn test_inner_edge_taper() {
+        let blackhole = BlackHole::default();
+        let options = SamplingOptions {
+            inner_edge_taper: 2.0,
+            ..Default::default()
+        };
+        let samples = blackhole.sample_flux_at_points_with_options(Deg(30.0), 1000, 0, &options);
+        for sample in &samples {
+            let untapered_flux = calc_observed_flux(
+                sample.radius,
+                blackhole.accretion_rate,
+                blackhole.mass,
+                blackhole.spin(),
+                calc_gravitational_redshift(sample.radius, blackhole.mass, blackhole.spin()),
+                calc_doppler_factor(
+                    sample.radius,
+                    sample.alpha,
+                    Rad::from(Deg(30.0)),
+                    blackhole.mass,
+                    blackhole.spin(),
+                    sample.impact_parameter,
+                ),
+            );
+            if sample.radius < blackhole.disk_inner_edge() + 2.0 {
+                let weight = (sample.radius - blackhole.disk_inner_edge()) / 2.0;
+                assert!((sample.observed_flux - weight * untapered_flux).abs() < 1e-12);
+                assert!(sample.observed_flux < untapered_flux);
+            } else {
+                assert_eq!(sample.observed_flux, untapered_flux);
+            }
+        }
+        assert!(samples
+            .iter()
+            .any(|sample| sample.radius < blackhole.disk_inner_edge() + 2.0));
+    }
+
+    #[test]
+    fn test_sampling_strategies() {
+        let blackhole = BlackHole::default();
+        let (inner, outer) = (blackhole.disk_inner_edge(), blackhole.disk_outer_edge());
+        // The number of samples in each cell of an `n` by `n` grid over radius and angle, allowing
+        // for rounding of points on the cell boundaries
+        let cell_counts = |samples: &[Sample], n: usize| {
+            let mut counts = vec![0; n * n];
+            for sample in samples {
+                let cell = |fraction: f64| (fraction * n as f64 + 1e-9) as usize;
+                let i = cell((sample.radius - inner) / (outer - inner));
+                let j = cell(sample.alpha.0 / (2.0 * PI));
+                counts[i.min(n - 1) * n + j.min(n - 1)] += 1;
+            }
+            counts
+        };
+
+        let random =
+            blackhole.sample_flux_at_points_with(SamplingStrategy::Random, Deg(30.0), 256, 0);
+        assert_eq!(random.len(), 256);
+
+        // One sample per lattice cell
+        let stratified = blackhole.sample_flux_at_points_with(
+            SamplingStrategy::StratifiedGrid,
+            Deg(30.0),
+            260,
+            0,
+        );
+        assert_eq!(stratified.len(), 256);
+        assert!(cell_counts(&stratified, 16).iter().all(|&count| count == 1));
+
+        // The first 256 Sobol points have one point in each cell of a 16 by 16 grid
+        let sobol =
+            blackhole.sample_flux_at_points_with(SamplingStrategy::Sobol, Deg(30.0), 256, 0);
+        assert_eq!(sobol.len(), 256);
+        assert!(cell_counts(&sobol, 16).iter().all(|&count| count == 1));
+    }
+
+    #[test]
+    fn test_power_law_emission() {
+        let blackhole = BlackHole::default();
+        let inner_flux_fraction = |index: f64| {
+            let options = SamplingOptions {
+                emission: EmissionModel::PowerLaw { index, r_in: 6.0 },
+                ..Default::default()
+            };
+            let samples =
+                blackhole.sample_flux_at_points_with_options(Deg(30.0), 2000, 0, &options);
+            let total = samples.iter().map(|s| s.observed_flux).sum::<f64>();
+            let inner = samples
+                .iter()
+                .filter(|s| s.radius < 12.0)
+                .map(|s| s.observed_flux)
+                .sum::<f64>();
+            inner / total
+        };
+        assert!(inner_flux_fraction(4.0) > inner_flux_fraction(2.0));
+        assert!(inner_flux_fraction(2.0) > inner_flux_fraction(1.0));
+
+        // Nothing is emitted inside the power law's inner radius
+        let options = SamplingOptions {
+            emission: EmissionModel::PowerLaw {
+                index: 3.0,
+                r_in: 10.0,
+            },
+            ..Default::default()
+        };
+        let samples = blackhole.sample_flux_at_points_with_options(Deg(30.0), 500, 0, &options);
+        for sample in &samples {
+            assert_eq!(sample.observed_flux > 0.0, sample.radius >= 10.0);
+            assert!(sample.temperature <= 1.0);
+        }
+
+        // A rotating black hole's power law is normalized to the peak of its own thin disk profile
+        let blackhole = BlackHole::new_kerr(1.0, DEFAULT_ACCRETION_RATE, 50.0, 0.9);
+        let options = SamplingOptions {
+            emission: EmissionModel::PowerLaw {
+                index: 3.0,
+                r_in: 3.0,
+            },
+            ..Default::default()
+        };
+        let samples = blackhole.sample_flux_at_points_with_options(Deg(30.0), 500, 0, &options);
+        assert!(samples.iter().all(|sample| sample.temperature <= 1.0));
+        assert!(samples
+            .iter()
+            .any(|sample| sample.radius < 4.0 && sample.temperature > 0.8));
+        let sample = &samples[0];
+        assert_relative_eq!(
+            blackhole.observed_monochromatic_flux(sample, 1.0),
+            calc_observed_monochromatic_flux(
+                sample.radius,
+                DEFAULT_ACCRETION_RATE,
+                1.0,
+                0.9,
+                calc_peak_intrinsic_flux_radius(1.0, 0.9),
+                sample.redshift_factor,
+                1.0
+            ),
+            max_relative = 1e-9
+        );
+    }
+
+    #[test]
+    fn test_no_doppler_beaming() {
+        let blackhole = BlackHole::default();
+        // The brightest sample on each side of the disk, approaching (`sin(alpha) < 0`) and
+        // receding
+        let max_fluxes = |options: &SamplingOptions| {
+            let samples = blackhole.sample_flux_at_points_with_options(Deg(80.0), 4000, 0, options);
+            let max_flux = |approaching: bool| {
+                samples
+                    .iter()
+                    .filter(|sample| (sample.alpha.sin() < 0.0) == approaching)
+                    .map(|sample| sample.observed_flux)
+                    .fold(0.0, f64::max)
+            };
+            (max_flux(true), max_flux(false))
+        };
+
+        let (approaching, receding) = max_fluxes(&SamplingOptions::default());
+        assert!(approaching > 2.0 * receding);
+
+        let (approaching, receding) = max_fluxes(&SamplingOptions {
+            no_doppler_beaming: true,
+            ..Default::default()
+        });
+        assert!((approaching - receding).abs() / approaching < 0.05);
+    }
+
+    #[test]
+    fn test_sample_flux_multi_order() {
+        let blackhole = BlackHole::default();
+        let samples = blackhole.sample_flux_multi_order(Deg(80.0), 300, &[0, 1, 2]);
+        assert_eq!(samples.len(), 3);
+        for (order, order_samples) in samples.iter().enumerate() {
+            assert_eq!(order_samples.len(), 300);
+            assert!(order_samples
+                .iter()
+                .all(|sample| sample.order as usize == order));
+            assert!(order_samples
+                .iter()
+                .zip(&samples[0])
+                .all(|(sample, direct)| {
+                    sample.radius == direct.radius && sample.alpha == direct.alpha
+                }));
+        }
+        assert!(samples[1]
+            .iter()
+            .zip(&samples[0])
+            .all(|(ghost, direct)| ghost.impact_parameter != direct.impact_parameter));
+
+        // Points with no solution are kept, so later points still pair up across orders
+        let samples = blackhole.sample_flux_multi_order(Deg(89.9), 300, &[0, 3]);
+        assert!(samples
+            .iter()
+            .all(|order_samples| order_samples.len() == 300));
+        assert!(
+            samples[1]
+                .iter()
+                .zip(&samples[0])
+                .all(|(sample, direct)| sample.radius == direct.radius
+                    && sample.alpha == direct.alpha)
+        );
+    }
+
+    #[test]
+    fn test_brightest_inclination() {
+        // Doppler beaming brightens the approaching side of the disk as it tilts edge-on, but not
+        // by enough to make up for the shrinking projected area
+        let blackhole = BlackHole::default();
+        let candidates = [10.0, 50.0, 85.0].map(|i| Rad::from(Deg(i)));
+        assert_eq!(
+            blackhole.brightest_inclination(1000, &candidates),
+            candidates[0]
+        );
+        assert!(
+            blackhole.integrated_observed_flux(Deg(85.0), 1000)
+                < 0.5 * blackhole.integrated_observed_flux(Deg(10.0), 1000)
+        );
+    }
+
+    #[test]
+    fn test_innermost_visible_radius() {
+        let blackhole = BlackHole::default();
+        for inclination in [10.0, 45.0, 80.0].map(|i| Rad::from(Deg(i))) {
+            for alpha in [0.0, 1.0, PI / 2.0, PI, 4.0].map(Rad) {
+                let radius = blackhole.innermost_visible_radius(inclination, alpha);
+                assert!((radius - blackhole.disk_inner_edge()).abs() < 1e-6);
+                let impact_parameter = IsoRadial::new(&blackhole, radius, 0)
+                    .get_impact_parameter_from_alpha(inclination, alpha);
+                assert_relative_eq!(
                     impact_parameter,
-                    order,
-                    redshift_factor,
-                    observed_flux,
-                }
+                    blackhole.apparent_inner_edge_radius(inclination, alpha),
+                    max_relative = 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_point_on_disk() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        // Along the major axis, where the image is rotated so that alpha = pi/2 is at +x
+        let (inner, outer) = (
+            blackhole.apparent_inner_edge_radius(inclination, Rad(PI / 2.0)),
+            blackhole.apparent_outer_edge_radius(inclination, Rad(PI / 2.0)),
+        );
+        let on_disk = |x| blackhole.is_point_on_disk(inclination, Vector2::new(x, 0.0));
+        assert_eq!(on_disk((inner + outer) / 2.0), Some(0));
+        assert_eq!(on_disk(0.0), None);
+        assert_eq!(on_disk(2.0 * outer), None);
+        // The ghost image lies inside the direct image's inner edge, with a gap between them
+        let ghost_edge = |radius| {
+            IsoRadial::new(&blackhole, radius, 1)
+                .get_impact_parameter_from_alpha(inclination, Rad(PI / 2.0))
+        };
+        let (ghost_inner, ghost_outer) = (
+            ghost_edge(blackhole.disk_inner_edge()),
+            ghost_edge(blackhole.disk_outer_edge()),
+        );
+        assert!(ghost_outer < inner);
+        assert_eq!(on_disk((ghost_inner + ghost_outer) / 2.0), Some(1));
+        assert_eq!(on_disk((ghost_outer + inner) / 2.0), None);
+
+        // Consistent with the rendered image
+        let fluxes = blackhole
+            .flux_at_observer_points(
+                inclination,
+                500,
+                &[Vector2::new(0.0, 0.0), Vector2::new(2.0 * outer, 0.0)],
+            )
+            .unwrap();
+        assert_eq!(fluxes, vec![None, None]);
+    }
+
+    #[test]
+    fn test_peak_flux_radius() {
+        let blackhole = BlackHole::new(2.0, DEFAULT_ACCRETION_RATE, 50.0);
+        assert_eq!(
+            blackhole.peak_flux_radius(),
+            calc_peak_intrinsic_flux_radius(2.0, 0.0)
+        );
+
+        // A spinning black hole's disk peaks further in, where its temperature is the peak one
+        let blackhole = BlackHole::new_kerr(2.0, DEFAULT_ACCRETION_RATE, 50.0, 0.9);
+        assert_relative_eq!(
+            blackhole.peak_flux_radius(),
+            calc_peak_intrinsic_flux_radius(2.0, 0.9),
+            max_relative = 1e-9
+        );
+        assert!(blackhole.peak_flux_radius() < BlackHole::default().peak_flux_radius() * 2.0);
+        let sample = blackhole
+            .sample_flux_at_point(
+                blackhole.peak_flux_radius(),
+                Rad(1.0),
+                Deg(80.0).into(),
+                0,
+                None,
+                FallbackMode::default(),
+                &SolverConfig::default(),
+            )
+            .unwrap();
+        assert_relative_eq!(sample.temperature, 1.0, max_relative = 1e-9);
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(BlackHole::try_new(1.0, DEFAULT_ACCRETION_RATE, 50.0).is_ok());
+        assert_eq!(
+            BlackHole::try_new(1.0, DEFAULT_ACCRETION_RATE, 3.0).err(),
+            Some(BlackHoleError::InvalidDiskOuterEdge {
+                disk_outer_edge: 3.0,
+                disk_inner_edge: 6.0
             })
-            .collect::<Vec<Sample>>()
+        );
+        assert!(BlackHole::try_new(1.0, DEFAULT_ACCRETION_RATE, 6.0).is_err());
+        for mass in [0.0, -1.0] {
+            assert_eq!(
+                BlackHole::try_new(mass, DEFAULT_ACCRETION_RATE, 50.0).err(),
+                Some(BlackHoleError::InvalidMass { mass })
+            );
+        }
+        assert_eq!(
+            BlackHole::try_new_kerr(1.0, DEFAULT_ACCRETION_RATE, 50.0, 1.0).err(),
+            Some(BlackHoleError::InvalidSpin { spin: 1.0 })
+        );
+        // A rapidly spinning black hole's disk extends inside the non-rotating inner edge
+        assert!(BlackHole::try_new_kerr(1.0, DEFAULT_ACCRETION_RATE, 5.0, 0.9).is_ok());
+    }
+
+    #[test]
+    fn test_builder() {
+        let blackhole = BlackHole::builder()
+            .mass(2.0)
+            .accretion_rate(1e-6)
+            .disk_outer_edge(30.0)
+            .build()
+            .unwrap();
+        assert_eq!(blackhole.mass, 2.0);
+        assert_eq!(blackhole.accretion_rate, 1e-6);
+        assert_eq!(blackhole.disk_outer_edge(), 60.0);
+        assert_eq!(blackhole.spin(), 0.0);
+        let default = BlackHole::builder().build().unwrap();
+        assert_eq!(
+            default.disk_outer_edge(),
+            BlackHole::default().disk_outer_edge()
+        );
+
+        assert_eq!(
+            BlackHole::builder().mass(0.0).build().err(),
+            Some(BlackHoleError::InvalidMass { mass: 0.0 })
+        );
+        assert!(BlackHole::builder().mass(f64::NAN).build().is_err());
+        assert_eq!(
+            BlackHole::builder().accretion_rate(-1.0).build().err(),
+            Some(BlackHoleError::InvalidAccretionRate {
+                accretion_rate: -1.0
+            })
+        );
+        assert_eq!(
+            BlackHole::builder().disk_outer_edge(-10.0).build().err(),
+            Some(BlackHoleError::InvalidDiskOuterEdge {
+                disk_outer_edge: -10.0,
+                disk_inner_edge: 6.0
+            })
+        );
+        assert!(BlackHole::builder()
+            .disk_outer_edge(f64::NAN)
+            .build()
+            .is_err());
+        assert_eq!(
+            BlackHole::builder().spin(-0.5).build().err(),
+            Some(BlackHoleError::InvalidSpin { spin: -0.5 })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let blackhole = BlackHole::builder()
+            .mass(2.0)
+            .spin(0.5)
+            .disk_inner_edge(5.0)
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&blackhole).unwrap();
+        let deserialized: BlackHole = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.mass, blackhole.mass);
+        assert_eq!(deserialized.accretion_rate, blackhole.accretion_rate);
+        assert_eq!(deserialized.disk_outer_edge(), blackhole.disk_outer_edge());
+        assert_eq!(deserialized.disk_inner_edge(), blackhole.disk_inner_edge());
+        assert_eq!(deserialized.spin(), blackhole.spin());
+        assert_eq!(
+            deserialized.apparent_inner_disk_edge(),
+            blackhole.apparent_inner_disk_edge()
+        );
+        assert_eq!(
+            deserialized.apparent_outer_disk_edge(),
+            blackhole.apparent_outer_disk_edge()
+        );
+
+        // Missing parameters take their defaults, and invalid ones are rejected
+        let deserialized: BlackHole = serde_json::from_str(r#"{"mass": 2.0}"#).unwrap();
+        assert_eq!(deserialized.mass, 2.0);
+        assert_eq!(
+            deserialized.disk_inner_edge(),
+            BlackHole::builder()
+                .mass(2.0)
+                .build()
+                .unwrap()
+                .disk_inner_edge()
+        );
+        assert!(serde_json::from_str::<BlackHole>(r#"{"mass": -1.0}"#).is_err());
+        assert!(serde_json::from_str::<BlackHole>(r#"{"mas": 1.0}"#).is_err());
+    }
+
+    #[test]
+    fn test_disk_inner_edge() {
+        let truncated = BlackHole::builder().disk_inner_edge(10.0).build().unwrap();
+        assert_eq!(truncated.disk_inner_edge(), 10.0);
+        let samples = truncated.sample_flux_at_points(Deg(80.0), 500, 0);
+        assert!(samples.iter().all(|sample| sample.radius >= 10.0));
+        let inclination = Rad::from(Deg(80.0));
+        assert!(
+            truncated.apparent_inner_edge_radius(inclination, Rad(0.0))
+                > BlackHole::default().apparent_inner_edge_radius(inclination, Rad(0.0))
+        );
+
+        // Inside the innermost stable circular orbit the disk is dark
+        let extended = BlackHole::builder().disk_inner_edge(4.5).build().unwrap();
+        let samples = extended.sample_flux_at_points(Deg(80.0), 500, 0);
+        assert!(samples.iter().any(|sample| sample.radius < 6.0));
+        for sample in &samples {
+            assert_eq!(sample.observed_flux == 0.0, sample.radius <= 6.0);
+        }
+
+        assert_eq!(
+            BlackHole::builder().disk_inner_edge(3.5).build().err(),
+            Some(BlackHoleError::InvalidDiskInnerEdge {
+                disk_inner_edge: 3.5,
+                marginally_bound_radius: 4.0
+            })
+        );
+        assert!(BlackHole::builder().disk_inner_edge(60.0).build().is_err());
+        // A rapidly spinning black hole's marginally bound orbit is further in
+        assert!(BlackHole::builder()
+            .spin(0.9)
+            .disk_inner_edge(3.5)
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_kerr_without_spin_matches_schwarzschild() {
+        let inclination = Rad::from(Deg(80.0));
+        let schwarzschild = BlackHole::default();
+        let kerr = BlackHole::new_kerr(1.0, DEFAULT_ACCRETION_RATE, 50.0, 0.0);
+        assert_eq!(kerr.disk_inner_edge(), schwarzschild.disk_inner_edge());
+        let fluxes = |blackhole: &BlackHole| {
+            blackhole
+                .sample_flux_at_points_seeded(inclination, 200, 0, 0)
+                .iter()
+                .map(|sample| (sample.impact_parameter, sample.observed_flux))
+                .collect::<Vec<(f64, f64)>>()
+        };
+        assert_eq!(fluxes(&kerr), fluxes(&schwarzschild));
+
+        let spinning = BlackHole::new_kerr(1.0, DEFAULT_ACCRETION_RATE, 50.0, 0.9);
+        let samples = spinning.sample_flux_at_points_seeded(inclination, 200, 0, 0);
+        assert!(samples.iter().any(|sample| sample.radius < 6.0));
+        assert!(samples
+            .iter()
+            .all(|sample| sample.observed_flux.is_finite() && sample.observed_flux >= 0.0));
+    }
+
+    #[test]
+    fn test_seeded_sampling_independent_of_thread_count() {
+        let blackhole = BlackHole::default();
+        let sample_with_threads = |num_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap()
+                .install(|| blackhole.sample_flux_at_points_seeded(Deg(80.0), 2500, 0, 42))
+        };
+        let samples_a = sample_with_threads(1);
+        let samples_b = sample_with_threads(4);
+        assert_eq!(samples_a.len(), 2500);
+        for (a, b) in samples_a.iter().zip(samples_b.iter()) {
+            assert_eq!(a.radius, b.radius);
+            assert_eq!(a.alpha, b.alpha);
+            assert_eq!(a.impact_parameter, b.impact_parameter);
+        }
+
+        let other_seed = blackhole.sample_flux_at_points_seeded(Deg(80.0), 2500, 0, 43);
+        assert_ne!(samples_a[0].radius, other_seed[0].radius);
+    }
+
+    #[test]
+    fn test_consecutive_seeds_sample_disjoint_points() {
+        let blackhole = BlackHole::default();
+        let points = |seed| {
+            blackhole
+                .sample_flux_at_points_seeded(Deg(80.0), 3000, 0, seed)
+                .iter()
+                .map(|sample| (sample.radius.to_bits(), sample.alpha.0.to_bits()))
+                .collect::<std::collections::HashSet<(u64, u64)>>()
+        };
+        let (a, b) = (points(0), points(1));
+        assert_eq!(a.len(), 3000);
+        assert!(a.is_disjoint(&b));
+    }
+
+    #[test]
+    fn test_no_fallback_drops_unsolved_samples() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        // No periastron solution exists for the near side of the inner edge of the disk
+        assert!(blackhole
+            .sample_flux_at_point(
+                6.0,
+                Rad(0.0),
+                inclination,
+                0,
+                None,
+                FallbackMode::None,
+                &SolverConfig::default()
+            )
+            .is_none());
+        assert!(blackhole
+            .sample_flux_at_point(
+                6.0,
+                Rad(0.0),
+                inclination,
+                0,
+                None,
+                FallbackMode::Ellipse,
+                &SolverConfig::default()
+            )
+            .is_some());
+
+        let options = SamplingOptions {
+            fallback: FallbackMode::None,
+            ..Default::default()
+        };
+        let samples = blackhole.sample_flux_at_points_with_options(inclination, 500, 0, &options);
+        assert!(samples.len() < 500);
+        assert!(samples
+            .iter()
+            .all(|sample| sample.impact_parameter_source == ImpactParameterSource::Periastron));
+
+        // With the ellipse fallback, the samples which would have been dropped are flagged instead
+        let samples = blackhole.sample_flux_at_points_seeded(inclination, 500, 0, 0);
+        assert_eq!(samples.len(), 500);
+        let fallback_samples = samples
+            .iter()
+            .filter(|sample| {
+                sample.impact_parameter_source == ImpactParameterSource::EllipseFallback
+            })
+            .collect::<Vec<_>>();
+        assert!(!fallback_samples.is_empty());
+        assert!(fallback_samples.iter().all(|sample| {
+            try_calc_impact_parameter(
+                sample.radius,
+                inclination,
+                sample.alpha,
+                blackhole.mass,
+                0,
+                FallbackMode::None,
+                &SolverConfig::default(),
+            )
+            .is_none()
+        }));
+    }
+
+    #[test]
+    fn test_azimuth_range() {
+        let blackhole = BlackHole::default();
+        let azimuth_range = 0.0..=PI;
+        let options = SamplingOptions {
+            azimuth_range: Some(azimuth_range.clone()),
+            ..Default::default()
+        };
+        for order in [0, 1] {
+            let samples =
+                blackhole.sample_flux_at_points_with_options(Deg(80.0), 1000, order, &options);
+            assert_eq!(samples.len(), 1000);
+            assert!(samples
+                .iter()
+                .all(|sample| azimuth_range.contains(&sample.alpha.0)));
+        }
+
+        // A range whose start is past its end wraps around through alpha = 0
+        let options = SamplingOptions {
+            azimuth_range: Some(3.0 * PI / 2.0..=PI / 2.0),
+            ..Default::default()
+        };
+        let samples = blackhole.sample_flux_at_points_with_options(Deg(80.0), 1000, 0, &options);
+        assert_eq!(samples.len(), 1000);
+        assert!(samples
+            .iter()
+            .all(|sample| sample.alpha.0 <= PI / 2.0 || sample.alpha.0 >= 3.0 * PI / 2.0));
+        assert!(samples.iter().any(|sample| sample.alpha.0 < PI / 4.0));
+        assert!(samples.iter().any(|sample| sample.alpha.0 > 7.0 * PI / 4.0));
+
+        // Ranges beyond [0, 2 pi] are taken modulo 2 pi, and non-finite ones cover the whole disk
+        assert_eq!(azimuth_wedge(Some(&(-PI..=0.0))), PI..=2.0 * PI);
+        assert_eq!(azimuth_wedge(Some(&(0.0..=3.0 * PI))), 0.0..=2.0 * PI);
+        assert_eq!(azimuth_wedge(Some(&(f64::NAN..=PI))), 0.0..=2.0 * PI);
+        assert_eq!(azimuth_wedge(None), 0.0..=2.0 * PI);
+    }
+
+    #[test]
+    fn test_apparent_disk_eccentricity() {
+        let blackhole = BlackHole::default();
+        let eccentricities = [1.0, 30.0, 60.0, 89.0]
+            .map(|inclination| blackhole.apparent_disk_eccentricity(Deg(inclination)));
+        assert!(eccentricities[0] < 0.1);
+        assert!(eccentricities[3] > 0.99);
+        assert!(eccentricities.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_magnification_diverges_at_caustic() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(89.5);
+
+        // Near edge-on, the unlensed far side of the disk collapses onto a line, while its lensed
+        // image does not
+        let magnifications = [PI / 2.0, PI - 0.5, PI - 0.2, PI - 0.05]
+            .map(|alpha| blackhole.magnification_at(20.0, Rad(alpha), inclination, 0));
+        assert!(magnifications.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(magnifications[3] > 10.0 * magnifications[0]);
+
+        // Exactly edge-on, the unlensed points in front of and behind the black hole lie on the
+        // line of sight, but the magnification stays finite
+        let front = blackhole.magnification_at(20.0, Rad(0.0), Deg(90.0), 0);
+        let behind = blackhole.magnification_at(20.0, Rad(PI), Deg(90.0), 0);
+        assert!(front.is_finite() && behind.is_finite());
+        assert!(behind > magnifications[3]);
+
+        // Far from the black hole, there is little lensing when face-on
+        let magnification = blackhole.magnification_at(45.0, Rad(1.0), Deg(5.0), 0);
+        assert!((magnification - 1.0).abs() < 0.2);
+    }
+
+    #[test]
+    fn test_photon_ring_width() {
+        let blackhole = BlackHole::default();
+        let width = blackhole.photon_ring_width(Deg(80.0), 2000, 0.5).unwrap();
+        assert!(width > 0.0);
+        assert!(width < blackhole.disk_outer_edge() - blackhole.disk_inner_edge());
+
+        // Without any samples in the ring there is no width to measure
+        assert_eq!(blackhole.photon_ring_width(Deg(80.0), 0, 0.5), None);
+    }
+
+    #[test]
+    fn test_radial_flux_profile() {
+        let blackhole = BlackHole::default();
+        let radius_bins = 22;
+        let profile = blackhole.radial_flux_profile(Deg(30.0), 5000, radius_bins);
+        assert_eq!(profile.len(), radius_bins);
+
+        let bin_width =
+            (blackhole.disk_outer_edge() - blackhole.disk_inner_edge()) / radius_bins as f64;
+        let (peak_radius, _) = profile
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+        assert!((peak_radius - blackhole.peak_flux_radius()).abs() <= bin_width);
+        assert!(profile.last().unwrap().1 < profile[0].1);
+
+        assert!(blackhole.radial_flux_profile(Deg(30.0), 100, 0).is_empty());
+    }
+
+    #[test]
+    fn test_hotspot_light_curve() {
+        let blackhole = BlackHole::default();
+        let hotspot = Hotspot {
+            radius: 10.0,
+            initial_alpha: Rad(0.0),
+            size: 0.5,
+            brightness: 1.0,
+        };
+        let phase_count = 36;
+        let phases = (0..phase_count)
+            .map(|i| 2.0 * PI * f64::from(i) / f64::from(phase_count))
+            .collect::<Vec<f64>>();
+        let light_curve = blackhole.hotspot_light_curve(&hotspot, Deg(30.0), &phases);
+        assert!(light_curve
+            .iter()
+            .all(|flux| flux.is_finite() && *flux > 0.0));
+
+        // Periodic in the orbital phase
+        let next_orbit = phases
+            .iter()
+            .map(|phase| phase + 2.0 * PI)
+            .collect::<Vec<f64>>();
+        for (flux, next_flux) in
+            light_curve
+                .iter()
+                .zip(blackhole.hotspot_light_curve(&hotspot, Deg(30.0), &next_orbit))
+        {
+            assert!((flux - next_flux).abs() <= 1e-9 * flux);
+        }
+
+        // Brightest on the approaching side of the disk, where sin(alpha) < 0
+        let peak_phase = phases[light_curve
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap()
+            .0];
+        assert!(hotspot.alpha_at_phase(peak_phase).sin() < 0.0);
+    }
+
+    #[test]
+    fn test_time_averaged_flux_image() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(30.0);
+        let max_disk_flux = blackhole
+            .sample_flux_at_points(inclination, 2000, 0)
+            .iter()
+            .map(|sample| sample.observed_flux)
+            .fold(0.0, f64::max);
+        let hotspot = Hotspot {
+            radius: 15.0,
+            initial_alpha: Rad(0.0),
+            size: 2.0,
+            brightness: 10.0 * max_disk_flux,
+        };
+        let (width, height) = (64, 48);
+        let variance = |img: &crate::plotting::Luma16Image| {
+            let lumas = img
+                .iter()
+                .map(|&luma| f64::from(luma))
+                .collect::<Vec<f64>>();
+            let mean = lumas.iter().sum::<f64>() / lumas.len() as f64;
+            lumas.iter().map(|luma| (luma - mean).powi(2)).sum::<f64>() / lumas.len() as f64
+        };
+
+        // The hotspot is smeared around its orbit, so the average is smoother than any one phase
+        let averaged = blackhole
+            .time_averaged_flux_image(&hotspot, inclination, 1000, width, height, 8)
+            .unwrap();
+        for i in 0..4 {
+            let frame = blackhole
+                .time_averaged_flux_image(
+                    &Hotspot {
+                        initial_alpha: Rad(f64::from(i) * PI / 2.0),
+                        ..hotspot
+                    },
+                    inclination,
+                    1000,
+                    width,
+                    height,
+                    1,
+                )
+                .unwrap();
+            assert!(variance(&averaged) < variance(&frame));
+        }
+
+        assert!(blackhole
+            .time_averaged_flux_image(&hotspot, inclination, 100, width, height, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_image_flux_ratio() {
+        let blackhole = BlackHole::default();
+        for inclination in [10.0, 45.0, 80.0] {
+            for radius in [7.0, 15.0, 40.0] {
+                for alpha in [0.0, PI / 2.0, PI, 3.0 * PI / 2.0] {
+                    let ratio = blackhole.image_flux_ratio(radius, Rad(alpha), Deg(inclination));
+                    assert!(ratio > 0.0 && ratio < 1.0);
+                }
+            }
+        }
     }
 }
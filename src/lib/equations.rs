@@ -2,14 +2,35 @@
 //!
 //! Note that several equation have errors in the paper. These are noted in the relevant
 //! function documentation.
+//!
+//! The paths of photons (`Q`, `k` and the elliptic integrals) are those of the Schwarzschild
+//! metric. A black hole's spin only enters through the disk: its inner edge, its intrinsic flux,
+//! and the redshift of its orbiting material. This neglects frame dragging of the photons, so is
+//! an approximation for rapidly rotating black holes.
 
 use cgmath::{Angle, Rad};
 use spec_math::Ellip;
-use std::f64::consts::PI;
+use std::f64::consts::{FRAC_PI_2, PI};
 
 const INCLINATION_TOLERANCE: Rad<f64> = Rad(1e-5);
 
+/// Inclinations closer than this to edge-on are treated as this far from edge-on, so that `gamma`
+/// stays well defined exactly at edge-on without changing the result at any other inclination.
+const EDGE_ON_INCLINATION_TOLERANCE: Rad<f64> = Rad(1e-9);
+
+/// The radius at which the intrinsic flux of the disk peaks, in units of black hole mass.
+pub const PEAK_INTRINSIC_FLUX_RADIUS: f64 = 7.913_318;
+
+/// The peak intrinsic flux of a rotating black hole's disk is searched for out to this multiple of
+/// the radius of its innermost stable circular orbit.
+const PEAK_SEARCH_ISCO_MULTIPLE: f64 = 4.0;
+/// Number of golden section iterations used to find the radius of peak intrinsic flux.
+const PEAK_SEARCH_ITERATIONS: usize = 100;
+
 /// Calculate `Q` from the periastron `P` (pg 229).
+///
+/// This is the Schwarzschild form, used whatever the black hole's spin, as photon paths aren't
+/// traced through the Kerr metric.
 pub fn calc_q(periastron: f64, mass: f64) -> f64 {
     ((periastron - 2.0 * mass) * (periastron + 6.0 * mass)).sqrt()
 }
@@ -22,10 +43,26 @@ pub fn calc_impact_parameter_from_periastron(periastron: f64, mass: f64) -> f64
     (periastron.powi(3) / (periastron - 2.0 * mass)).sqrt()
 }
 
+/// Calculate the periastron `P` from the impact parameter `b`, by inverting eqn 5.
+///
+/// This is the largest root of the cubic `P^3 - b^2 P + 2 M b^2 = 0`. Photons with an impact
+/// parameter at or below the critical value `3 sqrt(3) M` are captured, so have no periastron and
+/// give NaN.
+pub fn calc_periastron_from_impact_parameter(impact_parameter: f64, mass: f64) -> f64 {
+    let critical_impact_parameter = 3.0 * 3.0_f64.sqrt() * mass;
+    if impact_parameter <= critical_impact_parameter {
+        return f64::NAN;
+    }
+    (2.0 * impact_parameter / 3.0_f64.sqrt())
+        * ((-critical_impact_parameter / impact_parameter).acos() / 3.0).cos()
+}
+
 /// Calculate the modulus `k^2` of the elliptic integral (eqn 12).
 ///
 /// While equation 12 in the paper is correct, the definition of `k` on page 229 has an error,
 /// the numerator should be in parentheses.
+///
+/// Like [`calc_q`], this is the Schwarzschild form, used whatever the black hole's spin.
 pub fn calc_modulus(periastron: f64, mass: f64, q: Option<f64>) -> f64 {
     let q = q.unwrap_or_else(|| calc_q(periastron, mass));
     (q - periastron + 6.0 * mass) / (2.0 * q)
@@ -40,11 +77,17 @@ pub fn calc_zeta_inf(periastron: f64, mass: f64, q: Option<f64>) -> f64 {
 }
 
 /// Calculate the cosine of angle `gamma` (eqn 10).
+///
+/// For an edge-on disk `1 / tan^2(inclination)` vanishes and the result degenerates to the sign of
+/// `cos(alpha)`, which is dominated by rounding error near `alpha = ±pi/2`, so inclinations within
+/// [`EDGE_ON_INCLINATION_TOLERANCE`] of edge-on are limited to that tolerance.
 pub fn calc_cos_gamma(alpha: Rad<f64>, inclination: Rad<f64>) -> f64 {
     if inclination < INCLINATION_TOLERANCE {
         return 0.0;
     }
-    alpha.cos() / (alpha.cos().powi(2) + 1.0 / inclination.tan().powi(2)).sqrt()
+    let cot_squared =
+        (1.0 / inclination.tan().powi(2)).max(EDGE_ON_INCLINATION_TOLERANCE.0.tan().powi(2));
+    alpha.cos() / (alpha.cos().powi(2) + cot_squared).sqrt()
 }
 
 /// Calculate the cosine of the angle in the observer's reference frame `alpha`, from an angle
@@ -54,6 +97,32 @@ pub fn calc_cos_alpha(phi: f64, inclination: Rad<f64>) -> f64 {
     (phi.cos() * inclination.cos()) / (1.0 - inclination.sin().powi(2) * phi.cos().powi(2)).sqrt()
 }
 
+/// Calculate the total bending angle of a photon passing the black hole with the given impact
+/// parameter.
+///
+/// This is the angle swept by the photon (see [`calc_sweep_angle`]) less the `pi` of an
+/// undeflected path. Captured photons, with an impact parameter at or below the critical value,
+/// give infinity.
+pub fn calc_bending_angle(impact_parameter: f64, mass: f64) -> f64 {
+    let periastron = calc_periastron_from_impact_parameter(impact_parameter, mass);
+    if periastron.is_nan() {
+        return f64::INFINITY;
+    }
+    calc_sweep_angle(periastron, mass) - PI
+}
+
+/// Calculate the total polar angle swept by a photon with the given periastron, between arriving
+/// from and escaping back to infinity.
+///
+/// The photon's polar angle sweeps `2 sqrt(P/Q) (K(k) - F(zeta_inf, k))` between infinity and
+/// periastron (eqn 13 with `sn = 0`), and the same again on the way back out.
+pub fn calc_sweep_angle(periastron: f64, mass: f64) -> f64 {
+    let q = calc_q(periastron, mass);
+    let zeta_inf = calc_zeta_inf(periastron, mass, Some(q));
+    let modulus = calc_modulus(periastron, mass, Some(q));
+    4.0 * (periastron / q).sqrt() * (modulus.ellip_k() - zeta_inf.ellip_k_inc(modulus))
+}
+
 /// Calculate the reciprocal of `r` (eqn 13).
 ///
 /// Note that the paper has an error in this equation, the `sqrt(P/Q)` factor in the first term
@@ -86,6 +155,78 @@ pub fn calc_one_over_radius(
         + ((q - periastron + 6.0 * mass) / (4.0 * mass * periastron)) * elliptic_sine.powi(2)
 }
 
+/// Calculate `1/r` (eqn 13) and its derivative with respect to the periastron `P`, sharing the
+/// elliptic integrals between them.
+///
+/// `Q`, `k^2`, `zeta_inf` and so the argument of `sn` all depend on `P`. The derivatives of the
+/// elliptic integrals and `sn` with respect to `k^2` are written in terms of the incomplete
+/// elliptic integral of the second kind.
+pub fn calc_one_over_radius_with_derivative(
+    periastron: f64,
+    alpha: Rad<f64>,
+    mass: f64,
+    inclination: Rad<f64>,
+    order: u32,
+) -> (f64, f64) {
+    let q = calc_q(periastron, mass);
+    let dq = (periastron + 2.0 * mass) / q;
+    let zeta_inf = calc_zeta_inf(periastron, mass, Some(q));
+    let modulus = calc_modulus(periastron, mass, Some(q));
+    let elliptic_inf = zeta_inf.ellip_k_inc(modulus);
+    let (numerator, denominator) = (q - periastron + 2.0 * mass, q - periastron + 6.0 * mass);
+    // Both the numerator and denominator of `k^2` and `sin^2(zeta_inf)` vary as `Q - P`
+    let d_numerator = dq - 1.0;
+    let d_modulus = (d_numerator * q - denominator * dq) / (2.0 * q.powi(2));
+    let d_sin_squared_zeta_inf = d_numerator * (denominator - numerator) / denominator.powi(2);
+    let d_zeta_inf = d_sin_squared_zeta_inf / (2.0 * zeta_inf.sin() * zeta_inf.cos());
+
+    // Partial derivative of `F(phi, k)` with respect to `k^2`, given `F(phi, k)`
+    let d_ellip_k_inc_d_modulus = |phi: f64, ellip_k_inc: f64| {
+        (phi.ellip_e_inc(modulus) - (1.0 - modulus) * ellip_k_inc)
+            / (2.0 * modulus * (1.0 - modulus))
+            - phi.sin() * phi.cos()
+                / (2.0 * (1.0 - modulus) * (1.0 - modulus * phi.sin().powi(2)).sqrt())
+    };
+    let d_elliptic_inf = d_zeta_inf / (1.0 - modulus * zeta_inf.sin().powi(2)).sqrt()
+        + d_ellip_k_inc_d_modulus(zeta_inf, elliptic_inf) * d_modulus;
+
+    let gamma = calc_cos_gamma(alpha, inclination).acos();
+    let scale = 1.0 / (2.0 * (periastron / q).sqrt());
+    let d_scale = (dq * periastron - q) / (4.0 * periastron.powi(2) * (q / periastron).sqrt());
+    let (jacobian_elliptic_arg, d_jacobian_elliptic_arg) = if order == 0 {
+        (
+            gamma * scale + elliptic_inf,
+            gamma * d_scale + d_elliptic_inf,
+        )
+    } else {
+        let elliptic_k = modulus.ellip_k();
+        // `ellip_e` is evaluated at `1 - k^2` rather than `k^2`, so use the incomplete integral
+        let elliptic_e = FRAC_PI_2.ellip_e_inc(modulus);
+        let d_elliptic_k =
+            (elliptic_e - (1.0 - modulus) * elliptic_k) / (2.0 * modulus * (1.0 - modulus));
+        let shifted_gamma = gamma - 2.0 * (order as f64) * PI;
+        (
+            shifted_gamma * scale - elliptic_inf + 2.0 * elliptic_k,
+            shifted_gamma * d_scale - d_elliptic_inf + 2.0 * d_elliptic_k * d_modulus,
+        )
+    };
+    // `sn(u, k) = sin(am(u, k))`, where the amplitude is found by inverting `F(am, k) = u`
+    let jacobi = jacobian_elliptic_arg.ellip_j(modulus);
+    let d_elliptic_sine = jacobi.cn
+        * jacobi.dn
+        * (d_jacobian_elliptic_arg
+            - d_ellip_k_inc_d_modulus(jacobi.phi, jacobian_elliptic_arg) * d_modulus);
+
+    let one_over_radius =
+        (-numerator + denominator * jacobi.sn.powi(2)) / (4.0 * mass * periastron);
+    let derivative = (-d_numerator
+        + d_numerator * jacobi.sn.powi(2)
+        + 2.0 * denominator * jacobi.sn * d_elliptic_sine)
+        / (4.0 * mass * periastron)
+        - one_over_radius / periastron;
+    (one_over_radius, derivative)
+}
+
 /// Calculate the difference between the equation for `1/r` (eqn 13) and the reciprocal of the
 /// given radius value. This value should be small in magnitude if the supplied periastron value
 /// is the actual periastron value for the emitted photon.
@@ -102,8 +243,51 @@ pub fn calc_one_over_radius_minus_one_over_radius(
     1.0 - radius * calc_one_over_radius(periastron, alpha, mass, inclination, order)
 }
 
-/// Calculate the intrinsic flux of the disk `F_s` (eqn 15).
-pub fn calc_intrinsic_flux(radius: f64, accretion_rate: f64, mass: f64) -> f64 {
+/// Calculate the radius of the innermost stable circular orbit of a black hole with the given
+/// dimensionless spin `a/M`, for a prograde orbit (Bardeen, Press & Teukolsky 1972).
+///
+/// This is `6M` for a non-rotating black hole, falling towards `M` as the spin approaches one.
+pub fn calc_isco_radius(mass: f64, spin: f64) -> f64 {
+    let z1 = 1.0 + (1.0 - spin.powi(2)).cbrt() * ((1.0 + spin).cbrt() + (1.0 - spin).cbrt());
+    let z2 = (3.0 * spin.powi(2) + z1.powi(2)).sqrt();
+    (3.0 + z2 - ((3.0 - z1) * (3.0 + z1 + 2.0 * z2)).sqrt()) * mass
+}
+
+/// Calculate the radius of the marginally bound circular orbit of a black hole with the given
+/// dimensionless spin `a/M`, for a prograde orbit (Bardeen, Press & Teukolsky 1972), inside which
+/// no circular orbit is bound.
+///
+/// This is `4M` for a non-rotating black hole.
+pub fn calc_marginally_bound_radius(mass: f64, spin: f64) -> f64 {
+    (2.0 - spin + 2.0 * (1.0 - spin).sqrt()) * mass
+}
+
+/// Calculate the intrinsic flux of the disk `F_s` (eqn 15), for a black hole with the given
+/// dimensionless spin `a/M`.
+///
+/// A non-rotating black hole uses eqn 15 as written, which is zero at the innermost stable circular
+/// orbit (`r = 6M`). Inside it, where material plunges inwards without radiating and the equation is
+/// negative or undefined, this returns zero.
+///
+/// For a rotating black hole, the change in the flux of Page & Thorne (1974) from that without spin
+/// is added to eqn 15, so that the disk extends inwards to the innermost stable circular orbit for
+/// that spin and the flux tends to that of eqn 15 as the spin tends to zero.
+pub fn calc_intrinsic_flux(radius: f64, accretion_rate: f64, mass: f64, spin: f64) -> f64 {
+    let flux = calc_intrinsic_flux_schwarzschild(radius, accretion_rate, mass);
+    if spin == 0.0 {
+        return flux;
+    }
+    let spin_change = calc_intrinsic_flux_page_thorne(radius, accretion_rate, mass, spin)
+        - calc_intrinsic_flux_page_thorne(radius, accretion_rate, mass, 0.0);
+    (flux + spin_change).max(0.0)
+}
+
+/// Calculate the intrinsic flux of the disk around a non-rotating black hole (eqn 15), which is
+/// zero at and inside the innermost stable circular orbit.
+fn calc_intrinsic_flux_schwarzschild(radius: f64, accretion_rate: f64, mass: f64) -> f64 {
+    if radius <= 6.0 * mass {
+        return 0.0;
+    }
     let radius_star = radius / mass;
     let log_arg = ((radius_star.sqrt() + 3.0_f64.sqrt()) * (6.0_f64.sqrt() - 3.0_f64.sqrt()))
         / ((radius_star.sqrt() - 3.0_f64.sqrt()) * (6.0_f64.sqrt() + 3.0_f64.sqrt()));
@@ -112,29 +296,231 @@ pub fn calc_intrinsic_flux(radius: f64, accretion_rate: f64, mass: f64) -> f64 {
         * (radius_star.sqrt() - 6.0_f64.sqrt() + (3.0_f64.sqrt() / 3.0) * log_arg.log10())
 }
 
-/// Calculate the observed flux `F_O` (pg 233).
+/// Calculate the intrinsic flux of a disk with a power law emissivity `F_s ∝ r^-index`, zero inside
+/// `inner_radius` and normalized so that it there matches the peak intrinsic flux of eqn 15 (or of
+/// Page & Thorne for a rotating black hole), found at `peak_flux_radius` as per
+/// [`calc_temperature`].
+pub fn calc_power_law_intrinsic_flux(
+    radius: f64,
+    accretion_rate: f64,
+    mass: f64,
+    spin: f64,
+    peak_flux_radius: f64,
+    index: f64,
+    inner_radius: f64,
+) -> f64 {
+    if radius < inner_radius {
+        return 0.0;
+    }
+    let peak_flux = calc_intrinsic_flux(peak_flux_radius, accretion_rate, mass, spin);
+    peak_flux * (radius / inner_radius).powf(-index)
+}
+
+/// Calculate the intrinsic flux of the disk around a black hole from Page & Thorne (1974), which is
+/// zero at and inside the innermost stable circular orbit.
+///
+/// With `x = sqrt(r/M)`, this is `3 M Mdot / (8 pi) * B(x) / (x^4 (x^3 - 3x + 2a))`, where `B` is the
+/// bracketed term of Page & Thorne in terms of `x`, `x_0` at the innermost stable circular orbit and
+/// the roots `x_i` of `x^3 - 3x + 2a = 0`. Without spin, this differs from eqn 15 as written in the
+/// paper, which has a smaller logarithmic term.
+fn calc_intrinsic_flux_page_thorne(radius: f64, accretion_rate: f64, mass: f64, spin: f64) -> f64 {
+    let isco_radius = calc_isco_radius(mass, spin);
+    if radius <= isco_radius {
+        return 0.0;
+    }
+    let x = (radius / mass).sqrt();
+    let x0 = (isco_radius / mass).sqrt();
+    let third_acos = spin.acos() / 3.0;
+    let roots = [
+        2.0 * (third_acos - PI / 3.0).cos(),
+        2.0 * (third_acos + PI / 3.0).cos(),
+        -2.0 * third_acos.cos(),
+    ];
+    let mut bracket = x - x0 - 1.5 * spin * (x / x0).ln();
+    for (i, &root) in roots.iter().enumerate() {
+        if root == spin {
+            // The term vanishes for the root at zero without spin
+            continue;
+        }
+        let others = roots
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &other)| root - other)
+            .product::<f64>();
+        bracket -= 3.0 * (root - spin).powi(2) / (root * others) * ((x - root) / (x0 - root)).ln();
+    }
+    ((3.0 * mass * accretion_rate) / (8.0 * PI)) * bracket
+        / (x.powi(4) * (x.powi(3) - 3.0 * x + 2.0 * spin))
+}
+
+/// Calculate the observed bolometric specific intensity `I_O` of the disk, per unit solid angle.
+///
+/// The disk radiates as a Lambertian surface, so its emitted intensity is `I_s = F_s / pi`. Since
+/// `I_nu / nu^3` is invariant along a ray, integrating over frequency gives
+/// `I_O = I_s / (1 + z)^4`, so surface brightness is preserved apart from the redshift. This is
+/// the quantity an image pixel measures, whereas [`calc_observed_flux`] is `pi` times it.
+///
+/// Returns zero wherever the intrinsic flux is zero, where there is no disk.
+pub fn calc_specific_intensity(
+    radius: f64,
+    accretion_rate: f64,
+    mass: f64,
+    spin: f64,
+    redshift_factor: f64,
+) -> f64 {
+    let intrinsic_flux = calc_intrinsic_flux(radius, accretion_rate, mass, spin);
+    if intrinsic_flux == 0.0 {
+        // Avoid an undefined redshift factor inside the photon sphere
+        return 0.0;
+    }
+    intrinsic_flux / PI / redshift_factor.powi(4)
+}
+
+/// Calculate the observed flux `F_O` (pg 233), from the gravitational redshift and Doppler factor
+/// whose product is the redshift factor `1 + z`.
+///
+/// Passing a Doppler factor of one leaves out Doppler beaming, so that the approaching and
+/// receding sides of the disk are equally bright.
 pub fn calc_observed_flux(
     radius: f64,
     accretion_rate: f64,
     mass: f64,
+    spin: f64,
+    gravitational_redshift: f64,
+    doppler_factor: f64,
+) -> f64 {
+    let intrinsic_flux = calc_intrinsic_flux(radius, accretion_rate, mass, spin);
+    if intrinsic_flux == 0.0 {
+        // Avoid an undefined redshift factor inside the photon sphere
+        return 0.0;
+    }
+    intrinsic_flux / (gravitational_redshift * doppler_factor).powi(4)
+}
+
+/// Calculate the weight that linearly ramps the disk's emission from zero at its inner edge up to
+/// one at `taper_width` further out, for a soft inner edge. A width of zero gives a hard edge.
+pub fn calc_inner_edge_taper(radius: f64, inner_edge: f64, taper_width: f64) -> f64 {
+    if taper_width <= 0.0 {
+        return 1.0;
+    }
+    ((radius - inner_edge) / taper_width).clamp(0.0, 1.0)
+}
+
+/// Calculate the radius at which the intrinsic flux of the disk peaks, for a black hole with the
+/// given dimensionless spin `a/M`.
+///
+/// This is [`PEAK_INTRINSIC_FLUX_RADIUS`] for a non-rotating black hole, and is otherwise found by
+/// a golden section search outwards from the innermost stable circular orbit.
+pub fn calc_peak_intrinsic_flux_radius(mass: f64, spin: f64) -> f64 {
+    if spin == 0.0 {
+        return PEAK_INTRINSIC_FLUX_RADIUS * mass;
+    }
+    let flux = |radius: f64| calc_intrinsic_flux(radius, 1.0, mass, spin);
+    let inverse_golden_ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let isco_radius = calc_isco_radius(mass, spin);
+    let (mut low, mut high) = (isco_radius, PEAK_SEARCH_ISCO_MULTIPLE * isco_radius);
+    for _ in 0..PEAK_SEARCH_ITERATIONS {
+        let lower = high - inverse_golden_ratio * (high - low);
+        let upper = low + inverse_golden_ratio * (high - low);
+        if flux(lower) > flux(upper) {
+            high = upper;
+        } else {
+            low = lower;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Calculate the local temperature of the disk, in units of the temperature at the radius of peak
+/// intrinsic flux, assuming the disk radiates as a black body (`F_s ∝ T^4`).
+///
+/// The radius of peak intrinsic flux is that of [`calc_peak_intrinsic_flux_radius`], which is
+/// passed in as it's costly to find for a rotating black hole.
+pub fn calc_temperature(
+    radius: f64,
+    accretion_rate: f64,
+    mass: f64,
+    spin: f64,
+    peak_flux_radius: f64,
+) -> f64 {
+    let peak_flux = calc_intrinsic_flux(peak_flux_radius, accretion_rate, mass, spin);
+    (calc_intrinsic_flux(radius, accretion_rate, mass, spin) / peak_flux).powf(0.25)
+}
+
+/// Calculate the observed monochromatic flux at the given frequency, in units of `k T / h` for the
+/// peak temperature of the disk, with the radius of peak intrinsic flux as per
+/// [`calc_temperature`].
+///
+/// The disk emits locally as a black body, and since `I_ν / ν^3` is invariant along the ray, the
+/// observed intensity is the Planck function at the emitted frequency `ν (1 + z)`, divided by
+/// `(1 + z)^3`.
+pub fn calc_observed_monochromatic_flux(
+    radius: f64,
+    accretion_rate: f64,
+    mass: f64,
+    spin: f64,
+    peak_flux_radius: f64,
     redshift_factor: f64,
+    frequency: f64,
 ) -> f64 {
-    calc_intrinsic_flux(radius, accretion_rate, mass) / redshift_factor.powi(4)
+    let temperature = calc_temperature(radius, accretion_rate, mass, spin, peak_flux_radius);
+    frequency.powi(3) / (frequency * redshift_factor / temperature).exp_m1()
 }
 
-/// Calculate the gravitational redshift factor `1 + z`, ignoring cosmological redshift (eqn 19).
+/// Calculate the redshift factor `1 + z`, ignoring cosmological redshift (eqn 19).
 ///
 /// Note that while equation 19 is correct, the unlabelled but presumed equation 18 above is missing
 /// several terms, it should read `1 + z = (1 - Ω*b*cos(η)) * (-g_tt -2*Ω*g_tϕ - Ω²*g_ϕϕ)^(-1/2)`.
+///
+/// That form is evaluated with the equatorial Kerr metric for the given dimensionless spin `a/M`,
+/// and the angular velocity `Ω = sqrt(M) / (r^(3/2) + a sqrt(M))` of a prograde circular orbit,
+/// which reduces to eqn 19 for a non-rotating black hole. It is the product of
+/// [`calc_doppler_factor`] and [`calc_gravitational_redshift`].
 pub fn calc_redshift_factor(
     radius: f64,
     alpha: Rad<f64>,
     inclination: Rad<f64>,
     mass: f64,
+    spin: f64,
     impact_parameter: f64,
 ) -> f64 {
-    (1.0 + (mass / radius.powi(3)).sqrt() * impact_parameter * inclination.sin() * alpha.sin())
-        / (1.0 - 3.0 * mass / radius).sqrt()
+    calc_doppler_factor(radius, alpha, inclination, mass, spin, impact_parameter)
+        * calc_gravitational_redshift(radius, mass, spin)
+}
+
+/// Calculate the gravitational part `(-g_tt -2*Ω*g_tϕ - Ω²*g_ϕϕ)^(-1/2)` of the redshift factor,
+/// which doesn't depend on where the disk lies in the image.
+///
+/// Besides the gravitational redshift proper, this includes the time dilation of the disk's
+/// orbital motion (the transverse Doppler effect), which is the same on both sides of the disk.
+pub fn calc_gravitational_redshift(radius: f64, mass: f64, spin: f64) -> f64 {
+    let a = spin * mass;
+    let angular_velocity = calc_angular_velocity(radius, mass, spin);
+    let g_tt = -(1.0 - 2.0 * mass / radius);
+    let g_t_phi = -2.0 * mass * a / radius;
+    let g_phi_phi = radius.powi(2) + a.powi(2) + 2.0 * mass * a.powi(2) / radius;
+    1.0 / (-g_tt - 2.0 * angular_velocity * g_t_phi - angular_velocity.powi(2) * g_phi_phi).sqrt()
+}
+
+/// Calculate the Doppler part `1 - Ω*b*cos(η)` of the redshift factor, from the disk's orbital
+/// motion along the line of sight, which is less than one on the approaching side of the disk and
+/// greater than one on the receding side.
+pub fn calc_doppler_factor(
+    radius: f64,
+    alpha: Rad<f64>,
+    inclination: Rad<f64>,
+    mass: f64,
+    spin: f64,
+    impact_parameter: f64,
+) -> f64 {
+    let angular_velocity = calc_angular_velocity(radius, mass, spin);
+    1.0 + angular_velocity * impact_parameter * inclination.sin() * alpha.sin()
+}
+
+/// The angular velocity `Ω` of a prograde circular orbit in the equatorial plane.
+fn calc_angular_velocity(radius: f64, mass: f64, spin: f64) -> f64 {
+    mass.sqrt() / (radius.powf(1.5) + spin * mass * mass.sqrt())
 }
 
 /// The equation of an ellipse based on `cos(gamma)`.
@@ -145,3 +531,199 @@ pub fn ellipse(radius: f64, alpha: Rad<f64>, inclination: Rad<f64>) -> f64 {
     let gamma = calc_cos_gamma(alpha, inclination).acos();
     radius * gamma.sin()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        calc_bending_angle, calc_cos_gamma, calc_doppler_factor, calc_gravitational_redshift,
+        calc_impact_parameter_from_periastron, calc_intrinsic_flux, calc_isco_radius,
+        calc_observed_flux, calc_peak_intrinsic_flux_radius, calc_periastron_from_impact_parameter,
+        calc_redshift_factor, calc_specific_intensity, PEAK_INTRINSIC_FLUX_RADIUS,
+    };
+    use cgmath::assert_relative_eq;
+    use cgmath::{Angle, Deg, Rad};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_cos_gamma_near_edge_on() {
+        let alpha = Rad(1.0_f64);
+        let unclamped = |inclination: Rad<f64>| {
+            alpha.cos() / (alpha.cos().powi(2) + 1.0 / inclination.tan().powi(2)).sqrt()
+        };
+        // Inclinations short of edge-on are unaffected by the edge-on tolerance
+        for degrees in [89.0, 89.9, 89.99, 89.999] {
+            let inclination = Rad::from(Deg(degrees));
+            assert_relative_eq!(
+                calc_cos_gamma(alpha, inclination),
+                unclamped(inclination),
+                max_relative = 1e-12
+            );
+        }
+
+        // And the result varies continuously up to and through edge-on
+        let inclinations = (0..=200)
+            .map(|i| Rad::from(Deg(89.9 + f64::from(i) * 1e-3)))
+            .collect::<Vec<_>>();
+        for pair in inclinations.windows(2) {
+            let (cos_gamma, next_cos_gamma) = (
+                calc_cos_gamma(alpha, pair[0]),
+                calc_cos_gamma(alpha, pair[1]),
+            );
+            assert!(cos_gamma.is_finite());
+            assert!((next_cos_gamma - cos_gamma).abs() < 1e-4);
+        }
+        assert_relative_eq!(calc_cos_gamma(alpha, Rad(PI / 2.0)), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_specific_intensity() {
+        let (mass, accretion_rate, radius) = (1.0, 1e-7, 10.0);
+        let emitted_intensity = calc_intrinsic_flux(radius, accretion_rate, mass, 0.0) / PI;
+        assert!(
+            (calc_specific_intensity(radius, accretion_rate, mass, 0.0, 1.0) - emitted_intensity)
+                .abs()
+                < 1e-12 * emitted_intensity
+        );
+        // A redshift of z = 1 dims the intensity by (1 + z)^4 = 16
+        let intensity = calc_specific_intensity(radius, accretion_rate, mass, 0.0, 2.0);
+        assert!((intensity - emitted_intensity / 16.0).abs() < 1e-12 * emitted_intensity);
+        assert!(
+            (PI * intensity - calc_observed_flux(radius, accretion_rate, mass, 0.0, 2.0, 1.0))
+                .abs()
+                < 1e-12 * emitted_intensity
+        );
+        assert_eq!(
+            calc_specific_intensity(2.9 * mass, accretion_rate, mass, 0.0, f64::NAN),
+            0.0
+        );
+    }
+
+    #[test]
+    fn test_kerr_disk() {
+        let (mass, accretion_rate) = (2.0, 1e-7);
+        assert_eq!(calc_isco_radius(mass, 0.0), 6.0 * mass);
+        assert_relative_eq!(
+            calc_isco_radius(mass, 0.998),
+            1.2370 * mass,
+            max_relative = 1e-4
+        );
+
+        // Without spin, the redshift factor is that of eqn 19
+        let (radius, alpha, inclination) = (10.0 * mass, Rad(1.0), Rad::from(Deg(80.0)));
+        let schwarzschild_redshift_factor = (1.0
+            + (mass / radius.powi(3)).sqrt() * 12.0 * inclination.sin() * alpha.sin())
+            / (1.0 - 3.0 * mass / radius).sqrt();
+        assert_relative_eq!(
+            calc_redshift_factor(radius, alpha, inclination, mass, 0.0, 12.0),
+            schwarzschild_redshift_factor,
+            max_relative = 1e-12
+        );
+
+        // The Doppler factor is the only part which differs between the approaching and receding
+        // sides of the disk
+        let approaching = calc_doppler_factor(radius, -alpha, inclination, mass, 0.0, 12.0);
+        let receding = calc_doppler_factor(radius, alpha, inclination, mass, 0.0, 12.0);
+        assert!(approaching < 1.0 && receding > 1.0);
+        assert_relative_eq!(approaching + receding, 2.0, max_relative = 1e-12);
+        let gravitational_redshift = calc_gravitational_redshift(radius, mass, 0.0);
+        assert_relative_eq!(
+            gravitational_redshift,
+            1.0 / (1.0 - 3.0 * mass / radius).sqrt(),
+            max_relative = 1e-12
+        );
+        let flux = |doppler_factor| {
+            calc_observed_flux(
+                radius,
+                accretion_rate,
+                mass,
+                0.0,
+                gravitational_redshift,
+                doppler_factor,
+            )
+        };
+        assert!(flux(approaching) > flux(1.0) && flux(1.0) > flux(receding));
+
+        // A spinning black hole's disk extends further in, and is brightest further in
+        let spin = 0.9;
+        let isco_radius = calc_isco_radius(mass, spin);
+        assert!(isco_radius < 6.0 * mass);
+        assert_eq!(
+            calc_intrinsic_flux(isco_radius, accretion_rate, mass, spin),
+            0.0
+        );
+        assert!(calc_intrinsic_flux(1.01 * isco_radius, accretion_rate, mass, spin) > 0.0);
+        let peak_radius = calc_peak_intrinsic_flux_radius(mass, spin);
+        assert!(peak_radius > isco_radius && peak_radius < PEAK_INTRINSIC_FLUX_RADIUS * mass);
+        let peak_flux = calc_intrinsic_flux(peak_radius, accretion_rate, mass, spin);
+        for radius in [0.9 * peak_radius, 1.1 * peak_radius] {
+            assert!(calc_intrinsic_flux(radius, accretion_rate, mass, spin) < peak_flux);
+        }
+        assert_eq!(
+            calc_peak_intrinsic_flux_radius(mass, 0.0),
+            PEAK_INTRINSIC_FLUX_RADIUS * mass
+        );
+    }
+
+    #[test]
+    fn test_kerr_flux_tends_to_schwarzschild() {
+        let (mass, accretion_rate) = (2.0, 1e-7);
+        for radius in [6.5, 7.0, PEAK_INTRINSIC_FLUX_RADIUS, 10.0, 20.0, 50.0].map(|r| r * mass) {
+            assert_relative_eq!(
+                calc_intrinsic_flux(radius, accretion_rate, mass, 1e-6),
+                calc_intrinsic_flux(radius, accretion_rate, mass, 0.0),
+                max_relative = 1e-4
+            );
+        }
+        assert_relative_eq!(
+            calc_peak_intrinsic_flux_radius(mass, 1e-6),
+            PEAK_INTRINSIC_FLUX_RADIUS * mass,
+            max_relative = 1e-4
+        );
+    }
+
+    #[test]
+    fn test_flux_inside_photon_sphere() {
+        let (mass, accretion_rate) = (1.0, 1e-7);
+        let radius = 2.9 * mass;
+        assert_eq!(calc_intrinsic_flux(radius, accretion_rate, mass, 0.0), 0.0);
+
+        let redshift_factor =
+            calc_redshift_factor(radius, Rad(1.0), Rad::from(Deg(80.0)), mass, 0.0, 5.0);
+        assert!(redshift_factor.is_nan());
+        assert_eq!(
+            calc_observed_flux(radius, accretion_rate, mass, 0.0, redshift_factor, 1.0),
+            0.0
+        );
+        assert!(calc_intrinsic_flux(10.0 * mass, accretion_rate, mass, 0.0) > 0.0);
+    }
+
+    #[test]
+    fn test_bending_angle() {
+        let mass = 1.0;
+        for periastron in [3.5, 10.0, 100.0] {
+            let impact_parameter = calc_impact_parameter_from_periastron(periastron, mass);
+            assert_relative_eq!(
+                calc_periastron_from_impact_parameter(impact_parameter, mass),
+                periastron,
+                max_relative = 1e-9
+            );
+        }
+
+        // Weak-field limit of 4M/b
+        for impact_parameter in [1e3, 1e4] {
+            assert_relative_eq!(
+                calc_bending_angle(impact_parameter, mass),
+                4.0 * mass / impact_parameter,
+                max_relative = 1e-2
+            );
+        }
+
+        // Diverges approaching the critical impact parameter, and captures photons inside it
+        let critical_impact_parameter = 3.0 * 3.0_f64.sqrt() * mass;
+        let angles = [1e-1, 1e-3, 1e-6]
+            .map(|offset| calc_bending_angle(critical_impact_parameter + offset, mass));
+        assert!(angles[0] < angles[1] && angles[1] < angles[2]);
+        assert!(angles[2] > 4.0 * std::f64::consts::PI);
+        assert_eq!(calc_bending_angle(5.0, mass), f64::INFINITY);
+    }
+}
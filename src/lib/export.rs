@@ -0,0 +1,93 @@
+use crate::{BlackHole, IsoRadial, SolverConfig};
+use cgmath::{Basis2, Deg, Rad, Rotation, Rotation2, Vector2};
+use serde_json::json;
+
+/// Save the apparent geometry of a black hole to a JSON file, for use by other renderers.
+///
+/// The file contains the isoradials for each of the given `(radius, order)` pairs, the apparent
+/// inner and outer edges of the accretion disk, and the photon ring, each as an array of `[x, y]`
+/// points in the same orientation as the flux images.
+pub fn export_geometry_json<A: Into<Rad<f64>>, P: AsRef<std::path::Path>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    radii: &[(f64, u32)],
+    num_angles: usize,
+    path: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+
+    let curve_points = |isoradial: &IsoRadial| {
+        to_image_orientation(
+            &isoradial.calculate_coordinates(inclination, num_angles, &SolverConfig::default()),
+            isoradial.order,
+        )
+    };
+    let isoradials = radii
+        .iter()
+        .map(|&(radius, order)| {
+            json!({
+                "radius": radius,
+                "order": order,
+                "points": curve_points(&IsoRadial::new(blackhole, radius, order)),
+            })
+        })
+        .collect::<Vec<serde_json::Value>>();
+    let photon_ring = blackhole
+        .photon_ring_coordinates(num_angles)
+        .iter()
+        .map(|pt| [pt.x, pt.y])
+        .collect::<Vec<[f64; 2]>>();
+
+    let geometry = json!({
+        "inclination": inclination.0,
+        "isoradials": isoradials,
+        "apparent_inner_edge": curve_points(&blackhole.apparent_inner_disk_edge()),
+        "apparent_outer_edge": curve_points(&blackhole.apparent_outer_disk_edge()),
+        "photon_ring": photon_ring,
+    });
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer(std::io::BufWriter::new(file), &geometry)?;
+    Ok(())
+}
+
+/// Rotate points by -90 deg and vertically flip ghost image points, to match the flux images.
+fn to_image_orientation(points: &[Vector2<f64>], order: u32) -> Vec<[f64; 2]> {
+    let rotation = Basis2::from_angle(Deg(-90.0));
+    points
+        .iter()
+        .map(|&pt| {
+            let pt = rotation.rotate_vector(pt);
+            [pt.x, if order > 0 { -pt.y } else { pt.y }]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::export_geometry_json;
+    use crate::BlackHole;
+    use cgmath::Deg;
+
+    #[test]
+    fn test_export_geometry_json() {
+        let blackhole = BlackHole::default();
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("geometry.json");
+        export_geometry_json(&blackhole, Deg(80.0), &[(10.0, 0), (20.0, 1)], 24, &path).unwrap();
+
+        let geometry: serde_json::Value =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+
+        let isoradials = geometry["isoradials"].as_array().unwrap();
+        assert_eq!(isoradials.len(), 2);
+        for isoradial in isoradials {
+            assert_eq!(isoradial["points"].as_array().unwrap().len(), 24);
+        }
+        assert_eq!(isoradials[1]["order"], 1);
+        for curve in ["apparent_inner_edge", "apparent_outer_edge", "photon_ring"] {
+            let points = geometry[curve].as_array().unwrap();
+            assert_eq!(points.len(), 24);
+            assert_eq!(points[0].as_array().unwrap().len(), 2);
+        }
+    }
+}
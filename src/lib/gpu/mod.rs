@@ -0,0 +1,441 @@
+//! Sampling of the observed flux on a GPU, with a compute shader port of the periastron solver and
+//! flux equations.
+//!
+//! The shader works in single precision, so its samples agree with those of
+//! [`BlackHole::sample_flux_at_points`] to a relative tolerance of `1e-3` rather than exactly, apart
+//! from the odd point right on the boundary of where a periastron solution exists, which may fall
+//! on either side of it. The CPU implementation remains the reference.
+//!
+//! The periastron is always solved for by bisection, whatever the [`SolverMethod`] of the solver
+//! configuration, as both methods converge on the same solution.
+
+#[cfg(doc)]
+use crate::SolverMethod;
+use crate::{
+    equations::{calc_isco_radius, calc_temperature},
+    BlackHole, ImpactParameterSource, Sample, SolverConfig,
+};
+use bytemuck::{Pod, Zeroable};
+use cgmath::Rad;
+use rand::{distributions::Uniform, Rng};
+use std::f64::consts::PI;
+use wgpu::util::DeviceExt;
+
+/// Number of invocations in each workgroup of the compute shader.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Parameters shared by every invocation of the compute shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct Params {
+    mass: f32,
+    accretion_rate: f32,
+    spin: f32,
+    inclination: f32,
+    isco_radius: f32,
+    order: u32,
+    count: u32,
+    /// The solver configuration's periastron tolerance, maximum number of iterations and
+    /// periastron search range.
+    tolerance: f32,
+    max_iters: u32,
+    min_periastron: f32,
+    max_periastron: f32,
+    _padding: u32,
+}
+
+/// An emission point on the disk, as input to the compute shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuPoint {
+    radius: f32,
+    alpha: f32,
+}
+
+/// A sample output by the compute shader.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+struct GpuSample {
+    impact_parameter: f32,
+    redshift_factor: f32,
+    observed_flux: f32,
+    /// Nonzero if the impact parameter fell back to the ellipse.
+    ellipse_fallback: u32,
+}
+
+/// A GPU device with the flux sampling compute shader loaded, to be reused across calls.
+pub struct GpuSampler {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    /// The most points that can be sampled in one dispatch, limited by the device's maximum
+    /// workgroup count and storage buffer size.
+    max_points_per_dispatch: usize,
+}
+
+impl GpuSampler {
+    /// Request a GPU device and compile the flux sampling compute shader.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no GPU adapter which supports compute shaders, or if the device
+    /// can't be created.
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok_or("no GPU adapter found")?;
+        if !adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return Err(format!(
+                "GPU adapter {} doesn't support compute shaders",
+                adapter.get_info().name
+            )
+            .into());
+        }
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("luminet_blackhole"),
+                required_limits: adapter.limits(),
+                ..Default::default()
+            },
+            None,
+        ))?;
+
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sample_flux"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("sample_flux.wgsl").into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("sample_flux"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let limits = device.limits();
+        let max_points_per_dispatch = (limits.max_compute_workgroups_per_dimension as usize
+            * WORKGROUP_SIZE as usize)
+            .min(limits.max_storage_buffer_binding_size as usize / size_of::<GpuSample>());
+        Ok(GpuSampler {
+            device,
+            queue,
+            pipeline,
+            max_points_per_dispatch,
+        })
+    }
+
+    /// Sample the observed flux from the accretion disk at a number of random points, as for
+    /// [`BlackHole::sample_flux_at_points`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the samples can't be read back from the GPU.
+    pub fn sample_flux_at_points<A: Into<Rad<f64>>>(
+        &self,
+        blackhole: &BlackHole,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+    ) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+        self.sample_flux_at_points_with_solver(
+            blackhole,
+            inclination,
+            num_points,
+            order,
+            &SolverConfig::default(),
+        )
+    }
+
+    /// Sample the observed flux from the accretion disk at a number of random points, as per
+    /// [`sample_flux_at_points`](GpuSampler::sample_flux_at_points), solving for each photon's
+    /// periastron with the given solver configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the samples can't be read back from the GPU.
+    pub fn sample_flux_at_points_with_solver<A: Into<Rad<f64>>>(
+        &self,
+        blackhole: &BlackHole,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+        solver: &SolverConfig,
+    ) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+        let radius_dist = Uniform::new(blackhole.disk_inner_edge(), blackhole.disk_outer_edge());
+        let alpha_dist = Uniform::new(0.0, 2.0 * PI);
+        let mut rng = rand::thread_rng();
+        let points = (0..num_points)
+            .map(|_| (rng.sample(radius_dist), Rad(rng.sample(alpha_dist))))
+            .collect::<Vec<(f64, Rad<f64>)>>();
+        self.sample_flux_at(blackhole, inclination.into(), &points, order, solver)
+    }
+
+    /// Sample the observed flux at the given emission points, each a radius and angle `alpha`.
+    fn sample_flux_at(
+        &self,
+        blackhole: &BlackHole,
+        inclination: Rad<f64>,
+        points: &[(f64, Rad<f64>)],
+        order: u32,
+        solver: &SolverConfig,
+    ) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+        let mut samples = Vec::with_capacity(points.len());
+        for chunk in points.chunks(self.max_points_per_dispatch) {
+            let gpu_samples = self.dispatch(blackhole, inclination, chunk, order, solver)?;
+            samples.extend(
+                chunk
+                    .iter()
+                    .zip(gpu_samples)
+                    .map(|(&(radius, alpha), gpu_sample)| Sample {
+                        radius,
+                        alpha,
+                        impact_parameter: f64::from(gpu_sample.impact_parameter),
+                        impact_parameter_source: if gpu_sample.ellipse_fallback == 0 {
+                            ImpactParameterSource::Periastron
+                        } else {
+                            ImpactParameterSource::EllipseFallback
+                        },
+                        order,
+                        redshift_factor: f64::from(gpu_sample.redshift_factor),
+                        observed_flux: f64::from(gpu_sample.observed_flux),
+                        temperature: calc_temperature(
+                            radius,
+                            blackhole.accretion_rate,
+                            blackhole.mass,
+                            blackhole.spin(),
+                            blackhole.peak_flux_radius(),
+                        ),
+                        weight: 1.0,
+                    }),
+            );
+        }
+        Ok(samples)
+    }
+
+    /// Run the compute shader over a chunk of emission points that fits in a single dispatch.
+    #[allow(clippy::cast_possible_truncation)]
+    fn dispatch(
+        &self,
+        blackhole: &BlackHole,
+        inclination: Rad<f64>,
+        points: &[(f64, Rad<f64>)],
+        order: u32,
+        solver: &SolverConfig,
+    ) -> Result<Vec<GpuSample>, Box<dyn std::error::Error>> {
+        if points.is_empty() {
+            return Ok(Vec::new());
+        }
+        let count = u32::try_from(points.len())?;
+        // The shader's iteration count is 32 bit, which is far more iterations than are ever needed
+        let max_iters = u32::try_from(solver.max_iters).unwrap_or(u32::MAX);
+        let params = Params {
+            mass: blackhole.mass as f32,
+            accretion_rate: blackhole.accretion_rate as f32,
+            spin: blackhole.spin() as f32,
+            inclination: inclination.0 as f32,
+            isco_radius: calc_isco_radius(blackhole.mass, blackhole.spin()) as f32,
+            order,
+            count,
+            tolerance: solver.tolerance as f32,
+            max_iters,
+            min_periastron: solver.min_periastron as f32,
+            max_periastron: solver.max_periastron as f32,
+            _padding: 0,
+        };
+        let gpu_points = points
+            .iter()
+            .map(|&(radius, alpha)| GpuPoint {
+                radius: radius as f32,
+                alpha: alpha.0 as f32,
+            })
+            .collect::<Vec<GpuPoint>>();
+
+        let params_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let points_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("points"),
+                contents: bytemuck::cast_slice(&gpu_points),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let samples_size = (points.len() * size_of::<GpuSample>()) as u64;
+        let samples_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("samples"),
+            size: samples_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: samples_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sample_flux"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: points_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: samples_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&samples_buffer, 0, &readback_buffer, 0, samples_size);
+        self.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let readback_slice = readback_buffer.slice(..);
+        readback_slice.map_async(wgpu::MapMode::Read, move |result| {
+            // The receiver outlives the poll below, so sending can't fail
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver.recv()??;
+        let samples =
+            bytemuck::cast_slice::<u8, GpuSample>(&readback_slice.get_mapped_range()).to_vec();
+        readback_buffer.unmap();
+        Ok(samples)
+    }
+}
+
+impl BlackHole {
+    /// Sample the observed flux from the accretion disk at a number of random points on a GPU, as
+    /// for [`BlackHole::sample_flux_at_points`].
+    ///
+    /// This requests a new device each call, so use a [`GpuSampler`] directly to sample
+    /// repeatedly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no suitable GPU is available, or the samples can't be read back from it.
+    pub fn sample_flux_at_points_gpu<A: Into<Rad<f64>>>(
+        &self,
+        inclination: A,
+        num_points: usize,
+        order: u32,
+    ) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+        GpuSampler::new()?.sample_flux_at_points(self, inclination, num_points, order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GpuSampler;
+    use crate::{BlackHole, FallbackMode, SolverConfig, DEFAULT_ACCRETION_RATE};
+    use cgmath::{Deg, Rad};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    /// Relative tolerance between single precision GPU samples and double precision CPU samples, as
+    /// documented for the module.
+    const TOLERANCE: f64 = 1e-3;
+
+    #[test]
+    fn test_gpu_samples_match_cpu() {
+        let sampler = match GpuSampler::new() {
+            Ok(sampler) => sampler,
+            Err(err) => {
+                eprintln!("skipping GPU test: {err}");
+                return;
+            }
+        };
+
+        let inclination = Rad::from(Deg(80.0));
+        let mut rng = StdRng::seed_from_u64(0);
+        for blackhole in [
+            BlackHole::default(),
+            BlackHole::new_kerr(1.0, DEFAULT_ACCRETION_RATE, 50.0, 0.9),
+        ] {
+            let points = (0..2000)
+                .map(|_| {
+                    (
+                        rng.gen_range(blackhole.disk_inner_edge()..blackhole.disk_outer_edge()),
+                        Rad(rng.gen_range(0.0..std::f64::consts::TAU)),
+                    )
+                })
+                .collect::<Vec<(f64, Rad<f64>)>>();
+            // A narrower search range than the default leaves more points on the ellipse fallback
+            let narrow_solver = SolverConfig {
+                min_periastron: 4.0,
+                ..Default::default()
+            };
+            for (order, solver) in [
+                (0, SolverConfig::default()),
+                (1, SolverConfig::default()),
+                (2, SolverConfig::default()),
+                (0, narrow_solver),
+            ] {
+                let gpu_samples = sampler
+                    .sample_flux_at(&blackhole, inclination, &points, order, &solver)
+                    .unwrap();
+                assert_eq!(gpu_samples.len(), points.len());
+
+                // Points right on the boundary of where a periastron solution exists may fall
+                // either side of it in single precision
+                let matching = gpu_samples
+                    .iter()
+                    .filter(|gpu_sample| {
+                        let cpu_sample = blackhole
+                            .sample_flux_at_point(
+                                gpu_sample.radius,
+                                gpu_sample.alpha,
+                                inclination,
+                                order,
+                                None,
+                                FallbackMode::default(),
+                                &solver,
+                            )
+                            .unwrap();
+                        gpu_sample.impact_parameter_source == cpu_sample.impact_parameter_source
+                            && [
+                                (gpu_sample.impact_parameter, cpu_sample.impact_parameter),
+                                (gpu_sample.redshift_factor, cpu_sample.redshift_factor),
+                                (gpu_sample.observed_flux, cpu_sample.observed_flux),
+                            ]
+                            .iter()
+                            .all(|&(gpu, cpu)| {
+                                (gpu - cpu).abs() <= TOLERANCE * cpu.abs().max(1e-12)
+                            })
+                    })
+                    .count();
+                assert!(
+                    matching >= points.len() * 99 / 100,
+                    "{matching} of {} order {order} samples match",
+                    points.len()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,328 @@
+// Single precision port of `BlackHole::sample_flux_at_point`, with the ellipse fallback.
+//
+// Each invocation solves for the periastron of the photon emitted at one point on the disk by
+// bisection (see solvers.rs), with the tolerance, iteration limit and search range of the solver
+// configuration, then calculates its impact parameter, redshift factor and observed
+// flux (see equations.rs).
+//
+// Inverse sines and cosines are avoided where possible, in favour of `atan2` or working with the
+// sine directly, as some drivers only approximate them to a few significant figures.
+
+struct Params {
+    mass: f32,
+    accretion_rate: f32,
+    spin: f32,
+    inclination: f32,
+    isco_radius: f32,
+    order: u32,
+    count: u32,
+    tolerance: f32,
+    max_iters: u32,
+    min_periastron: f32,
+    max_periastron: f32,
+    _padding: u32,
+}
+
+struct Point {
+    radius: f32,
+    alpha: f32,
+}
+
+struct Sample {
+    impact_parameter: f32,
+    redshift_factor: f32,
+    observed_flux: f32,
+    ellipse_fallback: u32,
+}
+
+struct ImpactParameter {
+    value: f32,
+    ellipse_fallback: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> points: array<Point>;
+@group(0) @binding(2) var<storage, read_write> samples: array<Sample>;
+
+const PI: f32 = 3.14159265358979;
+const INCLINATION_TOLERANCE: f32 = 1e-5;
+const EDGE_ON_INCLINATION_TOLERANCE: f32 = 1e-9;
+const SWEEP_LIMIT_TOLERANCE: f32 = 1e-3;
+const MAX_SWEEP_LIMIT_ITERS: u32 = 100u;
+// Convergence tolerance of Carlson's duplication algorithm, whose error scales as its sixth power
+const CARLSON_TOLERANCE: f32 = 0.01;
+const MAX_CARLSON_ITERS: u32 = 32u;
+// Machine epsilon, to which the arithmetic-geometric mean is converged
+const MACHEP: f32 = 5.96e-8;
+const MAX_AGM_ITERS: u32 = 8u;
+
+// Carlson's symmetric elliptic integral of the first kind `R_F(x, y, z)`.
+fn carlson_rf(x0: f32, y0: f32, z0: f32) -> f32 {
+    var x = x0;
+    var y = y0;
+    var z = z0;
+    var mean = (x + y + z) / 3.0;
+    var dx = 0.0;
+    var dy = 0.0;
+    var dz = 0.0;
+    for (var i = 0u; i < MAX_CARLSON_ITERS; i++) {
+        let sqrt_x = sqrt(x);
+        let sqrt_y = sqrt(y);
+        let sqrt_z = sqrt(z);
+        let lambda = sqrt_x * (sqrt_y + sqrt_z) + sqrt_y * sqrt_z;
+        x = 0.25 * (x + lambda);
+        y = 0.25 * (y + lambda);
+        z = 0.25 * (z + lambda);
+        mean = (x + y + z) / 3.0;
+        dx = (mean - x) / mean;
+        dy = (mean - y) / mean;
+        dz = (mean - z) / mean;
+        if max(abs(dx), max(abs(dy), abs(dz))) <= CARLSON_TOLERANCE {
+            break;
+        }
+    }
+    let e2 = dx * dy - dz * dz;
+    let e3 = dx * dy * dz;
+    return (1.0 + (e2 / 24.0 - 0.1 - 3.0 * e3 / 44.0) * e2 + e3 / 14.0) / sqrt(mean);
+}
+
+// Complete elliptic integral of the first kind `K(m)`.
+fn ellip_k(m: f32) -> f32 {
+    return carlson_rf(0.0, 1.0 - m, 1.0);
+}
+
+// Incomplete elliptic integral of the first kind `F(phi | m)`, for `0 <= phi <= pi/2`, in terms of
+// `sin^2(phi)`.
+fn ellip_k_inc(sin_squared_phi: f32, m: f32) -> f32 {
+    return sqrt(sin_squared_phi)
+        * carlson_rf(1.0 - sin_squared_phi, 1.0 - m * sin_squared_phi, 1.0);
+}
+
+// Jacobi elliptic sine `sn(u | m)`, by the arithmetic-geometric mean as in Cephes' `ellpj`.
+fn jacobi_sn(u: f32, m: f32) -> f32 {
+    if m <= 0.0 {
+        return sin(u);
+    }
+    if m >= 1.0 {
+        return tanh(u);
+    }
+    var a: array<f32, 9>;
+    var c: array<f32, 9>;
+    a[0] = 1.0;
+    c[0] = sqrt(m);
+    var b = sqrt(1.0 - m);
+    var twon = 1.0;
+    var i = 0u;
+    while abs(c[i] / a[i]) > MACHEP && i < MAX_AGM_ITERS {
+        let ai = a[i];
+        i++;
+        c[i] = (ai - b) / 2.0;
+        let t = sqrt(ai * b);
+        a[i] = (ai + b) / 2.0;
+        b = t;
+        twon *= 2.0;
+    }
+    var phi = twon * a[i] * u;
+    while i >= 1u {
+        let t = clamp(c[i] * sin(phi) / a[i], -1.0, 1.0);
+        phi = (atan2(t, sqrt(1.0 - t * t)) + phi) / 2.0;
+        i--;
+    }
+    return sin(phi);
+}
+
+// The cosine of angle `gamma` (eqn 10), limited short of edge-on as in equations.rs.
+fn calc_cos_gamma(alpha: f32) -> f32 {
+    if params.inclination < INCLINATION_TOLERANCE {
+        return 0.0;
+    }
+    let tan_inclination = tan(params.inclination);
+    let tan_tolerance = tan(EDGE_ON_INCLINATION_TOLERANCE);
+    let cot_squared = max(
+        1.0 / (tan_inclination * tan_inclination),
+        tan_tolerance * tan_tolerance,
+    );
+    return cos(alpha) / sqrt(cos(alpha) * cos(alpha) + cot_squared);
+}
+
+// The reciprocal of `r` (eqn 13).
+fn calc_one_over_radius(periastron: f32, gamma: f32) -> f32 {
+    let mass = params.mass;
+    let q = sqrt((periastron - 2.0 * mass) * (periastron + 6.0 * mass));
+    let sin_squared_zeta_inf = (q - periastron + 2.0 * mass) / (q - periastron + 6.0 * mass);
+    let modulus = (q - periastron + 6.0 * mass) / (2.0 * q);
+    let elliptic_inf = ellip_k_inc(sin_squared_zeta_inf, modulus);
+
+    var jacobian_elliptic_arg: f32;
+    if params.order == 0u {
+        jacobian_elliptic_arg = gamma / (2.0 * sqrt(periastron / q)) + elliptic_inf;
+    } else {
+        jacobian_elliptic_arg = (gamma - 2.0 * f32(params.order) * PI)
+            / (2.0 * sqrt(periastron / q)) - elliptic_inf + 2.0 * ellip_k(modulus);
+    }
+    let elliptic_sine = jacobi_sn(jacobian_elliptic_arg, modulus);
+
+    return -(q - periastron + 2.0 * mass) / (4.0 * mass * periastron)
+        + ((q - periastron + 6.0 * mass) / (4.0 * mass * periastron)) * elliptic_sine
+        * elliptic_sine;
+}
+
+// The total polar angle swept by a photon with the given periastron, between arriving from and
+// escaping back to infinity.
+fn calc_sweep_angle(periastron: f32) -> f32 {
+    let mass = params.mass;
+    let q = sqrt((periastron - 2.0 * mass) * (periastron + 6.0 * mass));
+    let sin_squared_zeta_inf = (q - periastron + 2.0 * mass) / (q - periastron + 6.0 * mass);
+    let modulus = (q - periastron + 6.0 * mass) / (2.0 * q);
+    return 4.0 * sqrt(periastron / q)
+        * (ellip_k(modulus) - ellip_k_inc(sin_squared_zeta_inf, modulus));
+}
+
+fn calc_periastron_residual(radius: f32, periastron: f32, gamma: f32) -> f32 {
+    return 1.0 - radius * calc_one_over_radius(periastron, gamma);
+}
+
+// The impact parameter of the photon emitted at the given point, falling back to the ellipse
+// when there is no periastron solution.
+fn calc_impact_parameter(radius: f32, gamma: f32) -> ImpactParameter {
+    var periastron_a = params.min_periastron * params.mass;
+    var periastron_b = params.max_periastron * radius;
+    // Higher order images are capped at the periastron of the photon which sweeps just far enough
+    // around the black hole, to exclude spurious solutions beyond it (see solvers.rs)
+    if params.order > 0u {
+        let sweep = 2.0 * f32(params.order) * PI - gamma;
+        if calc_sweep_angle(periastron_a) < sweep {
+            return ImpactParameter(radius * sin(gamma), 1u);
+        }
+        if calc_sweep_angle(periastron_b) < sweep {
+            var low = periastron_a;
+            for (var i = 0u; i < MAX_SWEEP_LIMIT_ITERS; i++) {
+                if periastron_b - low <= SWEEP_LIMIT_TOLERANCE * params.mass {
+                    break;
+                }
+                let mid = (low + periastron_b) / 2.0;
+                if calc_sweep_angle(mid) >= sweep {
+                    low = mid;
+                } else {
+                    periastron_b = mid;
+                }
+            }
+        }
+    }
+    var positive_at_a = calc_periastron_residual(radius, periastron_a, gamma) >= 0.0;
+    let positive_at_b = calc_periastron_residual(radius, periastron_b, gamma) >= 0.0;
+    if positive_at_a == positive_at_b {
+        return ImpactParameter(radius * sin(gamma), 1u);
+    }
+
+    for (var i = 0u; i < params.max_iters; i++) {
+        let periastron_c = (periastron_a + periastron_b) / 2.0;
+        // Single precision runs out of resolution before the tolerance at large periastrons
+        if periastron_b - periastron_a <= params.tolerance
+            || periastron_c == periastron_a
+            || periastron_c == periastron_b {
+            break;
+        }
+        let positive_at_c = calc_periastron_residual(radius, periastron_c, gamma) >= 0.0;
+        if positive_at_a != positive_at_c {
+            periastron_b = periastron_c;
+        } else {
+            periastron_a = periastron_c;
+            positive_at_a = positive_at_c;
+        }
+    }
+
+    let periastron = (periastron_a + periastron_b) / 2.0;
+    return ImpactParameter(sqrt(pow(periastron, 3.0) / (periastron - 2.0 * params.mass)), 0u);
+}
+
+// The gravitational redshift factor `1 + z` (eqn 19), in its Kerr form.
+fn calc_redshift_factor(radius: f32, alpha: f32, impact_parameter: f32) -> f32 {
+    let mass = params.mass;
+    let a = params.spin * mass;
+    let angular_velocity = sqrt(mass) / (pow(radius, 1.5) + a * sqrt(mass));
+    let g_tt = -(1.0 - 2.0 * mass / radius);
+    let g_t_phi = -2.0 * mass * a / radius;
+    let g_phi_phi = radius * radius + a * a + 2.0 * mass * a * a / radius;
+    return (1.0 + angular_velocity * impact_parameter * sin(params.inclination) * sin(alpha))
+        / sqrt(-g_tt - 2.0 * angular_velocity * g_t_phi
+            - angular_velocity * angular_velocity * g_phi_phi);
+}
+
+// The intrinsic flux of the disk `F_s` (eqn 15), plus the change in that of Page & Thorne from
+// spin for a rotating black hole.
+fn calc_intrinsic_flux(radius: f32) -> f32 {
+    let flux = calc_intrinsic_flux_schwarzschild(radius);
+    if params.spin == 0.0 {
+        return flux;
+    }
+    let spin_change = calc_intrinsic_flux_page_thorne(radius, params.spin, params.isco_radius)
+        - calc_intrinsic_flux_page_thorne(radius, 0.0, 6.0 * params.mass);
+    return max(flux + spin_change, 0.0);
+}
+
+// The intrinsic flux of the disk around a non-rotating black hole (eqn 15).
+fn calc_intrinsic_flux_schwarzschild(radius: f32) -> f32 {
+    let mass = params.mass;
+    if radius <= 6.0 * mass {
+        return 0.0;
+    }
+    let scale = 3.0 * mass * params.accretion_rate / (8.0 * PI);
+    let radius_star = radius / mass;
+    let log_arg = ((sqrt(radius_star) + sqrt(3.0)) * (sqrt(6.0) - sqrt(3.0)))
+        / ((sqrt(radius_star) - sqrt(3.0)) * (sqrt(6.0) + sqrt(3.0)));
+    return scale * (1.0 / ((radius_star - 3.0) * pow(radius_star, 2.5)))
+        * (sqrt(radius_star) - sqrt(6.0) + (sqrt(3.0) / 3.0) * log(log_arg) / log(10.0));
+}
+
+// The intrinsic flux of the disk from Page & Thorne, for the given spin and innermost stable
+// circular orbit.
+fn calc_intrinsic_flux_page_thorne(radius: f32, spin: f32, isco_radius: f32) -> f32 {
+    let mass = params.mass;
+    if radius <= isco_radius {
+        return 0.0;
+    }
+    let scale = 3.0 * mass * params.accretion_rate / (8.0 * PI);
+    let x = sqrt(radius / mass);
+    let x0 = sqrt(isco_radius / mass);
+    let third_acos = atan2(sqrt(1.0 - spin * spin), spin) / 3.0;
+    let roots = array<f32, 3>(
+        2.0 * cos(third_acos - PI / 3.0),
+        2.0 * cos(third_acos + PI / 3.0),
+        -2.0 * cos(third_acos),
+    );
+    var bracket = x - x0 - 1.5 * spin * log(x / x0);
+    for (var i = 0u; i < 3u; i++) {
+        if roots[i] == spin {
+            continue;
+        }
+        let others = (roots[i] - roots[(i + 1u) % 3u]) * (roots[i] - roots[(i + 2u) % 3u]);
+        bracket -= 3.0 * (roots[i] - spin) * (roots[i] - spin) / (roots[i] * others)
+            * log((x - roots[i]) / (x0 - roots[i]));
+    }
+    return scale * bracket / (pow(x, 4.0) * (x * x * x - 3.0 * x + 2.0 * spin));
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    let point = points[index];
+    let cos_gamma = calc_cos_gamma(point.alpha);
+    let gamma = atan2(sqrt(1.0 - cos_gamma * cos_gamma), cos_gamma);
+    let impact_parameter = calc_impact_parameter(point.radius, gamma);
+    let redshift_factor = calc_redshift_factor(point.radius, point.alpha, impact_parameter.value);
+    let intrinsic_flux = calc_intrinsic_flux(point.radius);
+    var observed_flux = 0.0;
+    if intrinsic_flux != 0.0 {
+        observed_flux = intrinsic_flux / pow(redshift_factor, 4.0);
+    }
+    samples[index] = Sample(
+        impact_parameter.value,
+        redshift_factor,
+        observed_flux,
+        impact_parameter.ellipse_fallback,
+    );
+}
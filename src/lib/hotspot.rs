@@ -0,0 +1,22 @@
+use cgmath::Rad;
+
+/// A compact bright region orbiting within the accretion disk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hotspot {
+    /// The radius of the hotspot's orbit, in units of black hole mass.
+    pub radius: f64,
+    /// The angle of the hotspot at orbital phase zero.
+    pub initial_alpha: Rad<f64>,
+    /// The radius of the hotspot itself, in units of black hole mass.
+    pub size: f64,
+    /// The intrinsic flux emitted per unit area of the hotspot.
+    pub brightness: f64,
+}
+
+impl Hotspot {
+    /// The angle of the hotspot at the given orbital phase, in radians.
+    #[must_use]
+    pub fn alpha_at_phase(&self, phase: f64) -> Rad<f64> {
+        self.initial_alpha + Rad(phase)
+    }
+}
@@ -0,0 +1,575 @@
+//! Interpolation of flux samples over the observer's frame, by triangulating the samples of each
+//! image order and determining which image lies at each point.
+
+use crate::{equations::ellipse, BlackHole, IsoRadial, Sample};
+use cgmath::{Deg, Rad, Vector2};
+use spade::{Barycentric, DelaunayTriangulation, FloatTriangulation, Triangulation};
+use std::{cmp::Ordering, f64::consts::PI, ops::RangeInclusive};
+
+/// Image order to show at an image pixel.
+enum OrderToShow {
+    None,
+    Direct,
+    /// The ghost image, or an image of higher order, of the given order.
+    Ghost(usize),
+}
+
+/// How light from the accretion disk is occluded by the disk itself.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DiskOpacity {
+    /// An optically thick disk, which hides the ghost image wherever the direct image is visible.
+    #[default]
+    Opaque,
+    /// An optically thin disk, where the near side and far side of the direct image are layered
+    /// over the ghost image wherever they overlap.
+    Transparent,
+}
+
+/// Which image is shown where the direct and ghost images of an opaque disk overlap.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ZonePriority {
+    /// Show the direct image everywhere between the apparent inner and outer edges of the disk,
+    /// and the ghost image elsewhere.
+    #[default]
+    Direct,
+    /// Show the ghost image everywhere it lies, and the direct image elsewhere between the apparent
+    /// inner and outer edges of the disk.
+    Ghost,
+}
+
+/// Interpolates flux samples onto an image pixel grid.
+///
+/// Building the interpolator triangulates the samples, so it can be reused to render multiple
+/// images from the same samples without repeating that work.
+pub struct FluxInterpolator<'a> {
+    blackhole: &'a BlackHole,
+    inclination: Rad<f64>,
+    /// The triangulated samples of each image order, indexed by order.
+    pub(crate) triangulations: Vec<DelaunayTriangulation<&'a Sample>>,
+    /// The apparent inner and outer edges of the images of order one and up, indexed by order less
+    /// one.
+    ghost_edges: Vec<(IsoRadial, IsoRadial)>,
+    /// The width of the sampled region in the observer's frame.
+    pub(crate) sampled_width: f64,
+    /// The maximum flux of all samples.
+    pub(crate) max_flux: f64,
+    /// How far the apparent edges of the direct image are blended from their Newtonian ellipses
+    /// (zero) to their lensed isoradials (one), with the ghost image and shadow faded in alongside.
+    lensing: f64,
+}
+
+impl<'a> FluxInterpolator<'a> {
+    /// Construct an interpolator from the given samples.
+    ///
+    /// The samples are rotated by -90 degrees in place, so that the disk's near side is at the
+    /// bottom of the image.
+    pub fn new(
+        blackhole: &'a BlackHole,
+        inclination: Rad<f64>,
+        direct_samples: &'a mut [Sample],
+        ghost_samples: &'a mut [Sample],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_max_impact_parameter(blackhole, inclination, direct_samples, ghost_samples, None)
+    }
+
+    /// Construct an interpolator from the given samples, as per `new`, but only triangulating the
+    /// samples with an impact parameter no greater than `max_impact_parameter`.
+    ///
+    /// The extent of the sampled region, which sets the scale of rendered images, still covers
+    /// all of the samples.
+    pub fn with_max_impact_parameter(
+        blackhole: &'a BlackHole,
+        inclination: Rad<f64>,
+        direct_samples: &'a mut [Sample],
+        ghost_samples: &'a mut [Sample],
+        max_impact_parameter: Option<f64>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::with_orders(
+            blackhole,
+            inclination,
+            vec![direct_samples, ghost_samples],
+            max_impact_parameter,
+        )
+    }
+
+    /// Construct an interpolator from the given samples of any number of image orders, indexed by
+    /// order, as per `with_max_impact_parameter`.
+    ///
+    /// Each image of order one and up is shown only where no lower order image lies, so images of
+    /// order two and up add ever thinner rings just outside the black hole's shadow.
+    pub fn with_orders(
+        blackhole: &'a BlackHole,
+        inclination: Rad<f64>,
+        samples: Vec<&'a mut [Sample]>,
+        max_impact_parameter: Option<f64>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if samples.is_empty() {
+            return Err("no image orders to interpolate".into());
+        }
+
+        // Rotate points by -90 deg
+        let rotation_angle = Rad::from(Deg(-90.0));
+        let samples = samples
+            .into_iter()
+            .map(|order_samples| {
+                for sample in order_samples.iter_mut() {
+                    sample.alpha += rotation_angle;
+                }
+                let order_samples: &'a [Sample] = order_samples;
+                order_samples
+            })
+            .collect::<Vec<&'a [Sample]>>();
+
+        // Drop any samples with non-finite values, which can't be triangulated
+        let finite_samples =
+            |samples: &'a [Sample]| samples.iter().filter(|sample| sample.is_finite());
+
+        let (min_point, max_point) =
+            samples_range(samples.iter().flat_map(|&samples| finite_samples(samples)));
+        let max_flux = samples
+            .iter()
+            .flat_map(|&samples| finite_samples(samples))
+            .map(|s| s.observed_flux)
+            .max_by(f64::total_cmp)
+            .ok_or("no finite samples to interpolate")?;
+
+        // Create Delaunay triangulation so we can linearly interpolate samples on the image pixel
+        // grid
+        let max_impact_parameter = max_impact_parameter.unwrap_or(f64::INFINITY);
+        let triangulate = |samples: &'a [Sample]| {
+            let mut t: DelaunayTriangulation<&Sample> = DelaunayTriangulation::new();
+            for sample in
+                finite_samples(samples).filter(|s| s.impact_parameter <= max_impact_parameter)
+            {
+                t.insert(sample)?;
+            }
+            Ok::<_, spade::InsertionError>(t)
+        };
+        let triangulations = samples
+            .iter()
+            .map(|&samples| triangulate(samples))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            blackhole,
+            inclination,
+            triangulations,
+            ghost_edges: (1..samples.len())
+                .map(|order| ghost_edges(blackhole, order))
+                .collect(),
+            sampled_width: max_point.x - min_point.x,
+            max_flux,
+            lensing: 1.0,
+        })
+    }
+
+    /// Blend the apparent edges of the direct image between their Newtonian ellipses and lensed
+    /// isoradials by `lensing`, from zero to one, fading in the ghost image and the black hole's
+    /// shadow. Images are rendered at the scale, and normalized to the maximum flux, of the given
+    /// fully lensed interpolator.
+    ///
+    /// Used to render the disk part way between its Newtonian and lensed appearance, from samples
+    /// with impact parameters blended in the same way.
+    pub(crate) fn with_lensing(mut self, lensing: f64, lensed: &FluxInterpolator) -> Self {
+        self.lensing = lensing;
+        self.sampled_width = lensed.sampled_width;
+        self.max_flux = lensed.max_flux;
+        self
+    }
+
+    /// Interpolate the flux at a point in the observer's frame, in the same orientation as the
+    /// flux images, and normalize it to the given flux range. Returns None for points where no
+    /// image of the disk lies, such as inside the black hole's shadow.
+    pub(crate) fn flux_at(
+        &self,
+        interpolators: &mut [Barycentric<'_, DelaunayTriangulation<&Sample>>],
+        Vector2 { x, y }: Vector2<f64>,
+        flux_range: &RangeInclusive<f64>,
+        opacity: DiskOpacity,
+        priority: ZonePriority,
+    ) -> Option<f64> {
+        let (blackhole, inclination) = (self.blackhole, self.inclination);
+        let impact_parameter = (x.powi(2) + y.powi(2)).sqrt();
+        let alpha = Rad(y.atan2(x) + PI / 2.0);
+        let point = spade::Point2 { x, y };
+        let flux = match opacity {
+            DiskOpacity::Opaque => {
+                match opaque_order_to_show(
+                    blackhole,
+                    inclination,
+                    &self.ghost_edges,
+                    Vector2 { x, y },
+                    priority,
+                    self.lensing,
+                ) {
+                    OrderToShow::None => return None,
+                    OrderToShow::Direct => {
+                        interpolate_and_normalize_flux(&point, &mut interpolators[0], flux_range)
+                    }
+                    OrderToShow::Ghost(order) => {
+                        interpolate_and_normalize_flux(
+                            &point,
+                            &mut interpolators[order],
+                            flux_range,
+                        ) * self.lensing
+                    }
+                }
+            }
+            DiskOpacity::Transparent => {
+                if impact_parameter
+                    < apparent_shadow_radius(blackhole, inclination, alpha, self.lensing)
+                {
+                    return None;
+                }
+                // Layer the direct image (containing both the near side of the disk and
+                // the far side lensed over the top of the black hole) over the ghost image and
+                // any higher order images, restricting each to the region it actually occupies
+                let (inner_edge, outer_edge) =
+                    direct_image_edges(blackhole, inclination, alpha, self.lensing);
+                let in_direct_image =
+                    impact_parameter > inner_edge && impact_parameter <= outer_edge;
+                let (direct_interpolator, ghost_interpolators) = interpolators
+                    .split_first_mut()
+                    .expect("at least one image order");
+                let mut in_any_image = in_direct_image;
+                let mut flux = 0.0;
+                if in_direct_image {
+                    flux += interpolate_and_normalize_flux(&point, direct_interpolator, flux_range);
+                }
+                for (edges, ghost_interpolator) in self.ghost_edges.iter().zip(ghost_interpolators)
+                {
+                    if ghost_image_contains(blackhole, inclination, edges, impact_parameter, x, y) {
+                        in_any_image = true;
+                        flux +=
+                            interpolate_and_normalize_flux(&point, ghost_interpolator, flux_range)
+                                * self.lensing;
+                    }
+                }
+                if !in_any_image {
+                    return None;
+                }
+                flux
+            }
+        };
+        Some(flux)
+    }
+
+    /// Interpolate the observed flux at a set of points in the observer's frame, in the same
+    /// orientation as the flux images. Points where no image of the disk lies, such as inside the
+    /// black hole's shadow, give None.
+    #[must_use]
+    pub fn observed_flux_at_points(
+        &self,
+        points: &[Vector2<f64>],
+        opacity: DiskOpacity,
+    ) -> Vec<Option<f64>> {
+        let mut interpolators = self.interpolators();
+        points
+            .iter()
+            .map(|&point| {
+                self.flux_at(
+                    &mut interpolators,
+                    point,
+                    &(0.0..=1.0),
+                    opacity,
+                    ZonePriority::default(),
+                )
+            })
+            .collect()
+    }
+
+    /// An interpolator over the triangulated samples of each image order, indexed by order.
+    pub(crate) fn interpolators(&self) -> Vec<Barycentric<'_, DelaunayTriangulation<&'a Sample>>> {
+        self.triangulations
+            .iter()
+            .map(DelaunayTriangulation::barycentric)
+            .collect()
+    }
+}
+
+/// Determine which image of an opaque disk to show at a point in the observer's frame, in the same
+/// orientation as the flux images, given the apparent inner and outer edges of the images of order
+/// one and up and how far lensing is blended in (see [`direct_image_edges`]).
+fn opaque_order_to_show(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    ghost_edges: &[(IsoRadial, IsoRadial)],
+    Vector2 { x, y }: Vector2<f64>,
+    priority: ZonePriority,
+    lensing: f64,
+) -> OrderToShow {
+    let impact_parameter = (x.powi(2) + y.powi(2)).sqrt();
+    let alpha = Rad(y.atan2(x) + PI / 2.0);
+    // Determine which zone we're in:
+    //   - Outside the apparent outer edge of the accretion disk -> show ghost image
+    //   - Inside the apparent inner edge of the accretion disk -> show ghost image
+    //   - Inside the apparent inner edge of the black hole -> no image
+    //   - Otherwise -> show direct image
+    // unless the ghost image takes priority, in which case it's shown wherever it lies. The ghost
+    // image is only shown where it actually lies, and higher order images only where it doesn't.
+    let (inner_edge, outer_edge) = direct_image_edges(blackhole, inclination, alpha, lensing);
+    let in_direct_zone = impact_parameter > inner_edge && impact_parameter <= outer_edge;
+    let ghost_order = ghost_edges
+        .iter()
+        .position(|edges| {
+            ghost_image_contains(blackhole, inclination, edges, impact_parameter, x, y)
+        })
+        .map(|index| index + 1);
+    if in_direct_zone && !(priority == ZonePriority::Ghost && ghost_order.is_some()) {
+        OrderToShow::Direct
+    } else if impact_parameter < apparent_shadow_radius(blackhole, inclination, alpha, lensing) {
+        OrderToShow::None
+    } else {
+        ghost_order.map_or(OrderToShow::None, OrderToShow::Ghost)
+    }
+}
+
+/// The image order shown at a point in the observer's frame, in the same orientation as the flux
+/// images, for an opaque disk with the default zone priority, or None if no image lies there.
+pub(crate) fn image_order_at(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    point: Vector2<f64>,
+) -> Option<u32> {
+    match opaque_order_to_show(
+        blackhole,
+        inclination,
+        &[ghost_edges(blackhole, 1)],
+        point,
+        ZonePriority::default(),
+        1.0,
+    ) {
+        OrderToShow::None => None,
+        OrderToShow::Direct => Some(0),
+        OrderToShow::Ghost(order) => u32::try_from(order).ok(),
+    }
+}
+
+/// The apparent inner and outer edges of the image of the given order, one or higher.
+pub(crate) fn ghost_edges(blackhole: &BlackHole, order: usize) -> (IsoRadial, IsoRadial) {
+    let order = u32::try_from(order).expect("image order fits in u32");
+    (
+        IsoRadial::new(blackhole, blackhole.disk_inner_edge(), order),
+        IsoRadial::new(blackhole, blackhole.disk_outer_edge(), order),
+    )
+}
+
+/// Whether a point in the observer's frame lies within the ghost image of the disk, or an image of
+/// higher order, given the apparent inner and outer edges of that image.
+///
+/// Outside of this region the image's triangulation can still cover the point (e.g. across the
+/// hole in the middle of the ghost image), but any interpolated flux there is spurious.
+fn ghost_image_contains(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    (ghost_inner_edge, ghost_outer_edge): &(IsoRadial, IsoRadial),
+    impact_parameter: f64,
+    x: f64,
+    y: f64,
+) -> bool {
+    // Ghost image points, like those of every higher order, are vertically flipped, and can never
+    // appear inside the critical impact parameter
+    let ghost_alpha = Rad(PI / 2.0 - y.atan2(x));
+    impact_parameter
+        >= ghost_inner_edge
+            .get_impact_parameter_from_alpha(inclination, ghost_alpha)
+            .max(blackhole.critical_impact_parameter())
+        && impact_parameter
+            <= ghost_outer_edge.get_impact_parameter_from_alpha(inclination, ghost_alpha)
+}
+
+/// The apparent inner and outer edges of the direct image at the given angle, blended by
+/// `lensing` from the Newtonian ellipses (zero) to the lensed isoradials (one).
+fn direct_image_edges(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    lensing: f64,
+) -> (f64, f64) {
+    let inner_edge = blackhole.apparent_inner_edge_radius(inclination, alpha);
+    let outer_edge = blackhole.apparent_outer_edge_radius(inclination, alpha);
+    if lensing == 1.0 {
+        return (inner_edge, outer_edge);
+    }
+    let blend = |newtonian: f64, lensed: f64| (1.0 - lensing) * newtonian + lensing * lensed;
+    (
+        blend(
+            ellipse(blackhole.disk_inner_edge(), alpha, inclination),
+            inner_edge,
+        ),
+        blend(
+            ellipse(blackhole.disk_outer_edge(), alpha, inclination),
+            outer_edge,
+        ),
+    )
+}
+
+/// The apparent radius of the black hole's shadow at the given angle, inside of which no flux
+/// is shown, shrinking to nothing as `lensing` goes to zero.
+fn apparent_shadow_radius(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    lensing: f64,
+) -> f64 {
+    direct_image_edges(blackhole, inclination, alpha, lensing)
+        .0
+        .min(lensing * blackhole.critical_impact_parameter())
+}
+
+/// Interpolate the flux at the given point, weighted by the sample weights, and normalize it to
+/// the given flux range.
+pub(crate) fn interpolate_and_normalize_flux(
+    point: &spade::Point2<f64>,
+    interpolator: &mut Barycentric<'_, DelaunayTriangulation<&Sample>>,
+    flux_range: &RangeInclusive<f64>,
+) -> f64 {
+    let weighted_flux =
+        interpolator.interpolate(|v| v.data().observed_flux * v.data().weight, *point);
+    let weight = interpolator.interpolate(|v| v.data().weight, *point);
+    if let (Some(weighted_flux), Some(weight)) = (weighted_flux, weight) {
+        if weight <= 0.0 {
+            return 0.0;
+        }
+        let flux = weighted_flux / weight;
+        (flux - flux_range.start()) / (flux_range.end() - flux_range.start())
+    } else {
+        0.0
+    }
+}
+
+pub(crate) fn samples_range<'a, I>(samples: I) -> (Vector2<f64>, Vector2<f64>)
+where
+    I: Iterator<Item = &'a Sample>,
+{
+    let points = samples.map(Sample::observer_position);
+    let (min_x, min_y, max_x, max_y) = points.fold(
+        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
+        |(mut min_x, mut min_y, mut max_x, mut max_y), pt| {
+            if pt.x.partial_cmp(&min_x) == Some(Ordering::Less) {
+                min_x = pt.x;
+            }
+            if pt.x.partial_cmp(&max_x) == Some(Ordering::Greater) {
+                max_x = pt.x;
+            }
+            if pt.y.partial_cmp(&min_y) == Some(Ordering::Less) {
+                min_y = pt.y;
+            }
+            if pt.y.partial_cmp(&max_y) == Some(Ordering::Greater) {
+                max_y = pt.y;
+            }
+            (min_x, min_y, max_x, max_y)
+        },
+    );
+    (Vector2::new(min_x, min_y), Vector2::new(max_x, max_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate_and_normalize_flux, samples_range, FluxInterpolator};
+    use crate::{BlackHole, ImpactParameterSource, Sample};
+    use cgmath::{assert_abs_diff_eq, Deg, Rad, Vector2};
+    use spade::{DelaunayTriangulation, FloatTriangulation, Triangulation};
+
+    #[test]
+    fn test_samples_range() {
+        {
+            let samples = [Sample {
+                radius: 1.0,
+                alpha: Rad(0.0),
+                impact_parameter: 1.0,
+                impact_parameter_source: ImpactParameterSource::Periastron,
+                order: 0,
+                redshift_factor: 0.0,
+                observed_flux: 0.0,
+                temperature: 1.0,
+                weight: 1.0,
+            }];
+            let (min_pt, max_pt) = samples_range(samples.iter());
+            assert_eq!(min_pt, Vector2::new(1.0, 0.0));
+            assert_eq!(max_pt, Vector2::new(1.0, 0.0));
+        }
+
+        {
+            let samples = [
+                Sample {
+                    radius: 1.0,
+                    alpha: Rad(0.0),
+                    impact_parameter: 1.0,
+                    impact_parameter_source: ImpactParameterSource::Periastron,
+                    order: 0,
+                    redshift_factor: 0.0,
+                    observed_flux: 0.0,
+                    temperature: 1.0,
+                    weight: 1.0,
+                },
+                Sample {
+                    radius: 1.0,
+                    alpha: Rad::from(Deg(-180.0)),
+                    impact_parameter: 1.0,
+                    impact_parameter_source: ImpactParameterSource::Periastron,
+                    order: 0,
+                    redshift_factor: 0.0,
+                    observed_flux: 0.0,
+                    temperature: 1.0,
+                    weight: 1.0,
+                },
+            ];
+            let (min_pt, max_pt) = samples_range(samples.iter());
+            assert_abs_diff_eq!(min_pt, Vector2::new(-1.0, 0.0));
+            assert_abs_diff_eq!(max_pt, Vector2::new(1.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_flux_at_observer_points() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(30.0));
+        let points = [
+            Vector2::new(0.0, 0.0),
+            Vector2::new(20.0, 0.0),
+            Vector2::new(-20.0, 0.0),
+        ];
+        let fluxes = blackhole
+            .flux_at_observer_points(inclination, 1000, &points)
+            .unwrap();
+        assert_eq!(fluxes[0], None);
+        assert!(fluxes[1].is_some_and(|flux| flux > 0.0));
+        assert!(fluxes[2].is_some_and(|flux| flux > 0.0));
+    }
+
+    #[test]
+    fn test_uniform_weights_match_unweighted_interpolation() {
+        let blackhole = BlackHole::default();
+        let samples = blackhole.sample_flux_at_points(Deg(80.0), 500, 0);
+        let mut triangulation: DelaunayTriangulation<&Sample> = DelaunayTriangulation::new();
+        for sample in &samples {
+            triangulation.insert(sample).unwrap();
+        }
+        let flux_range = 0.0..=1.0;
+
+        let mut interpolator = triangulation.barycentric();
+        for sample in samples.iter().take(50) {
+            let position = sample.observer_position() * 0.9;
+            let point = spade::Point2::new(position.x, position.y);
+            let unweighted = interpolator
+                .interpolate(|v| v.data().observed_flux, point)
+                .unwrap_or(0.0);
+            let weighted = interpolate_and_normalize_flux(&point, &mut interpolator, &flux_range);
+            assert_abs_diff_eq!(weighted, unweighted, epsilon = unweighted * 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_no_finite_samples() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        assert!(FluxInterpolator::new(&blackhole, inclination, &mut [], &mut []).is_err());
+
+        let mut samples = blackhole.sample_flux_at_points_seeded(inclination, 10, 0, 1);
+        for sample in &mut samples {
+            sample.observed_flux = f64::NAN;
+        }
+        assert!(FluxInterpolator::new(&blackhole, inclination, &mut samples, &mut []).is_err());
+    }
+}
@@ -1,7 +1,20 @@
-use crate::{blackhole::BlackHole, solvers::calc_impact_parameter};
-use cgmath::{Angle, Rad, Vector2};
-use std::f64::consts::PI;
+use crate::{
+    blackhole::BlackHole,
+    solvers::{calc_impact_parameter, SolverConfig},
+};
+use cgmath::{Angle, InnerSpace, Rad, Vector2};
+use std::{cmp::Ordering, f64::consts::PI};
 
+/// Number of angles to sample when searching for the extremes of an isoradial.
+const EXTREMAL_GRID_ANGLES: usize = 360;
+/// Number of golden-section iterations to refine each extreme by.
+const EXTREMAL_REFINE_ITERS: usize = 40;
+/// Number of evenly spaced angles to start adaptive sampling from.
+const ADAPTIVE_INITIAL_ANGLES: usize = 16;
+/// Maximum number of times each initial segment can be bisected when sampling adaptively.
+const ADAPTIVE_MAX_DEPTH: u32 = 16;
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct IsoRadial {
     /// Mass of the associated black hole.
     mass: f64,
@@ -21,18 +34,26 @@ impl IsoRadial {
         }
     }
 
-    /// Calculate the coordinates of this isoradial line as it would appear to the observer.
+    /// Calculate the coordinates of this isoradial line as it would appear to the observer, with
+    /// the given solver configuration.
     #[must_use]
     pub fn calculate_coordinates(
         &self,
         inclination: Rad<f64>,
         num_angles: usize,
+        solver: &SolverConfig,
     ) -> Vec<Vector2<f64>> {
         (0..num_angles)
             .map(|i| {
                 let alpha = Rad((i as f64) / (num_angles as f64) * 2.0 * PI);
-                let impact_parameter =
-                    calc_impact_parameter(self.radius, inclination, alpha, self.mass, self.order);
+                let impact_parameter = calc_impact_parameter(
+                    self.radius,
+                    inclination,
+                    alpha,
+                    self.mass,
+                    self.order,
+                    solver,
+                );
                 Vector2::new(
                     impact_parameter * alpha.cos(),
                     impact_parameter * alpha.sin(),
@@ -41,9 +62,229 @@ impl IsoRadial {
             .collect::<Vec<Vector2<f64>>>()
     }
 
+    /// Calculate the coordinates of this isoradial line as it would appear to the observer, with
+    /// more points where the curve bends sharply.
+    ///
+    /// Each segment between neighbouring points is bisected until the curve deviates from the
+    /// straight chord between them by no more than `tolerance`.
+    #[must_use]
+    pub fn calculate_coordinates_adaptive(
+        &self,
+        inclination: Rad<f64>,
+        tolerance: f64,
+    ) -> Vec<Vector2<f64>> {
+        let point_at = |alpha: f64| {
+            let impact_parameter = self.get_impact_parameter_from_alpha(inclination, Rad(alpha));
+            Vector2::new(
+                impact_parameter * alpha.cos(),
+                impact_parameter * alpha.sin(),
+            )
+        };
+        let step = 2.0 * PI / ADAPTIVE_INITIAL_ANGLES as f64;
+
+        let mut points = Vec::new();
+        for i in 0..ADAPTIVE_INITIAL_ANGLES {
+            let (start, end) = (i as f64 * step, (i + 1) as f64 * step);
+            let start_point = point_at(start);
+            points.push(start_point);
+            subdivide(
+                &point_at,
+                (start, start_point),
+                (end, point_at(end)),
+                tolerance,
+                ADAPTIVE_MAX_DEPTH,
+                &mut points,
+            );
+        }
+        points
+    }
+
     /// Calculate the impact parameter corresponding to the given angle on this isoradial curve.
     #[must_use]
     pub fn get_impact_parameter_from_alpha(&self, inclination: Rad<f64>, alpha: Rad<f64>) -> f64 {
-        calc_impact_parameter(self.radius, inclination, alpha, self.mass, self.order)
+        calc_impact_parameter(
+            self.radius,
+            inclination,
+            alpha,
+            self.mass,
+            self.order,
+            &SolverConfig::default(),
+        )
+    }
+
+    /// Find the angles at which the impact parameter of this isoradial is largest and smallest,
+    /// returned as `(max_alpha, min_alpha)`.
+    ///
+    /// The impact parameter is sampled over a grid of angles, then each extreme is refined with a
+    /// golden-section search between its neighbouring grid points.
+    #[must_use]
+    pub fn extremal_alphas(&self, inclination: Rad<f64>) -> (Rad<f64>, Rad<f64>) {
+        let step = 2.0 * PI / EXTREMAL_GRID_ANGLES as f64;
+        let impact_parameter =
+            |alpha: f64| self.get_impact_parameter_from_alpha(inclination, Rad(alpha));
+        let grid = (0..EXTREMAL_GRID_ANGLES)
+            .map(|i| {
+                let alpha = i as f64 * step;
+                (alpha, impact_parameter(alpha))
+            })
+            .collect::<Vec<(f64, f64)>>();
+        let compare =
+            |a: &&(f64, f64), b: &&(f64, f64)| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal);
+        let (max_alpha, _) = grid.iter().max_by(compare).expect("non-empty grid");
+        let (min_alpha, _) = grid.iter().min_by(compare).expect("non-empty grid");
+
+        let max_alpha = refine_maximum(impact_parameter, max_alpha - step, max_alpha + step);
+        let min_alpha = refine_maximum(
+            |alpha| -impact_parameter(alpha),
+            min_alpha - step,
+            min_alpha + step,
+        );
+        (
+            Rad(max_alpha.rem_euclid(2.0 * PI)),
+            Rad(min_alpha.rem_euclid(2.0 * PI)),
+        )
+    }
+}
+
+/// Recursively bisect the curve between two points, pushing any points inserted between them
+/// (excluding the end points) in order of increasing angle.
+fn subdivide<F: Fn(f64) -> Vector2<f64>>(
+    point_at: &F,
+    (start, start_point): (f64, Vector2<f64>),
+    (end, end_point): (f64, Vector2<f64>),
+    tolerance: f64,
+    depth: u32,
+    points: &mut Vec<Vector2<f64>>,
+) {
+    let mid = (start + end) / 2.0;
+    let mid_point = point_at(mid);
+    if depth == 0 || chord_deviation(start_point, end_point, mid_point) <= tolerance {
+        return;
+    }
+    subdivide(
+        point_at,
+        (start, start_point),
+        (mid, mid_point),
+        tolerance,
+        depth - 1,
+        points,
+    );
+    points.push(mid_point);
+    subdivide(
+        point_at,
+        (mid, mid_point),
+        (end, end_point),
+        tolerance,
+        depth - 1,
+        points,
+    );
+}
+
+/// The distance of a point from the chord between two others.
+fn chord_deviation(start: Vector2<f64>, end: Vector2<f64>, point: Vector2<f64>) -> f64 {
+    let chord = end - start;
+    let length = chord.magnitude();
+    if length == 0.0 {
+        return (point - start).magnitude();
+    }
+    (chord.x * (point.y - start.y) - chord.y * (point.x - start.x)).abs() / length
+}
+
+/// Find the location of the maximum of `f` in the range `[lo, hi]` with a golden-section search.
+fn refine_maximum<F: Fn(f64) -> f64>(f: F, mut lo: f64, mut hi: f64) -> f64 {
+    let inv_phi = (5.0_f64.sqrt() - 1.0) / 2.0;
+    let mut a = hi - inv_phi * (hi - lo);
+    let mut b = lo + inv_phi * (hi - lo);
+    let (mut f_a, mut f_b) = (f(a), f(b));
+    for _ in 0..EXTREMAL_REFINE_ITERS {
+        if f_a > f_b {
+            hi = b;
+            b = a;
+            f_b = f_a;
+            a = hi - inv_phi * (hi - lo);
+            f_a = f(a);
+        } else {
+            lo = a;
+            a = b;
+            f_a = f_b;
+            b = lo + inv_phi * (hi - lo);
+            f_b = f(b);
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{chord_deviation, IsoRadial};
+    use crate::BlackHole;
+    use cgmath::{assert_abs_diff_eq, Deg, Rad};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_extremal_alphas() {
+        let blackhole = BlackHole::default();
+        let isoradial = IsoRadial::new(&blackhole, 20.0, 0);
+
+        // A face-on isoradial is a circle
+        let inclination = Rad::from(Deg(0.01));
+        let (max_alpha, min_alpha) = isoradial.extremal_alphas(inclination);
+        let max_b = isoradial.get_impact_parameter_from_alpha(inclination, max_alpha);
+        let min_b = isoradial.get_impact_parameter_from_alpha(inclination, min_alpha);
+        assert_abs_diff_eq!(max_b, min_b, epsilon = 1e-3 * max_b);
+
+        // When inclined, the near side of the disk appears closest to the black hole
+        let inclination = Rad::from(Deg(60.0));
+        let (max_alpha, min_alpha) = isoradial.extremal_alphas(inclination);
+        assert!(min_alpha.0 < 0.01 || min_alpha.0 > 2.0 * PI - 0.01);
+        assert!(
+            isoradial.get_impact_parameter_from_alpha(inclination, max_alpha)
+                > isoradial.get_impact_parameter_from_alpha(inclination, min_alpha)
+        );
+    }
+
+    #[test]
+    fn test_adaptive_coordinates() {
+        let blackhole = BlackHole::default();
+        let isoradial = IsoRadial::new(&blackhole, 6.0, 0);
+        let inclination = Rad::from(Deg(80.0));
+        let tolerance = 0.01;
+
+        // The largest deviation of the curve from the chords between consecutive points
+        let max_deviation = |alphas: &[f64]| {
+            let point_at = |alpha: f64| {
+                let b = isoradial.get_impact_parameter_from_alpha(inclination, Rad(alpha));
+                cgmath::Vector2::new(b * alpha.cos(), b * alpha.sin())
+            };
+            (0..alphas.len())
+                .map(|i| {
+                    let (start, end) = (alphas[i], alphas.get(i + 1).copied().unwrap_or(2.0 * PI));
+                    let mid = point_at((start + end) / 2.0);
+                    chord_deviation(point_at(start), point_at(end), mid)
+                })
+                .fold(0.0, f64::max)
+        };
+
+        let adaptive = isoradial.calculate_coordinates_adaptive(inclination, tolerance);
+        let adaptive_alphas = adaptive
+            .iter()
+            .map(|pt| pt.y.atan2(pt.x).rem_euclid(2.0 * PI))
+            .collect::<Vec<f64>>();
+        assert!(adaptive_alphas.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(max_deviation(&adaptive_alphas) <= tolerance);
+
+        // Find the smallest number of evenly spaced angles (to within a factor of two) meeting
+        // the same tolerance
+        let mut uniform_count = 16;
+        loop {
+            let alphas = (0..uniform_count)
+                .map(|i| i as f64 / uniform_count as f64 * 2.0 * PI)
+                .collect::<Vec<f64>>();
+            if max_deviation(&alphas) <= tolerance {
+                break;
+            }
+            uniform_count *= 2;
+        }
+        assert!(adaptive.len() < uniform_count / 2);
     }
 }
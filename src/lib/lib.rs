@@ -1,9 +1,29 @@
-pub use blackhole::{BlackHole, DEFAULT_ACCRETION_RATE, DEFAULT_DISK_OUTER_EDGE};
+pub use blackhole::{
+    BlackHole, BlackHoleBuilder, BlackHoleError, EmissionModel, SamplingOptions, SamplingStrategy,
+    DEFAULT_ACCRETION_RATE, DEFAULT_DISK_OUTER_EDGE, LUMINET_1979_INCLINATION,
+};
+pub use equations::{calc_bending_angle, calc_specific_intensity};
+pub use export::export_geometry_json;
+#[cfg(feature = "gpu")]
+pub use gpu::GpuSampler;
+pub use hotspot::Hotspot;
 pub use isoradial::IsoRadial;
-pub use sample::Sample;
+pub use sample::{
+    load_samples, observer_area_weights, samples_to_xy_buffer, save_samples, save_samples_by_order,
+    Sample,
+};
+pub use solvers::{
+    calc_impact_parameter_checked, periastron_search_range, FallbackMode, ImpactParameterCache,
+    ImpactParameterSource, SolverConfig, SolverMethod,
+};
 
 mod blackhole;
 mod equations;
+mod export;
+#[cfg(feature = "gpu")]
+mod gpu;
+mod hotspot;
+mod interpolation;
 mod isoradial;
 pub mod plotting;
 mod sample;
@@ -0,0 +1,134 @@
+use super::{image_units_per_pixel, Luma16Image};
+use crate::BlackHole;
+use cgmath::{Deg, Rad};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Progressively accumulates batches of flux samples into an image, for previews which refine
+/// over time.
+///
+/// Each sample is binned into the pixel it appears at, and each pixel shows the mean flux of all
+/// samples binned into it so far. Samples of different orders which land in the same pixel are
+/// averaged together, so the disk is effectively transparent.
+pub struct Accumulator {
+    width: u32,
+    height: u32,
+    /// Sum of the observed flux of the samples binned into each pixel.
+    flux_sum: Vec<f64>,
+    /// Number of samples binned into each pixel.
+    sample_count: Vec<u32>,
+    /// Generates a fresh seed for each batch of samples.
+    rng: StdRng,
+}
+
+impl Accumulator {
+    #[must_use]
+    pub fn new(width: u32, height: u32, seed: u64) -> Self {
+        let pixel_count = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            flux_sum: vec![0.0; pixel_count],
+            sample_count: vec![0; pixel_count],
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Sample a new batch of points on the accretion disk, and add them to the image.
+    ///
+    /// The image is scaled to fit the apparent outer edge of the disk, so the same black hole and
+    /// inclination should be used for every batch.
+    pub fn add_samples<A: Into<Rad<f64>>>(
+        &mut self,
+        blackhole: &BlackHole,
+        inclination: A,
+        batch_size: usize,
+        order: u32,
+    ) {
+        let inclination: Rad<f64> = inclination.into();
+        let units_per_pixel = image_units_per_pixel(blackhole, inclination, self.width);
+        let samples =
+            blackhole.sample_flux_at_points_seeded(inclination, batch_size, order, self.rng.gen());
+        for mut sample in samples {
+            // Rotate points by -90 deg
+            sample.alpha += Rad::from(Deg(-90.0));
+            let position = sample.observer_position();
+            #[allow(clippy::cast_possible_truncation)]
+            let (col, row) = (
+                (position.x / units_per_pixel).round() as i64 + i64::from(self.width / 2),
+                (-position.y / units_per_pixel).round() as i64 + i64::from(self.height / 2),
+            );
+            if let (Ok(col), Ok(row)) = (u32::try_from(col), u32::try_from(row)) {
+                if col < self.width && row < self.height {
+                    let i = (row * self.width + col) as usize;
+                    self.flux_sum[i] += sample.observed_flux;
+                    self.sample_count[i] += 1;
+                }
+            }
+        }
+    }
+
+    /// The current image, with the brightest pixel mapped to white.
+    #[must_use]
+    pub fn current_image(&self) -> Luma16Image {
+        let mean_flux = self.mean_flux();
+        let max_flux = mean_flux.iter().copied().fold(0.0, f64::max);
+        Luma16Image::from_fn(self.width, self.height, |col, row| {
+            let flux = mean_flux[(row * self.width + col) as usize];
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let luma = if max_flux > 0.0 {
+                (flux / max_flux * f64::from(u16::MAX)).round() as u16
+            } else {
+                0
+            };
+            image::Luma([luma])
+        })
+    }
+
+    /// The mean flux of the samples in each pixel, or zero for pixels without any samples.
+    fn mean_flux(&self) -> Vec<f64> {
+        self.flux_sum
+            .iter()
+            .zip(self.sample_count.iter())
+            .map(|(&sum, &count)| {
+                if count > 0 {
+                    sum / f64::from(count)
+                } else {
+                    0.0
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Accumulator;
+    use crate::BlackHole;
+    use cgmath::Deg;
+
+    #[test]
+    fn test_accumulator_converges() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(30.0);
+        let mut a = Accumulator::new(16, 16, 1);
+        let mut b = Accumulator::new(16, 16, 2);
+
+        // Two accumulators with different seeds should agree more closely as batches are added
+        let mut differences = Vec::new();
+        for _ in 0..3 {
+            for _ in 0..4 {
+                a.add_samples(&blackhole, inclination, 200, 0);
+                b.add_samples(&blackhole, inclination, 200, 0);
+            }
+            let difference = a
+                .mean_flux()
+                .iter()
+                .zip(b.mean_flux().iter())
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>();
+            differences.push(difference);
+        }
+        assert!(differences.windows(2).all(|pair| pair[1] < pair[0]));
+        assert!(a.current_image().pixels().any(|p| p.0[0] == u16::MAX));
+    }
+}
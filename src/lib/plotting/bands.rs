@@ -0,0 +1,88 @@
+use super::{generate_flux_image_from_samples, FluxImageOptions};
+use crate::{BlackHole, Sample};
+use cgmath::Rad;
+use image::Rgb;
+
+pub type Rgb16Image = image::ImageBuffer<Rgb<u16>, Vec<u16>>;
+
+/// Generate a false-color image of the accretion disk observed at two frequencies, with the
+/// monochromatic flux at `freq_lo` in the red channel and at `freq_hi` in the blue channel.
+///
+/// Frequencies are in units of `k T / h` for the peak temperature of the disk. Each channel is
+/// normalized separately, so the color shows the local shape of the spectrum rather than its
+/// overall brightness.
+pub fn generate_two_band_image<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+    freq_lo: f64,
+    freq_hi: f64,
+) -> Result<Rgb16Image, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+
+    let render_band = |frequency: f64| {
+        let band_samples = |samples: &[Sample]| {
+            samples
+                .iter()
+                .map(|sample| Sample {
+                    observed_flux: blackhole.observed_monochromatic_flux(sample, frequency),
+                    ..sample.clone()
+                })
+                .collect::<Vec<Sample>>()
+        };
+        generate_flux_image_from_samples(
+            blackhole,
+            inclination,
+            &mut band_samples(&direct_samples),
+            &mut band_samples(&ghost_samples),
+            image_width,
+            image_height,
+            &FluxImageOptions::default(),
+        )
+    };
+    let red = render_band(freq_lo)?;
+    let blue = render_band(freq_hi)?;
+
+    Ok(Rgb16Image::from_fn(
+        image_width,
+        image_height,
+        |col, row| {
+            Rgb([
+                red.get_pixel(col, row).0[0],
+                0,
+                blue.get_pixel(col, row).0[0],
+            ])
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_two_band_image;
+    use crate::{plotting::image_units_per_pixel, BlackHole};
+    use cgmath::Deg;
+
+    #[test]
+    fn test_inner_disk_is_bluer() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(10.0);
+        let (width, height) = (64, 64);
+        let img = generate_two_band_image(&blackhole, inclination, 2000, width, height, 0.5, 4.0)
+            .unwrap();
+
+        // Compare the blue to red ratio near the inner and outer edges of the disk, along the
+        // horizontal axis through the black hole
+        let units_per_pixel = image_units_per_pixel(&blackhole, inclination, width);
+        let blueness = |radius: f64| {
+            let col = width / 2 + (radius / units_per_pixel).round() as u32;
+            let pixel = img.get_pixel(col, height / 2).0;
+            assert!(pixel[0] > 0);
+            f64::from(pixel[2]) / f64::from(pixel[0])
+        };
+        assert!(blueness(10.0) > 10.0 * blueness(40.0));
+    }
+}
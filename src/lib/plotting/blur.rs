@@ -0,0 +1,124 @@
+use super::Luma16Image;
+use rayon::prelude::*;
+
+/// Number of standard deviations the blur kernel extends to on either side of its centre.
+const KERNEL_RADIUS_SIGMAS: f64 = 3.0;
+
+/// Blur an image with a Gaussian kernel of the given standard deviation, in pixels.
+///
+/// The kernel is applied separably, first along rows then along columns, accumulating in `f64` so
+/// no precision is lost between passes. Pixels beyond the edges of the image are taken to be equal
+/// to the nearest edge pixel.
+///
+/// Returns an error if the image is empty or the standard deviation isn't finite.
+pub fn gaussian_blur(
+    img: &Luma16Image,
+    sigma: f64,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    if img.width() == 0 || img.height() == 0 {
+        return Err(format!(
+            "cannot blur an empty {}x{} image",
+            img.width(),
+            img.height()
+        )
+        .into());
+    }
+    if !sigma.is_finite() {
+        return Err(format!("blur standard deviation must be finite, got {sigma}").into());
+    }
+    if sigma <= 0.0 {
+        return Ok(img.clone());
+    }
+    let kernel = gaussian_kernel(sigma);
+    let radius = kernel.len() / 2;
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let clamp = |i: usize, len: usize| i.saturating_sub(radius).min(len - 1);
+
+    // Horizontal pass
+    let mut horizontal = vec![0.0; width * height];
+    horizontal
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(row, out)| {
+            let pixels = &img.as_raw()[row * width..(row + 1) * width];
+            for (col, value) in out.iter_mut().enumerate() {
+                *value = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| weight * f64::from(pixels[clamp(col + k, width)]))
+                    .sum();
+            }
+        });
+
+    // Vertical pass
+    let mut blurred = Luma16Image::new(img.width(), img.height());
+    blurred
+        .par_chunks_mut(width)
+        .enumerate()
+        .for_each(|(row, out)| {
+            for (col, pixel) in out.iter_mut().enumerate() {
+                let value: f64 = kernel
+                    .iter()
+                    .enumerate()
+                    .map(|(k, weight)| weight * horizontal[clamp(row + k, height) * width + col])
+                    .sum();
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let luma = value.round() as u16;
+                *pixel = luma;
+            }
+        });
+    Ok(blurred)
+}
+
+/// Construct a normalized one-dimensional Gaussian kernel.
+fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let radius = (KERNEL_RADIUS_SIGMAS * sigma).ceil() as usize;
+    let kernel = (0..=2 * radius)
+        .map(|i| {
+            let x = i as f64 - radius as f64;
+            (-x.powi(2) / (2.0 * sigma.powi(2))).exp()
+        })
+        .collect::<Vec<f64>>();
+    let total: f64 = kernel.iter().sum();
+    kernel.iter().map(|weight| weight / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gaussian_blur;
+    use crate::plotting::Luma16Image;
+    use cgmath::assert_abs_diff_eq;
+
+    #[test]
+    fn test_gaussian_blur_point_source() {
+        let sigma = 2.5;
+        let mut img = Luma16Image::new(41, 41);
+        img.put_pixel(20, 20, image::Luma([u16::MAX]));
+        let blurred = gaussian_blur(&img, sigma).unwrap();
+
+        // The blurred image should be symmetric, and its variance along each axis should match
+        let (mut total, mut variance) = (0.0, 0.0);
+        for (col, row, pixel) in blurred.enumerate_pixels() {
+            assert_eq!(pixel, blurred.get_pixel(40 - col, row));
+            assert_eq!(pixel, blurred.get_pixel(row, col));
+            let value = f64::from(pixel.0[0]);
+            total += value;
+            variance += (f64::from(col) - 20.0).powi(2) * value;
+        }
+        variance /= total;
+        assert_abs_diff_eq!(total, f64::from(u16::MAX), epsilon = 100.0);
+        assert_abs_diff_eq!(variance.sqrt(), sigma, epsilon = 0.05 * sigma);
+    }
+
+    #[test]
+    fn test_gaussian_blur_invalid_input() {
+        assert!(gaussian_blur(&Luma16Image::new(0, 10), 1.0).is_err());
+        assert!(gaussian_blur(&Luma16Image::new(10, 0), 1.0).is_err());
+        assert!(gaussian_blur(&Luma16Image::new(10, 10), f64::NAN).is_err());
+        assert_eq!(
+            gaussian_blur(&Luma16Image::new(10, 10), 0.0).unwrap(),
+            Luma16Image::new(10, 10)
+        );
+    }
+}
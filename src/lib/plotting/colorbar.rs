@@ -0,0 +1,184 @@
+use super::{
+    flux::{render_orders, sample_orders},
+    Colormap, FluxImageOptions,
+};
+use crate::BlackHole;
+use cgmath::Rad;
+use image::{Rgb, RgbImage};
+use plotters::prelude::*;
+
+/// Width in pixels of the panel holding the colorbar and its labels.
+const PANEL_WIDTH: u32 = 128;
+/// Width in pixels of the colorbar itself.
+const BAR_WIDTH: u32 = 24;
+/// Gap in pixels between the flux image and the colorbar.
+const BAR_MARGIN: u32 = 16;
+/// Number of ticks labelled along the colorbar.
+const TICK_COUNT: usize = 5;
+
+/// Generate an image of the observed flux in the colors of the given colormap, with a labelled
+/// colorbar to its right showing the flux range.
+///
+/// The flux range is that of the options if given, otherwise from zero to the maximum flux of the
+/// samples, so the labels always match the image.
+pub fn generate_flux_image_with_colorbar<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+    colormap: &Colormap,
+    options: &FluxImageOptions,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let mut samples = sample_orders(blackhole, inclination, sample_count, options)?;
+    let flux_range = options.flux_range.clone().unwrap_or_else(|| {
+        0.0..=samples
+            .iter()
+            .flatten()
+            .map(|sample| sample.observed_flux)
+            .filter(|flux| flux.is_finite())
+            .fold(0.0, f64::max)
+    });
+    let flux = render_orders(
+        blackhole,
+        inclination,
+        &mut samples,
+        image_width,
+        image_height,
+        &FluxImageOptions {
+            flux_range: Some(flux_range.clone()),
+            ..options.clone()
+        },
+    )?;
+    let colorbar = render_colorbar(colormap, flux_range, image_height)?;
+
+    let colored = colormap.apply(&flux);
+    let mut img = RgbImage::new(image_width + PANEL_WIDTH, image_height);
+    for (col, row, pixel) in colored.enumerate_pixels() {
+        #[allow(clippy::cast_possible_truncation)]
+        img.put_pixel(
+            col,
+            row,
+            Rgb(pixel.0.map(|c| ((u32::from(c) + 128) / 257) as u8)),
+        );
+    }
+    image::imageops::replace(&mut img, &colorbar, i64::from(image_width), 0);
+    Ok(img)
+}
+
+/// Render a vertical colorbar on a black background, with white tick labels for the flux range
+/// along its right side.
+fn render_colorbar(
+    colormap: &Colormap,
+    flux_range: std::ops::RangeInclusive<f64>,
+    height: u32,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let (min_flux, max_flux) = (*flux_range.start(), *flux_range.end());
+    if max_flux.partial_cmp(&min_flux) != Some(std::cmp::Ordering::Greater) {
+        return Err(format!("invalid flux range {min_flux} to {max_flux} for a colorbar").into());
+    }
+    let font_size = (height / 40).clamp(10, 20);
+    let vertical_margin = font_size;
+
+    let mut buffer = vec![0; PANEL_WIDTH as usize * height as usize * 3];
+    {
+        let root =
+            BitMapBackend::with_buffer(&mut buffer, (PANEL_WIDTH, height)).into_drawing_area();
+        root.fill(&BLACK)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin_left(BAR_MARGIN)
+            .margin_top(vertical_margin)
+            .margin_bottom(vertical_margin)
+            .right_y_label_area_size(PANEL_WIDTH - BAR_MARGIN - BAR_WIDTH)
+            .build_cartesian_2d(0.0..1.0, min_flux..max_flux)?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .disable_y_mesh()
+            .disable_x_axis()
+            .y_labels(TICK_COUNT)
+            .y_label_formatter(&|flux| format!("{flux:.2e}"))
+            .label_style(("sans-serif", font_size).into_font().color(&WHITE))
+            .axis_style(WHITE)
+            .draw()?;
+
+        // One band of color per pixel of bar height
+        let steps = height.saturating_sub(2 * vertical_margin).max(1);
+        chart.draw_series((0..steps).map(|i| {
+            let (lower, upper) = (f64::from(i), f64::from(i + 1));
+            let Rgb([r, g, b]) = colormap.color_at((lower + 0.5) / f64::from(steps));
+            #[allow(clippy::cast_possible_truncation)]
+            let color = RGBColor((r / 257) as u8, (g / 257) as u8, (b / 257) as u8);
+            let flux_at = |t: f64| min_flux + t / f64::from(steps) * (max_flux - min_flux);
+            Rectangle::new(
+                [(0.0, flux_at(lower)), (1.0, flux_at(upper))],
+                color.filled(),
+            )
+        }))?;
+
+        root.present()?;
+    }
+
+    Ok(RgbImage::from_raw(PANEL_WIDTH, height, buffer).expect("buffer matches image size"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_flux_image_with_colorbar, BAR_MARGIN, BAR_WIDTH};
+    use crate::{
+        plotting::{FluxColormap, FluxImageOptions},
+        BlackHole,
+    };
+    use cgmath::Deg;
+    use image::Rgb;
+
+    #[test]
+    fn test_generate_flux_image_with_colorbar() {
+        let blackhole = BlackHole::default();
+        let (width, height) = (160, 120);
+        let colormap = FluxColormap::Inferno.colormap().unwrap();
+        let img = generate_flux_image_with_colorbar(
+            &blackhole,
+            Deg(80.0),
+            2000,
+            width,
+            height,
+            &colormap,
+            &FluxImageOptions {
+                flux_range: Some(0.0..=1e-3),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(img.width() > width);
+        assert_eq!(img.height(), height);
+
+        // The colorbar runs from the colormap's brightest color at the top to its darkest at the
+        // bottom
+        let bar_x = width + BAR_MARGIN + BAR_WIDTH / 2;
+        let luma = |y: u32| {
+            img.get_pixel(bar_x, y)
+                .0
+                .iter()
+                .map(|&c| u32::from(c))
+                .sum::<u32>()
+        };
+        assert!(luma(height / 8) > luma(height / 2));
+        assert!(luma(height / 2) > luma(7 * height / 8));
+        let Rgb(darkest) = colormap.color_at(0.0);
+        #[allow(clippy::cast_possible_truncation)]
+        let darkest = darkest.map(|c| (c / 257) as u8);
+        assert!(img
+            .get_pixel(bar_x, 7 * height / 8)
+            .0
+            .iter()
+            .zip(darkest)
+            .all(|(&a, b)| a.abs_diff(b) < 48));
+
+        // Labels are drawn to the right of the bar
+        assert!((bar_x + BAR_WIDTH..img.width())
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .any(|(x, y)| img.get_pixel(x, y).0.iter().all(|&c| c > 128)));
+    }
+}
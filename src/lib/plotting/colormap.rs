@@ -0,0 +1,351 @@
+use super::{
+    flux::{render_orders, sample_orders},
+    temperature::{planckian_rgb, PEAK_TEMPERATURE_KELVIN},
+    FluxImageOptions, Luma16Image, Rgb16Image,
+};
+use crate::{equations::calc_temperature, BlackHole, Sample};
+use cgmath::Rad;
+use image::{Rgb, RgbImage};
+
+/// Evenly spaced colors of matplotlib's perceptually uniform colormaps, from low to high.
+const INFERNO: [[u8; 3]; 9] = [
+    [0x00, 0x00, 0x04],
+    [0x1f, 0x0c, 0x48],
+    [0x55, 0x0f, 0x6d],
+    [0x88, 0x22, 0x6a],
+    [0xba, 0x36, 0x55],
+    [0xe3, 0x59, 0x33],
+    [0xf9, 0x8c, 0x0a],
+    [0xf9, 0xc9, 0x32],
+    [0xfc, 0xff, 0xa4],
+];
+const MAGMA: [[u8; 3]; 9] = [
+    [0x00, 0x00, 0x04],
+    [0x1c, 0x10, 0x44],
+    [0x4f, 0x12, 0x7b],
+    [0x81, 0x25, 0x81],
+    [0xb5, 0x36, 0x7a],
+    [0xe5, 0x50, 0x64],
+    [0xfb, 0x87, 0x61],
+    [0xfe, 0xc2, 0x87],
+    [0xfc, 0xfd, 0xbf],
+];
+const VIRIDIS: [[u8; 3]; 9] = [
+    [0x44, 0x01, 0x54],
+    [0x47, 0x2c, 0x7a],
+    [0x3b, 0x51, 0x8b],
+    [0x2c, 0x71, 0x8e],
+    [0x21, 0x90, 0x8d],
+    [0x27, 0xad, 0x81],
+    [0x5c, 0xc8, 0x63],
+    [0xaa, 0xdc, 0x32],
+    [0xfd, 0xe7, 0x25],
+];
+
+/// A mapping from normalized flux values to colors, linearly interpolating between a set of color
+/// stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colormap {
+    /// Positions, normalized to `[0, 1]`, and RGB colors with channels in `[0, 1]`, sorted by
+    /// position.
+    stops: Vec<(f64, [f64; 3])>,
+}
+
+impl Colormap {
+    /// Construct a colormap from a set of stops, each a position and an RGB color with channels in
+    /// `[0, 1]`.
+    ///
+    /// The positions may be in any units, and are rescaled so that the lowest maps to zero and the
+    /// highest to one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are fewer than two stops, or if all of the stops share the same
+    /// position.
+    pub fn new(mut stops: Vec<(f64, [f64; 3])>) -> Result<Self, Box<dyn std::error::Error>> {
+        if stops.len() < 2 {
+            return Err("a colormap needs at least two stops".into());
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let (min_position, max_position) = (stops[0].0, stops[stops.len() - 1].0);
+        if max_position <= min_position {
+            return Err("colormap stops must span a range of positions".into());
+        }
+        for stop in &mut stops {
+            stop.0 = (stop.0 - min_position) / (max_position - min_position);
+        }
+        Ok(Colormap { stops })
+    }
+
+    /// Load a colormap from a CSV file with rows of `position,r,g,b`, with color channels in
+    /// `[0, 255]`.
+    ///
+    /// Blank lines and lines starting with `#` are ignored, as is a header row.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read, a row can't be parsed, or the stops don't form a
+    /// valid colormap.
+    pub fn from_csv<P: AsRef<std::path::Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = std::fs::read_to_string(path)?;
+        let mut stops = Vec::new();
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line
+                .split(',')
+                .map(|field| field.trim().parse::<f64>())
+                .collect::<Result<Vec<f64>, _>>();
+            match fields {
+                Ok(fields) if fields.len() == 4 => {
+                    stops.push((
+                        fields[0],
+                        [fields[1], fields[2], fields[3]].map(|c| c / 255.0),
+                    ));
+                }
+                Err(_) if stops.is_empty() && i == 0 => {
+                    // Header row
+                }
+                _ => {
+                    return Err(
+                        format!("invalid colormap row {}, expected position,r,g,b", i + 1).into(),
+                    )
+                }
+            }
+        }
+        Colormap::new(stops)
+    }
+
+    /// The color at the given position, which is clamped to `[0, 1]`.
+    #[must_use]
+    pub fn color_at(&self, position: f64) -> Rgb<u16> {
+        let position = position.clamp(0.0, 1.0);
+        let upper = self
+            .stops
+            .iter()
+            .position(|stop| stop.0 >= position)
+            .unwrap_or(self.stops.len() - 1)
+            .max(1);
+        let ((lower_position, lower_color), (upper_position, upper_color)) =
+            (self.stops[upper - 1], self.stops[upper]);
+        let t = if upper_position > lower_position {
+            (position - lower_position) / (upper_position - lower_position)
+        } else {
+            0.0
+        };
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Rgb([0, 1, 2].map(|c| {
+            let value = lower_color[c] + t * (upper_color[c] - lower_color[c]);
+            (value.clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16
+        }))
+    }
+
+    /// Color an image of the observed flux.
+    #[must_use]
+    pub fn apply(&self, img: &Luma16Image) -> Rgb16Image {
+        Rgb16Image::from_fn(img.width(), img.height(), |col, row| {
+            self.color_at(f64::from(img.get_pixel(col, row).0[0]) / f64::from(u16::MAX))
+        })
+    }
+}
+
+/// A choice of colors for presenting an image of the observed flux.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FluxColormap {
+    /// Shades of gray, matching the luma of the grayscale flux image.
+    #[default]
+    Grayscale,
+    Inferno,
+    Magma,
+    Viridis,
+    /// The color of a black body at the local temperature of the disk, implied by its intrinsic
+    /// flux, so the hot inner disk is blue-white and the cooler outer disk red. Brightness is given
+    /// by the observed flux.
+    BlackbodyTemperature,
+}
+
+impl FluxColormap {
+    /// The colormap from normalized flux to color, or None for
+    /// [`BlackbodyTemperature`](FluxColormap::BlackbodyTemperature), whose color doesn't depend on
+    /// the flux.
+    #[must_use]
+    pub fn colormap(self) -> Option<Colormap> {
+        let colors: &[[u8; 3]] = match self {
+            FluxColormap::Grayscale => &[[0; 3], [u8::MAX; 3]],
+            FluxColormap::Inferno => &INFERNO,
+            FluxColormap::Magma => &MAGMA,
+            FluxColormap::Viridis => &VIRIDIS,
+            FluxColormap::BlackbodyTemperature => return None,
+        };
+        #[allow(clippy::cast_precision_loss)]
+        let stops = colors
+            .iter()
+            .enumerate()
+            .map(|(i, color)| (i as f64, color.map(|c| f64::from(c) / f64::from(u8::MAX))))
+            .collect();
+        Some(Colormap::new(stops).expect("at least two distinct stops"))
+    }
+}
+
+/// Generate an image of the observed flux in the colors of the given colormap, from samples of the
+/// image orders given by the options.
+///
+/// The grayscale image, which is what should be dithered, is still available from
+/// [`generate_flux_image`](super::generate_flux_image).
+pub fn generate_flux_image_rgb<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+    colormap: FluxColormap,
+    options: &FluxImageOptions,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let mut samples = sample_orders(blackhole, inclination, sample_count, options)?;
+    let flux = render_orders(
+        blackhole,
+        inclination,
+        &mut samples.clone(),
+        image_width,
+        image_height,
+        options,
+    )?;
+    if let Some(colormap) = colormap.colormap() {
+        let img = colormap.apply(&flux);
+        #[allow(clippy::cast_possible_truncation)]
+        return Ok(RgbImage::from_fn(image_width, image_height, |col, row| {
+            Rgb(img
+                .get_pixel(col, row)
+                .0
+                .map(|c| ((u32::from(c) + 128) / 257) as u8))
+        }));
+    }
+
+    // Interpolate the local temperature, in units of its peak, in the same way as the flux
+    for sample in samples.iter_mut().flatten() {
+        *sample = Sample {
+            observed_flux: calc_temperature(
+                sample.radius,
+                blackhole.accretion_rate,
+                blackhole.mass,
+                blackhole.spin(),
+                blackhole.peak_flux_radius(),
+            ),
+            ..sample.clone()
+        };
+    }
+    let temperature = render_orders(
+        blackhole,
+        inclination,
+        &mut samples,
+        image_width,
+        image_height,
+        &FluxImageOptions {
+            flux_range: Some(0.0..=1.0),
+            min_flux_fraction: 0.0,
+            ..options.clone()
+        },
+    )?;
+    Ok(RgbImage::from_fn(image_width, image_height, |col, row| {
+        let temperature = f64::from(temperature.get_pixel(col, row).0[0]) / f64::from(u16::MAX);
+        let brightness = f64::from(flux.get_pixel(col, row).0[0]) / f64::from(u16::MAX);
+        let color = planckian_rgb(temperature * PEAK_TEMPERATURE_KELVIN);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Rgb(color.map(|channel| (channel * brightness * f64::from(u8::MAX)).round() as u8))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_flux_image_rgb, Colormap, FluxColormap};
+    use crate::{
+        plotting::{image_units_per_pixel, FluxImageOptions},
+        BlackHole,
+    };
+    use cgmath::Deg;
+    use image::Rgb;
+
+    #[test]
+    fn test_colormap_from_csv() {
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("colormap.csv");
+        std::fs::write(
+            &path,
+            "position,r,g,b\n# black to orange\n0,0,0,0\n10,255,128,0\n",
+        )
+        .unwrap();
+        let colormap = Colormap::from_csv(&path).unwrap();
+
+        assert_eq!(colormap.color_at(0.0), Rgb([0, 0, 0]));
+        assert_eq!(colormap.color_at(1.0), Rgb([u16::MAX, 32896, 0]));
+        let Rgb([r, g, b]) = colormap.color_at(0.5);
+        assert_eq!(r, u16::MAX / 2 + 1);
+        assert_eq!(g, 16448);
+        assert_eq!(b, 0);
+        assert_eq!(colormap.color_at(2.0), colormap.color_at(1.0));
+
+        assert!(Colormap::new(vec![(0.0, [0.0; 3])]).is_err());
+    }
+
+    #[test]
+    fn test_flux_colormaps() {
+        let grayscale = FluxColormap::Grayscale.colormap().unwrap();
+        for luma in [0, 1, 12345, u16::MAX] {
+            assert_eq!(
+                grayscale.color_at(f64::from(luma) / f64::from(u16::MAX)),
+                Rgb([luma; 3])
+            );
+        }
+        for colormap in [
+            FluxColormap::Inferno,
+            FluxColormap::Magma,
+            FluxColormap::Viridis,
+        ] {
+            // Each gets brighter from low to high flux
+            let colormap = colormap.colormap().unwrap();
+            let luminance = |position: f64| {
+                let Rgb([r, g, b]) = colormap.color_at(position);
+                0.2126 * f64::from(r) + 0.7152 * f64::from(g) + 0.0722 * f64::from(b)
+            };
+            assert!(
+                (0..8).all(|i| luminance(f64::from(i) / 8.0) < luminance(f64::from(i + 1) / 8.0))
+            );
+        }
+        assert!(FluxColormap::BlackbodyTemperature.colormap().is_none());
+    }
+
+    #[test]
+    fn test_blackbody_temperature_image() {
+        let blackhole = BlackHole::default();
+        let inclination = Deg(10.0);
+        let (width, height) = (64, 64);
+        let img = generate_flux_image_rgb(
+            &blackhole,
+            inclination,
+            2000,
+            width,
+            height,
+            FluxColormap::BlackbodyTemperature,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(img.dimensions(), (width, height));
+
+        // The inner disk is bluer than the outer disk, along the horizontal axis through the
+        // black hole
+        let units_per_pixel = image_units_per_pixel(&blackhole, inclination, width);
+        let blueness = |radius: f64| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let col = width / 2 + (radius / units_per_pixel).round() as u32;
+            let pixel = img.get_pixel(col, height / 2).0;
+            assert!(pixel[0] > 0);
+            f64::from(pixel[2]) / f64::from(pixel[0])
+        };
+        assert!(blueness(8.0) > 2.0 * blueness(40.0));
+    }
+}
@@ -0,0 +1,145 @@
+use super::Luma16Image;
+
+/// Height in pixels of the label strip under each tile.
+const LABEL_HEIGHT: u32 = 24;
+/// Gap in pixels between the left edge of each tile and its label.
+const LABEL_MARGIN: u32 = 4;
+/// Number of image pixels per font pixel.
+const LABEL_SCALE: u32 = 2;
+/// Width of each font glyph, in font pixels.
+const GLYPH_WIDTH: u32 = 5;
+/// Height of each font glyph, in font pixels.
+const GLYPH_HEIGHT: u32 = 7;
+
+/// Arrange a set of labelled images into a grid with the given number of columns.
+///
+/// Each tile is the size of the largest image, with its label written underneath.
+pub fn contact_sheet(
+    tiles: &[(String, Luma16Image)],
+    columns: u32,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    let tile_count = u32::try_from(tiles.len())?;
+    let columns = columns.clamp(1, tile_count.max(1));
+    let rows = tile_count.div_ceil(columns);
+    let tile_width = tiles.iter().map(|(_, img)| img.width()).max().unwrap_or(0);
+    let tile_height = tiles.iter().map(|(_, img)| img.height()).max().unwrap_or(0) + LABEL_HEIGHT;
+
+    let mut sheet = Luma16Image::new(columns * tile_width, rows * tile_height);
+    for (i, (label, img)) in (0..).zip(tiles.iter()) {
+        let (x, y) = ((i % columns) * tile_width, (i / columns) * tile_height);
+        image::imageops::replace(&mut sheet, img, i64::from(x), i64::from(y));
+        let label = render_label(label, tile_width);
+        image::imageops::replace(
+            &mut sheet,
+            &label,
+            i64::from(x),
+            i64::from(y + tile_height - LABEL_HEIGHT),
+        );
+    }
+    Ok(sheet)
+}
+
+/// Render a line of white text on a black background, using a built-in 5x7 pixel font so no
+/// system fonts are needed.
+fn render_label(text: &str, width: u32) -> Luma16Image {
+    let mut label = Luma16Image::new(width, LABEL_HEIGHT);
+    let top = (LABEL_HEIGHT - GLYPH_HEIGHT * LABEL_SCALE) / 2;
+    for (i, c) in (0..).zip(text.chars()) {
+        let left = LABEL_MARGIN + i * (GLYPH_WIDTH + 1) * LABEL_SCALE;
+        for (row, bits) in (0..).zip(glyph(c)) {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                    continue;
+                }
+                for dy in 0..LABEL_SCALE {
+                    for dx in 0..LABEL_SCALE {
+                        let (x, y) = (left + col * LABEL_SCALE + dx, top + row * LABEL_SCALE + dy);
+                        if x < width {
+                            label.put_pixel(x, y, image::Luma([u16::MAX]));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    label
+}
+
+/// The rows of a 5x7 pixel glyph for the given character, with the leftmost pixel in the most
+/// significant bit. Lowercase letters are drawn as uppercase, and unsupported characters as `?`.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match c.to_ascii_uppercase() {
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ' ' => [0x00; GLYPH_HEIGHT as usize],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contact_sheet, LABEL_HEIGHT};
+    use crate::plotting::Luma16Image;
+
+    #[test]
+    fn test_contact_sheet() {
+        let tiles = (1..=5)
+            .map(|i| {
+                (
+                    format!("tile {i}"),
+                    Luma16Image::from_pixel(40, 30, image::Luma([i])),
+                )
+            })
+            .collect::<Vec<(String, Luma16Image)>>();
+        let sheet = contact_sheet(&tiles, 3).unwrap();
+
+        let tile_height = 30 + LABEL_HEIGHT;
+        assert_eq!(sheet.dimensions(), (3 * 40, 2 * tile_height));
+        for i in 0..5 {
+            let (x, y) = ((i % 3) * 40, (i / 3) * tile_height);
+            assert_eq!(
+                sheet.get_pixel(x, y).0[0],
+                tiles[i as usize].1.get_pixel(0, 0).0[0]
+            );
+            let label_has_text = (0..40)
+                .any(|col| (30..tile_height).any(|row| sheet.get_pixel(x + col, y + row).0[0] > 0));
+            assert!(label_has_text);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::{flux::generate_flux_image_from_samples, Colormap, FluxImageOptions, Luma16Image};
+use crate::BlackHole;
+use cgmath::Rad;
+use image::{Rgb, RgbImage};
+
+/// Colors of the diverging colormap for difference images, from the most negative difference
+/// through zero to the most positive, after matplotlib's `RdBu` reversed.
+const DIVERGING: [[u8; 3]; 3] = [[0x21, 0x66, 0xac], [0xf7, 0xf7, 0xf7], [0xb2, 0x18, 0x2b]];
+
+/// Generate an image of the difference in observed flux between the disk seen from
+/// `inclination_b` and from `inclination_a`, to show where tilting the disk adds or removes flux.
+///
+/// Both images are rendered from samples at the same points of the disk and normalized to the
+/// maximum flux of either, and their signed difference is shown with a diverging colormap: red
+/// where there is more flux from `inclination_b`, blue where there is less, and white where they
+/// agree.
+pub fn generate_inclination_difference<A: Into<Rad<f64>>, B: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination_a: A,
+    inclination_b: B,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let inclinations: [Rad<f64>; 2] = [inclination_a.into(), inclination_b.into()];
+    let mut samples = inclinations.map(|inclination| {
+        (
+            blackhole.sample_flux_at_points_seeded(inclination, sample_count, 0, 0),
+            blackhole.sample_flux_at_points_seeded(inclination, sample_count, 1, 1),
+        )
+    });
+    let max_flux = samples
+        .iter()
+        .flat_map(|(direct_samples, ghost_samples)| direct_samples.iter().chain(ghost_samples))
+        .map(|sample| sample.observed_flux)
+        .filter(|flux| flux.is_finite())
+        .fold(0.0, f64::max);
+    let options = FluxImageOptions {
+        flux_range: Some(0.0..=max_flux),
+        ..Default::default()
+    };
+
+    let mut images = Vec::with_capacity(2);
+    for (&inclination, (direct_samples, ghost_samples)) in inclinations.iter().zip(&mut samples) {
+        images.push(generate_flux_image_from_samples(
+            blackhole,
+            inclination,
+            direct_samples,
+            ghost_samples,
+            image_width,
+            image_height,
+            &options,
+        )?);
+    }
+
+    // Map differences from -1 to 1 onto the colormap, with no difference at its centre
+    let difference = Luma16Image::from_fn(image_width, image_height, |col, row| {
+        let (a, b) = (
+            i32::from(images[0].get_pixel(col, row).0[0]),
+            i32::from(images[1].get_pixel(col, row).0[0]),
+        );
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        image::Luma([((b - a + i32::from(u16::MAX)) / 2) as u16])
+    });
+    #[allow(clippy::cast_precision_loss)]
+    let colormap = Colormap::new(
+        DIVERGING
+            .iter()
+            .enumerate()
+            .map(|(i, color)| (i as f64, color.map(|c| f64::from(c) / f64::from(u8::MAX))))
+            .collect(),
+    )?;
+    let colored = colormap.apply(&difference);
+    #[allow(clippy::cast_possible_truncation)]
+    Ok(RgbImage::from_fn(image_width, image_height, |col, row| {
+        Rgb(colored
+            .get_pixel(col, row)
+            .0
+            .map(|c| ((u32::from(c) + 128) / 257) as u8))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_inclination_difference, DIVERGING};
+    use crate::BlackHole;
+    use cgmath::Deg;
+    use image::Rgb;
+
+    #[test]
+    fn test_generate_inclination_difference() {
+        let blackhole = BlackHole::default();
+        let (width, height) = (64, 32);
+        let unchanged =
+            generate_inclination_difference(&blackhole, Deg(60.0), Deg(60.0), 2000, width, height)
+                .unwrap();
+        assert_eq!(unchanged.dimensions(), (width, height));
+        // Half way between the lowest and highest luma, so within a step of the central color
+        assert!(unchanged.pixels().all(|&Rgb(color)| color
+            .iter()
+            .zip(DIVERGING[1])
+            .all(|(&c, expected)| c.abs_diff(expected) <= 1)));
+
+        let tilted =
+            generate_inclination_difference(&blackhole, Deg(10.0), Deg(80.0), 2000, width, height)
+                .unwrap();
+        assert!(tilted
+            .pixels()
+            .any(|&Rgb(color)| u16::from(color[0]) > u16::from(color[2]) + 16));
+        assert!(tilted
+            .pixels()
+            .any(|&Rgb(color)| u16::from(color[2]) > u16::from(color[0]) + 16));
+    }
+}
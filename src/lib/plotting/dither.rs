@@ -1,7 +1,8 @@
-use super::{gilbert, Luma16Image};
+use super::{contact_sheet, gilbert, Luma16Image};
 use clap::ValueEnum;
 use rand::Rng;
 use rayon::iter::ParallelIterator;
+use std::sync::OnceLock;
 
 #[derive(Debug, Copy, Clone, ValueEnum)]
 pub enum DitherAlgorithm {
@@ -10,6 +11,8 @@ pub enum DitherAlgorithm {
     BlueNoise,
     Random,
     Riemersma,
+    HashNoise,
+    Bayer,
 }
 
 impl std::fmt::Display for DitherAlgorithm {
@@ -20,41 +23,96 @@ impl std::fmt::Display for DitherAlgorithm {
             DitherAlgorithm::BlueNoise => write!(f, "blue-noise"),
             DitherAlgorithm::Random => write!(f, "random"),
             DitherAlgorithm::Riemersma => write!(f, "riemersma"),
+            DitherAlgorithm::HashNoise => write!(f, "hash-noise"),
+            DitherAlgorithm::Bayer => write!(f, "bayer"),
         }
     }
 }
 
 pub fn dither(algorithm: DitherAlgorithm, img: &mut Luma16Image) {
+    dither_recording_errors(algorithm, img, None);
+}
+
+/// Dither an image in place, returning a map of the magnitude of the quantization error at each
+/// pixel, as a fraction of full scale.
+pub fn dither_with_error_map(algorithm: DitherAlgorithm, img: &mut Luma16Image) -> Luma16Image {
+    let mut errors = vec![0.0; img.len()];
+    dither_recording_errors(algorithm, img, Some(&mut errors));
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let error_map = errors
+        .iter()
+        .map(|error| (error.abs().min(1.0) * f64::from(u16::MAX)).round() as u16)
+        .collect();
+    Luma16Image::from_raw(img.width(), img.height(), error_map).expect("buffer matches image size")
+}
+
+fn dither_recording_errors(
+    algorithm: DitherAlgorithm,
+    img: &mut Luma16Image,
+    errors: Option<&mut [f64]>,
+) {
     match algorithm {
-        DitherAlgorithm::FloydSteinberg => floyd_steinberg(img),
-        DitherAlgorithm::Atkinson => atkinson(img),
-        DitherAlgorithm::BlueNoise => blue_noise(img),
-        DitherAlgorithm::Random => random(img),
-        DitherAlgorithm::Riemersma => riemersma(img),
+        DitherAlgorithm::FloydSteinberg => floyd_steinberg(img, errors),
+        DitherAlgorithm::Atkinson => atkinson(img, errors),
+        DitherAlgorithm::BlueNoise => blue_noise(img, errors),
+        DitherAlgorithm::Random => random(img, errors),
+        DitherAlgorithm::Riemersma => riemersma(img, errors),
+        DitherAlgorithm::HashNoise => hash_noise(img, errors),
+        DitherAlgorithm::Bayer => {
+            let original = errors.is_some().then(|| img.clone());
+            bayer(img);
+            if let (Some(original), Some(errors)) = (original, errors) {
+                record_quantization_errors(&original, img, errors);
+            }
+        }
     }
 }
 
-fn floyd_steinberg(img: &mut Luma16Image) {
+/// Dither an image with every algorithm, and arrange the results into a labelled contact sheet
+/// for comparison.
+pub fn dither_contact_sheet(img: &Luma16Image) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    let tiles = DitherAlgorithm::value_variants()
+        .iter()
+        .map(|&algorithm| {
+            let mut dithered = img.clone();
+            dither(algorithm, &mut dithered);
+            (algorithm.to_string(), dithered)
+        })
+        .collect::<Vec<(String, Luma16Image)>>();
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let columns = (tiles.len() as f64).sqrt().ceil() as u32;
+    contact_sheet(&tiles, columns)
+}
+
+fn floyd_steinberg(img: &mut Luma16Image, mut errors: Option<&mut [f64]>) {
     let m = [
         (1, 7),
         (img.width() as usize - 1, 3),
         (img.width() as usize, 5),
         (img.width() as usize + 1, 1),
     ];
-    for i in 0..img.len() {
-        let x = f64::from(*img.get(i).unwrap()) / f64::from(u16::MAX);
+    // Diffuse errors through a working buffer, as they can push pixels outside the range of `u16`
+    let mut values = img
+        .iter()
+        .map(|&pixel| f64::from(pixel) / f64::from(u16::MAX))
+        .collect::<Vec<f64>>();
+    for i in 0..values.len() {
+        let x = values[i].clamp(0.0, 1.0);
         let col = if x > 0.5 { 1.0 } else { 0.0 };
+        if let Some(errors) = errors.as_deref_mut() {
+            errors[i] = x - col;
+        }
         let err = (x - col) / 16.0;
         for (x, y) in &m {
-            if let Some(pixel) = img.get_mut(i + x) {
-                *pixel += (err * f64::from(*y) * f64::from(u16::MAX)).round() as u16;
+            if let Some(value) = values.get_mut(i + x) {
+                *value += err * f64::from(*y);
             }
         }
         *img.get_mut(i).unwrap() = (col * f64::from(u16::MAX)).round() as u16;
     }
 }
 
-fn atkinson(img: &mut Luma16Image) {
+fn atkinson(img: &mut Luma16Image, mut errors: Option<&mut [f64]>) {
     let m = [
         1,
         2,
@@ -63,25 +121,60 @@ fn atkinson(img: &mut Luma16Image) {
         img.width() as usize + 1,
         (img.width() * 2) as usize,
     ];
-    for i in 0..img.len() {
-        let x = f64::from(*img.get(i).unwrap()) / f64::from(u16::MAX);
+    // Diffuse errors through a working buffer, as they can push pixels outside the range of `u16`
+    let mut values = img
+        .iter()
+        .map(|&pixel| f64::from(pixel) / f64::from(u16::MAX))
+        .collect::<Vec<f64>>();
+    for i in 0..values.len() {
+        let x = values[i].clamp(0.0, 1.0);
         let col = if x > 0.5 { 1.0 } else { 0.0 };
+        if let Some(errors) = errors.as_deref_mut() {
+            errors[i] = x - col;
+        }
         let err = (x - col) / 8.0;
         for x in &m {
-            if let Some(pixel) = img.get_mut(i + x) {
-                *pixel += (err * f64::from(u16::MAX)).round() as u16;
+            if let Some(value) = values.get_mut(i + x) {
+                *value += err;
             }
         }
         *img.get_mut(i).unwrap() = (col * f64::from(u16::MAX)).round() as u16;
     }
 }
 
-fn blue_noise(img: &mut Luma16Image) {
-    let mask = image::io::Reader::open("images/blue_noise.png")
-        .expect("can open blue noise image file")
-        .decode()
-        .expect("can decode blue noise image file")
-        .to_luma16();
+/// The blue noise threshold mask, embedded so that dithering doesn't depend on the working
+/// directory.
+const BLUE_NOISE_MASK_PNG: &[u8] = include_bytes!("../../../images/blue_noise.png");
+
+/// The embedded blue noise mask, decoded on first use.
+fn blue_noise_mask() -> &'static Luma16Image {
+    static MASK: OnceLock<Luma16Image> = OnceLock::new();
+    MASK.get_or_init(|| {
+        image::load_from_memory_with_format(BLUE_NOISE_MASK_PNG, image::ImageFormat::Png)
+            .expect("can decode embedded blue noise mask")
+            .to_luma16()
+    })
+}
+
+fn blue_noise(img: &mut Luma16Image, errors: Option<&mut [f64]>) {
+    let original = errors.is_some().then(|| img.clone());
+    blue_noise_with_mask(img, blue_noise_mask());
+    if let (Some(original), Some(errors)) = (original, errors) {
+        record_quantization_errors(&original, img, errors);
+    }
+}
+
+/// Dither an image in place by thresholding each pixel against the given mask, tiled across the
+/// image, as [`DitherAlgorithm::BlueNoise`] does with its embedded blue noise mask.
+///
+/// # Panics
+///
+/// If the mask is empty.
+pub fn blue_noise_with_mask(img: &mut Luma16Image, mask: &Luma16Image) {
+    assert!(
+        mask.width() > 0 && mask.height() > 0,
+        "dither mask must not be empty"
+    );
     img.par_enumerate_pixels_mut().for_each(|(x, y, pixel)| {
         let mask_value = mask.get_pixel(x % mask.width(), y % mask.height()).0[0];
         let pixel_value = pixel.0[0];
@@ -93,7 +186,8 @@ fn blue_noise(img: &mut Luma16Image) {
     });
 }
 
-fn random(img: &mut Luma16Image) {
+fn random(img: &mut Luma16Image, errors: Option<&mut [f64]>) {
+    let original = errors.is_some().then(|| img.clone());
     img.par_pixels_mut()
         .for_each_init(rand::thread_rng, |rng, pixel| {
             let offset: i16 = rng.gen();
@@ -104,9 +198,95 @@ fn random(img: &mut Luma16Image) {
                 pixel.0[0] = 0;
             }
         });
+    if let (Some(original), Some(errors)) = (original, errors) {
+        record_quantization_errors(&original, img, errors);
+    }
+}
+
+/// Size of the Bayer ordered dithering matrix.
+const BAYER_SIZE: usize = 8;
+
+/// Bayer ordered dithering matrix, with each threshold index from 0 to `BAYER_SIZE^2 - 1`.
+const BAYER_MATRIX: [[u16; BAYER_SIZE]; BAYER_SIZE] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+/// Bayer matrix thresholds scaled to the full range of `u16`, at the centre of each of the
+/// `BAYER_SIZE^2` intervals.
+const BAYER_THRESHOLDS: [[u16; BAYER_SIZE]; BAYER_SIZE] = {
+    let mut thresholds = [[0; BAYER_SIZE]; BAYER_SIZE];
+    let mut row = 0;
+    while row < BAYER_SIZE {
+        let mut col = 0;
+        while col < BAYER_SIZE {
+            let index = BAYER_MATRIX[row][col] as u32;
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                thresholds[row][col] = (((2 * index + 1) * (u16::MAX as u32 + 1))
+                    / (2 * (BAYER_SIZE * BAYER_SIZE) as u32))
+                    as u16;
+            }
+            col += 1;
+        }
+        row += 1;
+    }
+    thresholds
+};
+
+/// Ordered dithering with a Bayer matrix, using only integer operations and without allocating.
+fn bayer(img: &mut Luma16Image) {
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
+        let threshold = BAYER_THRESHOLDS[y as usize % BAYER_SIZE][x as usize % BAYER_SIZE];
+        pixel.0[0] = if pixel.0[0] > threshold { u16::MAX } else { 0 };
+    }
+}
+
+/// Threshold each pixel against value noise derived by hashing its coordinates, giving the look of
+/// random dithering but reproducibly.
+fn hash_noise(img: &mut Luma16Image, errors: Option<&mut [f64]>) {
+    let original = errors.is_some().then(|| img.clone());
+    img.par_enumerate_pixels_mut().for_each(|(x, y, pixel)| {
+        #[allow(clippy::cast_possible_truncation)]
+        let threshold = (hash_coordinates(x, y) >> 16) as u16;
+        if pixel.0[0] > threshold {
+            pixel.0[0] = u16::MAX;
+        } else {
+            pixel.0[0] = 0;
+        }
+    });
+    if let (Some(original), Some(errors)) = (original, errors) {
+        record_quantization_errors(&original, img, errors);
+    }
+}
+
+/// Hash a pair of pixel coordinates to a well-mixed 32-bit value.
+fn hash_coordinates(x: u32, y: u32) -> u32 {
+    // Combine the coordinates, then apply the "lowbias32" integer hash finalizer
+    let mut h = x.wrapping_mul(0x8da6_b343) ^ y.wrapping_mul(0xd816_3841);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h
+}
+
+/// Record the difference between each pixel of an image before and after it was quantized, as a
+/// fraction of full scale.
+fn record_quantization_errors(original: &Luma16Image, quantized: &Luma16Image, errors: &mut [f64]) {
+    for ((error, before), after) in errors.iter_mut().zip(original.iter()).zip(quantized.iter()) {
+        *error = (f64::from(*before) - f64::from(*after)) / f64::from(u16::MAX);
+    }
 }
 
-fn riemersma(img: &mut Luma16Image) {
+fn riemersma(img: &mut Luma16Image, mut error_map: Option<&mut [f64]>) {
     const ERROR_FALLOFF: f64 = 1.0 / 4.0;
     const ERROR_LENGTH: usize = 32;
 
@@ -135,6 +315,9 @@ fn riemersma(img: &mut Luma16Image) {
         let pixel = image::Luma([quantized_pixel as u16]);
 
         let error = (source_pixel as i32) - (quantized_pixel as i32);
+        if let Some(error_map) = error_map.as_deref_mut() {
+            error_map[(y * img.width() + x) as usize] = f64::from(error) / f64::from(u16::MAX);
+        }
         errors.push(error);
         errors.remove(0);
 
@@ -142,3 +325,195 @@ fn riemersma(img: &mut Luma16Image) {
         curve_idx += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blue_noise_mask, blue_noise_with_mask, dither, dither_contact_sheet, dither_with_error_map,
+        DitherAlgorithm, BAYER_MATRIX,
+    };
+    use crate::plotting::Luma16Image;
+    use clap::ValueEnum;
+
+    #[test]
+    fn test_dither_contact_sheet() {
+        let img = Luma16Image::from_pixel(32, 32, image::Luma([u16::MAX / 4]));
+        let sheet = dither_contact_sheet(&img).unwrap();
+
+        // Tiles are laid out in a 3x3 grid, each with a label strip underneath
+        let algorithm_count = DitherAlgorithm::value_variants().len() as u32;
+        assert_eq!(algorithm_count, 7);
+        let (width, height) = sheet.dimensions();
+        assert_eq!(width, 3 * 32);
+        let tile_height = height / 3;
+        for i in 0..algorithm_count {
+            let (x, y) = ((i % 3) * 32, (i / 3) * tile_height);
+            let tile = (0..32).flat_map(|row| (0..32).map(move |col| (x + col, y + row)));
+            let tile_pixels = tile
+                .map(|(col, row)| sheet.get_pixel(col, row).0[0])
+                .collect::<Vec<u16>>();
+            assert!(tile_pixels.iter().all(|&p| p == 0 || p == u16::MAX));
+            assert!(tile_pixels.contains(&u16::MAX));
+        }
+    }
+
+    #[test]
+    fn test_dither_contact_sheet_of_image() {
+        // Every algorithm handles the full range of a real image, whose errors can push pixels
+        // well beyond black and white
+        let img = blue_noise_mask();
+        let sheet = dither_contact_sheet(img).unwrap();
+        assert_eq!(sheet.width(), 3 * img.width());
+        assert!(sheet.pixels().all(|p| p.0[0] == 0 || p.0[0] == u16::MAX));
+    }
+
+    #[test]
+    fn test_bayer_matches_float_thresholds() {
+        let img = Luma16Image::from_fn(64, 64, |col, row| {
+            image::Luma([(col * 1024 + row * 16) as u16])
+        });
+        let mut dithered = img.clone();
+        dither(DitherAlgorithm::Bayer, &mut dithered);
+
+        let step = 1.0 / f64::from(u16::MAX);
+        for (x, y, pixel) in img.enumerate_pixels() {
+            let index = f64::from(BAYER_MATRIX[y as usize % 8][x as usize % 8]);
+            let threshold = (index + 0.5) / 64.0;
+            let value = f64::from(pixel.0[0]) / f64::from(u16::MAX);
+            let dithered = dithered.get_pixel(x, y).0[0];
+            if (value - threshold).abs() > step {
+                let expected = if value > threshold { u16::MAX } else { 0 };
+                assert_eq!(dithered, expected);
+            }
+        }
+
+        // Each of the 64 thresholds is used once per tile, so a flat image dithers to a matching
+        // density of white pixels
+        let mut flat = Luma16Image::from_pixel(8, 8, image::Luma([u16::MAX / 4]));
+        dither(DitherAlgorithm::Bayer, &mut flat);
+        assert_eq!(flat.pixels().filter(|p| p.0[0] == u16::MAX).count(), 16);
+    }
+
+    #[test]
+    fn test_blue_noise_mask() {
+        let img = Luma16Image::from_fn(96, 96, |col, row| image::Luma([(col * 600 + row) as u16]));
+
+        // The embedded mask matches the one in the repository
+        let mask = image::open(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/images/blue_noise.png"
+        ))
+        .unwrap()
+        .to_luma16();
+        let mut embedded = img.clone();
+        dither(DitherAlgorithm::BlueNoise, &mut embedded);
+        let mut from_file = img.clone();
+        blue_noise_with_mask(&mut from_file, &mask);
+        assert_eq!(embedded, from_file);
+
+        // A flat mask thresholds every pixel at the same level
+        let mut flat = img.clone();
+        blue_noise_with_mask(
+            &mut flat,
+            &Luma16Image::from_pixel(4, 4, image::Luma([u16::MAX / 2])),
+        );
+        for (original, dithered) in img.pixels().zip(flat.pixels()) {
+            let expected = if original.0[0] > u16::MAX / 2 {
+                u16::MAX
+            } else {
+                0
+            };
+            assert_eq!(dithered.0[0], expected);
+        }
+    }
+
+    #[test]
+    fn test_floyd_steinberg_does_not_wrap() {
+        // A high contrast gradient, from black to white across each row
+        let img = Luma16Image::from_fn(64, 64, |col, _| {
+            image::Luma([(f64::from(col) / 63.0 * f64::from(u16::MAX)).round() as u16])
+        });
+        let mut dithered = img.clone();
+        dither(DitherAlgorithm::FloydSteinberg, &mut dithered);
+
+        assert!(dithered.pixels().all(|p| p.0[0] == 0 || p.0[0] == u16::MAX));
+        // Errors diffused into the black and white ends of each row don't wrap around
+        assert!((0..64).all(|row| dithered.get_pixel(0, row).0[0] == 0));
+        assert!((0..64).all(|row| dithered.get_pixel(63, row).0[0] == u16::MAX));
+        let white_fraction =
+            dithered.pixels().filter(|p| p.0[0] == u16::MAX).count() as f64 / f64::from(64 * 64);
+        assert!((white_fraction - 0.5).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_atkinson_does_not_wrap() {
+        let img = Luma16Image::from_fn(64, 64, |col, _| {
+            image::Luma([(f64::from(col) / 63.0 * f64::from(u16::MAX)).round() as u16])
+        });
+        let mut dithered = img.clone();
+        dither(DitherAlgorithm::Atkinson, &mut dithered);
+
+        assert!(dithered.pixels().all(|p| p.0[0] == 0 || p.0[0] == u16::MAX));
+        assert!((0..64).all(|row| dithered.get_pixel(0, row).0[0] == 0));
+        assert!((0..64).all(|row| dithered.get_pixel(63, row).0[0] == u16::MAX));
+        let white_fraction =
+            dithered.pixels().filter(|p| p.0[0] == u16::MAX).count() as f64 / f64::from(64 * 64);
+        assert!((white_fraction - 0.5).abs() < 0.05);
+
+        // Negative errors are diffused too, darkening the pixels after a bright one rounded down
+        let mut dithered = Luma16Image::from_pixel(64, 64, image::Luma([u16::MAX / 4]));
+        dither(DitherAlgorithm::Atkinson, &mut dithered);
+        let white_fraction =
+            dithered.pixels().filter(|p| p.0[0] == u16::MAX).count() as f64 / f64::from(64 * 64);
+        assert!(white_fraction > 0.1 && white_fraction < 0.4);
+    }
+
+    #[test]
+    fn test_hash_noise_is_reproducible() {
+        let img = Luma16Image::from_fn(64, 64, |col, _| image::Luma([(col * 1024) as u16]));
+        let dither_copy = || {
+            let mut dithered = img.clone();
+            dither(DitherAlgorithm::HashNoise, &mut dithered);
+            dithered
+        };
+        let dithered = dither_copy();
+        assert_eq!(dithered, dither_copy());
+
+        // The density of white pixels follows the brightness of the original
+        let white_fraction = |cols: std::ops::Range<u32>| {
+            let count = cols.len() as f64 * 64.0;
+            cols.flat_map(|col| (0..64).map(move |row| (col, row)))
+                .filter(|&(col, row)| dithered.get_pixel(col, row).0[0] == u16::MAX)
+                .count() as f64
+                / count
+        };
+        assert!(white_fraction(0..16) < 0.25);
+        assert!(white_fraction(48..64) > 0.75);
+    }
+
+    #[test]
+    fn test_error_map_follows_gradients() {
+        for algorithm in [DitherAlgorithm::FloydSteinberg, DitherAlgorithm::Atkinson] {
+            // A flat black region at the top, and a steep ramp up to just under half brightness
+            // at the bottom
+            let mut img = Luma16Image::from_fn(32, 32, |_, row| {
+                let value = f64::from(row.saturating_sub(16)) / 16.0 * 0.45;
+                image::Luma([(value * f64::from(u16::MAX)).round() as u16])
+            });
+            let error_map = dither_with_error_map(algorithm, &mut img);
+
+            assert!((0..16).all(|row| (0..32).all(|col| error_map.get_pixel(col, row).0[0] == 0)));
+            let ramp_error_pixels = (20..32)
+                .flat_map(|row| (0..32).map(move |col| (col, row)))
+                .filter(|&(col, row)| error_map.get_pixel(col, row).0[0] > 0)
+                .count();
+            assert!(ramp_error_pixels > 32 * 12 / 2);
+        }
+
+        // Errors are recorded for a real image, whose diffused errors overshoot black and white
+        let mut img = blue_noise_mask().clone();
+        let error_map = dither_with_error_map(DitherAlgorithm::Atkinson, &mut img);
+        assert_eq!(error_map.dimensions(), img.dimensions());
+        assert!(error_map.pixels().any(|p| p.0[0] > u16::MAX / 4));
+    }
+}
@@ -0,0 +1,226 @@
+use super::Luma16Image;
+use std::io::Write;
+
+/// Size of a FITS block, which the header and data are each padded to a multiple of.
+const BLOCK_SIZE: usize = 2880;
+/// Size of a FITS header card.
+const CARD_SIZE: usize = 80;
+/// Maximum length of a FITS header keyword.
+const MAX_KEYWORD_LENGTH: usize = 8;
+/// Offset applied to unsigned 16-bit values to store them as FITS signed 16-bit integers.
+const UNSIGNED_16_ZERO: i32 = 32768;
+/// Keywords which describe the structure of the file, and so can't be set by the caller. The
+/// `NAXISn` keywords are also reserved.
+const RESERVED_KEYWORDS: [&str; 9] = [
+    "SIMPLE", "BITPIX", "NAXIS", "EXTEND", "XTENSION", "PCOUNT", "GCOUNT", "BZERO", "BSCALE",
+];
+
+/// Save an image of the observed flux as the primary HDU of a FITS file, with 16-bit data.
+///
+/// Each header keyword is written as a card after the mandatory ones. Values which parse as finite
+/// numbers, or are `T` or `F`, are written as-is, and anything else is written as a quoted string.
+/// Rows are written from the bottom of the image up, so that FITS viewers show it the same way up
+/// as a PNG.
+///
+/// # Errors
+///
+/// Returns an error if a keyword is invalid, is one of the keywords describing the file's
+/// structure such as `BITPIX`, `NAXISn` or `END`, or the file can't be written.
+pub fn save_fits<P: AsRef<std::path::Path>>(
+    img: &Luma16Image,
+    path: P,
+    header_keywords: &[(String, String)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_fits(img, &mut file, header_keywords)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Write an image as a FITS file to the given writer.
+fn write_fits<W: Write>(
+    img: &Luma16Image,
+    mut writer: W,
+    header_keywords: &[(String, String)],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut header = vec![
+        header_card("SIMPLE", "T")?,
+        header_card("BITPIX", "16")?,
+        header_card("NAXIS", "2")?,
+        header_card("NAXIS1", &img.width().to_string())?,
+        header_card("NAXIS2", &img.height().to_string())?,
+        header_card("BZERO", &UNSIGNED_16_ZERO.to_string())?,
+        header_card("BSCALE", "1")?,
+    ];
+    for (keyword, value) in header_keywords {
+        if is_reserved_keyword(keyword) {
+            return Err(format!("FITS keyword {keyword} is reserved").into());
+        }
+        header.push(header_card(keyword, value)?);
+    }
+    header.push(format!("{:<CARD_SIZE$}", "END"));
+    let mut header = header.concat().into_bytes();
+    header.resize(header.len().next_multiple_of(BLOCK_SIZE), b' ');
+    writer.write_all(&header)?;
+
+    let mut data = Vec::with_capacity((img.width() * img.height()) as usize * 2);
+    for row in img.rows().rev() {
+        for pixel in row {
+            #[allow(clippy::cast_possible_truncation)]
+            let value = (i32::from(pixel.0[0]) - UNSIGNED_16_ZERO) as i16;
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    data.resize(data.len().next_multiple_of(BLOCK_SIZE), 0);
+    writer.write_all(&data)?;
+    Ok(())
+}
+
+/// Whether the keyword describes the structure of the file, or ends the header, so may not be set
+/// by the caller.
+fn is_reserved_keyword(keyword: &str) -> bool {
+    RESERVED_KEYWORDS.contains(&keyword)
+        || keyword == "END"
+        || keyword
+            .strip_prefix("NAXIS")
+            .is_some_and(|axis| !axis.is_empty() && axis.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Format a single FITS header card, with the value right-justified to column 30 if it's a number
+/// or logical, and quoted otherwise.
+fn header_card(keyword: &str, value: &str) -> Result<String, Box<dyn std::error::Error>> {
+    if keyword.is_empty()
+        || keyword.len() > MAX_KEYWORD_LENGTH
+        || !keyword
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "invalid FITS keyword {keyword:?}, expected up to {MAX_KEYWORD_LENGTH} uppercase letters, digits, hyphens or underscores"
+        )
+        .into());
+    }
+    if !value.is_ascii() {
+        return Err(format!("FITS header value {value:?} must be ASCII").into());
+    }
+
+    let value = if value == "T" || value == "F" {
+        format!("{value:>20}")
+    } else if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        // FITS only allows an upper case exponent
+        format!("{:>20}", value.to_ascii_uppercase())
+    } else {
+        format!("{:<20}", format!("'{:<8}'", value.replace('\'', "''")))
+    };
+    let card = format!("{keyword:<MAX_KEYWORD_LENGTH$}= {value}");
+    if card.len() > CARD_SIZE {
+        return Err(format!("FITS header card for {keyword} is too long").into());
+    }
+    Ok(format!("{card:<CARD_SIZE$}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{save_fits, BLOCK_SIZE, CARD_SIZE};
+    use crate::plotting::Luma16Image;
+    use image::Luma;
+
+    /// Read the header cards and data of a FITS primary HDU.
+    fn read_fits(bytes: &[u8]) -> (Vec<(String, String)>, Vec<i16>) {
+        let mut cards = Vec::new();
+        let mut header_end = 0;
+        for (i, card) in bytes.chunks(CARD_SIZE).enumerate() {
+            let card = std::str::from_utf8(card).unwrap();
+            if card.trim_end() == "END" {
+                header_end = (i + 1) * CARD_SIZE;
+                break;
+            }
+            let (keyword, value) = card.split_once('=').unwrap();
+            cards.push((keyword.trim().to_string(), value.trim().to_string()));
+        }
+        let data_start = header_end.next_multiple_of(BLOCK_SIZE);
+        let data = bytes[data_start..]
+            .chunks(2)
+            .map(|pair| i16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        (cards, data)
+    }
+
+    #[test]
+    fn test_fits_round_trip() {
+        let mut img = Luma16Image::new(3, 2);
+        img.put_pixel(0, 0, Luma([u16::MAX]));
+        img.put_pixel(2, 1, Luma([1234]));
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("image.fits");
+        save_fits(
+            &img,
+            &path,
+            &[
+                ("INCLIN".to_string(), "80".to_string()),
+                ("OBJECT".to_string(), "black hole".to_string()),
+            ],
+        )
+        .unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len() % BLOCK_SIZE, 0);
+
+        let (cards, data) = read_fits(&bytes);
+        let value = |keyword: &str| {
+            cards
+                .iter()
+                .find(|(k, _)| k == keyword)
+                .map(|(_, v)| v.as_str())
+        };
+        assert_eq!(cards[0], ("SIMPLE".to_string(), "T".to_string()));
+        assert_eq!(value("NAXIS1"), Some("3"));
+        assert_eq!(value("NAXIS2"), Some("2"));
+        assert_eq!(value("INCLIN"), Some("80"));
+        assert_eq!(value("OBJECT"), Some("'black hole'"));
+
+        let bzero = value("BZERO").unwrap().parse::<i32>().unwrap();
+        let pixel = |col: usize, row: usize| i32::from(data[row * 3 + col]) + bzero;
+        // The first row of FITS data is the bottom row of the image
+        assert_eq!(pixel(0, 1), i32::from(u16::MAX));
+        assert_eq!(pixel(2, 0), 1234);
+        assert_eq!(pixel(1, 0), 0);
+
+        assert!(save_fits(&img, directory.path(), &[]).is_err());
+        let invalid_keyword = [("inclination".to_string(), "80".to_string())];
+        assert!(super::write_fits(&img, Vec::new(), &invalid_keyword).is_err());
+    }
+
+    #[test]
+    fn test_fits_header_values_and_reserved_keywords() {
+        let img = Luma16Image::new(2, 2);
+        let write = |keyword: &str, value: &str| {
+            let mut bytes = Vec::new();
+            super::write_fits(
+                &img,
+                &mut bytes,
+                &[(keyword.to_string(), value.to_string())],
+            )
+            .map(|()| read_fits(&bytes).0)
+        };
+
+        // Only finite numbers are written unquoted
+        let value = |value: &str| {
+            let cards = write("VALUE", value).unwrap();
+            cards.into_iter().find(|(k, _)| k == "VALUE").unwrap().1
+        };
+        assert_eq!(value("1.5e3"), "1.5E3");
+        assert_eq!(value("-2"), "-2");
+        assert_eq!(value("T"), "T");
+        assert_eq!(value("NaN"), "'NaN     '");
+        assert_eq!(value("inf"), "'inf     '");
+        assert_eq!(value("-infinity"), "'-infinity'");
+
+        for keyword in [
+            "END", "SIMPLE", "BITPIX", "NAXIS", "NAXIS1", "NAXIS3", "BZERO",
+        ] {
+            assert!(write(keyword, "1").is_err());
+        }
+        assert!(write("NAXISX", "1").is_ok());
+    }
+}
@@ -1,23 +1,108 @@
-use crate::{BlackHole, Sample};
-use cgmath::{Deg, Rad, Vector2};
+use crate::{
+    interpolation::{DiskOpacity, FluxInterpolator, ZonePriority},
+    BlackHole, Sample, SamplingOptions, SolverConfig, LUMINET_1979_INCLINATION,
+};
+use cgmath::{Basis2, Matrix2, Rad, Rotation, Rotation2, SquareMatrix, Vector2};
 use image::Luma;
 use indicatif::{ParallelProgressIterator, ProgressBar};
-use rayon::iter::ParallelIterator;
-use spade::{Barycentric, DelaunayTriangulation, FloatTriangulation, Triangulation};
-use std::{cmp::Ordering, f64::consts::PI, ops::RangeInclusive};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+use std::ops::RangeInclusive;
 
 pub type Luma16Image = image::ImageBuffer<Luma<u16>, Vec<u16>>;
 
-/// Image order to show at an image pixel.
-enum OrderToShow {
-    None,
-    Direct,
-    Ghost,
+/// Number of samples of each image order per pixel when rendering the paper's photograph.
+const LUMINET_1979_SAMPLES_PER_PIXEL: f64 = 0.1;
+/// Minimum number of samples of each image order when rendering the paper's photograph.
+const LUMINET_1979_MIN_SAMPLES: usize = 2000;
+
+/// Options for rendering an image of the observed flux.
+#[derive(Debug, Clone)]
+pub struct FluxImageOptions {
+    /// The range of flux values mapped to black and white. If `None`, the range is taken from the
+    /// samples.
+    pub flux_range: Option<RangeInclusive<f64>>,
+    /// The opacity of the accretion disk.
+    pub opacity: DiskOpacity,
+    /// Which image is shown where the direct and ghost images overlap, for an opaque disk.
+    pub zone_priority: ZonePriority,
+    /// The position angle of the accretion disk, rotating the image anticlockwise about the
+    /// black hole.
+    pub position_angle: Rad<f64>,
+    /// A linear transform of the observer's plane into the image, such as a camera's orientation
+    /// in a 3D scene, applied after the position angle rotation. It must be invertible.
+    pub camera_transform: Matrix2<f64>,
+    /// Samples with an impact parameter beyond this are dropped before triangulation, to speed up
+    /// rendering when they would fall outside the image anyway. The image scale is unaffected.
+    pub max_impact_parameter: Option<f64>,
+    /// Interpolated flux below this fraction of the maximum of the flux range is set to zero, to
+    /// suppress sampling noise in the faint outer disk.
+    pub min_flux_fraction: f64,
+    /// The luma value of pixels where no image of the disk lies, such as the black hole's shadow.
+    pub background: u16,
+    /// The configuration of the periastron solver, for functions which sample the flux.
+    pub solver: SolverConfig,
+    /// The highest image order sampled, for functions which sample the flux. Images of order two
+    /// and up form ever thinner rings just outside the black hole's shadow, each shown only where
+    /// no lower order image lies.
+    pub max_order: u32,
+    /// The number of samples of each image of order one and up, as a multiple of the number of
+    /// samples of the direct image, for functions which sample the flux. The ghost image covers a
+    /// much smaller region than the direct image, so a multiplier above one gives it comparable
+    /// sample density. Must be finite and non-negative.
+    pub ghost_sample_multiplier: f64,
+}
+
+impl Default for FluxImageOptions {
+    fn default() -> Self {
+        Self {
+            flux_range: None,
+            opacity: DiskOpacity::default(),
+            zone_priority: ZonePriority::default(),
+            position_angle: Rad(0.0),
+            camera_transform: Matrix2::identity(),
+            max_impact_parameter: None,
+            min_flux_fraction: 0.0,
+            background: 0,
+            solver: SolverConfig::default(),
+            max_order: 1,
+            ghost_sample_multiplier: 1.0,
+        }
+    }
+}
+
+impl FluxImageOptions {
+    /// The number of samples to take of the image of the given order, for a sample count of the
+    /// direct image, scaled by the ghost sample multiplier for orders one and up.
+    ///
+    /// Returns an error if the ghost sample multiplier isn't finite and non-negative.
+    pub fn order_sample_count(
+        &self,
+        sample_count: usize,
+        order: u32,
+    ) -> Result<usize, Box<dyn std::error::Error>> {
+        if !(self.ghost_sample_multiplier.is_finite() && self.ghost_sample_multiplier >= 0.0) {
+            return Err(format!(
+                "ghost sample multiplier {} must be finite and non-negative",
+                self.ghost_sample_multiplier
+            )
+            .into());
+        }
+        if order == 0 {
+            Ok(sample_count)
+        } else {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let count = (sample_count as f64 * self.ghost_sample_multiplier).round() as usize;
+            Ok(count)
+        }
+    }
 }
 
 /// Generate a series of images with the given viewer inclination.
 ///
-/// The flux values will be normalized across the whole series of images.
+/// The flux values will be normalized across the whole series of images, and the images are
+/// rendered in parallel.
 pub fn generate_flux_images_inclinations(
     blackhole: &BlackHole,
     sample_count: usize,
@@ -26,43 +111,130 @@ pub fn generate_flux_images_inclinations(
     image_height: u32,
 ) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
     // Sample flux at all inclinations
-    let mut all_samples = Vec::new();
-    let mut max_flux = 0.0;
-    for &inclination in inclinations {
-        let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
-        let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    let mut all_samples = inclinations
+        .iter()
+        .map(|&inclination| {
+            (
+                blackhole.sample_flux_at_points(inclination, sample_count, 0),
+                blackhole.sample_flux_at_points(inclination, sample_count, 1),
+            )
+        })
+        .collect::<Vec<(Vec<Sample>, Vec<Sample>)>>();
+    render_inclinations(
+        blackhole,
+        inclinations,
+        &mut all_samples,
+        image_width,
+        image_height,
+    )
+}
 
-        let inclination_max_flux = direct_samples
-            .iter()
-            .chain(ghost_samples.iter())
-            .map(|s| s.observed_flux)
-            .max_by(|a, b| a.partial_cmp(b).expect("no NaNs"))
-            .expect("non-empty iter of samples");
-        if inclination_max_flux > max_flux {
-            max_flux = inclination_max_flux;
-        }
+/// Render the images of [`generate_flux_images_inclinations`] from the direct and ghost samples
+/// at each inclination, normalized to the maximum flux across all of them.
+///
+/// Each image is rendered on rayon's global thread pool, which the pixel loop of each render
+/// shares, so rendering several at once doesn't oversubscribe the CPU.
+fn render_inclinations(
+    blackhole: &BlackHole,
+    inclinations: &[Rad<f64>],
+    all_samples: &mut [(Vec<Sample>, Vec<Sample>)],
+    image_width: u32,
+    image_height: u32,
+) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
+    let max_flux = all_samples
+        .iter()
+        .flat_map(|(direct_samples, ghost_samples)| direct_samples.iter().chain(ghost_samples))
+        .map(|s| s.observed_flux)
+        .max_by(|a, b| a.partial_cmp(b).expect("no NaNs"))
+        .expect("non-empty iter of samples");
+    let options = FluxImageOptions {
+        flux_range: Some(0.0..=max_flux),
+        ..Default::default()
+    };
 
-        all_samples.push((direct_samples, ghost_samples));
-    }
-    let flux_range = 0.0..=max_flux;
+    let images = inclinations
+        .par_iter()
+        .zip(all_samples.par_iter_mut())
+        .map(|(&inclination, (direct_samples, ghost_samples))| {
+            generate_flux_image_from_samples(
+                blackhole,
+                inclination,
+                direct_samples,
+                ghost_samples,
+                image_width,
+                image_height,
+                &options,
+            )
+            .map_err(|err| err.to_string())
+        })
+        .collect::<Result<Vec<Luma16Image>, String>>()?;
+    Ok(images)
+}
+
+/// Generate a series of images with the given disk position angles, for a turntable animation of
+/// the accretion disk spinning about the line of sight.
+///
+/// The same flux samples are used for every image, so the images differ only in orientation, and
+/// the flux values are normalized across the whole series.
+pub fn generate_flux_images_position_angles<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    position_angles: &[Rad<f64>],
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    let max_flux = direct_samples
+        .iter()
+        .chain(ghost_samples.iter())
+        .map(|s| s.observed_flux)
+        .max_by(|a, b| a.partial_cmp(b).expect("no NaNs"))
+        .expect("non-empty iter of samples");
 
     let mut images = Vec::new();
-    for (&inclination, (direct_samples, ghost_samples)) in
-        inclinations.iter().zip(all_samples.iter_mut())
-    {
+    for &position_angle in position_angles {
+        let options = FluxImageOptions {
+            flux_range: Some(0.0..=max_flux),
+            position_angle,
+            ..Default::default()
+        };
         images.push(generate_flux_image_from_samples(
             blackhole,
             inclination,
-            direct_samples,
-            ghost_samples,
+            &mut direct_samples.clone(),
+            &mut ghost_samples.clone(),
             image_width,
             image_height,
-            Some(flux_range.clone()),
+            &options,
         )?);
     }
     Ok(images)
 }
 
+/// Rescale a set of images so that the brightest pixel across all of them is white, keeping
+/// their brightness consistent with each other.
+pub fn normalize_images(images: &mut [Luma16Image]) {
+    let max_luma = images
+        .iter()
+        .flat_map(|img| img.pixels().map(|p| p.0[0]))
+        .max()
+        .unwrap_or(0);
+    if max_luma == 0 {
+        return;
+    }
+    let scale = f64::from(u16::MAX) / f64::from(max_luma);
+    for img in images.iter_mut() {
+        img.par_pixels_mut().for_each(|pixel| {
+            #[allow(clippy::cast_possible_truncation)]
+            let luma = (f64::from(pixel.0[0]) * scale).round() as u16;
+            pixel.0[0] = luma;
+        });
+    }
+}
+
 /// Generate an image of the observed flux.
 pub fn generate_flux_image<A: Into<Rad<f64>>>(
     blackhole: &BlackHole,
@@ -70,19 +242,87 @@ pub fn generate_flux_image<A: Into<Rad<f64>>>(
     sample_count: usize,
     image_width: u32,
     image_height: u32,
-    flux_range: Option<RangeInclusive<f64>>,
+    options: &FluxImageOptions,
 ) -> Result<Luma16Image, Box<dyn std::error::Error>> {
     let inclination: Rad<f64> = inclination.into();
-    let mut direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
-    let mut ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
-    generate_flux_image_from_samples(
+    render_orders(
         blackhole,
         inclination,
-        &mut direct_samples,
-        &mut ghost_samples,
+        &mut sample_orders(blackhole, inclination, sample_count, options)?,
         image_width,
         image_height,
-        flux_range,
+        options,
+    )
+}
+
+/// Sample the observed flux of each image order up to that of the options, indexed by order, with
+/// the ghost sample multiplier of the options applied to orders one and up.
+pub(super) fn sample_orders(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    sample_count: usize,
+    options: &FluxImageOptions,
+) -> Result<Vec<Vec<Sample>>, Box<dyn std::error::Error>> {
+    let sampling_options = SamplingOptions {
+        solver: options.solver,
+        ..Default::default()
+    };
+    (0..=options.max_order)
+        .map(|order| {
+            Ok(blackhole.sample_flux_at_points_with_options(
+                inclination,
+                options.order_sample_count(sample_count, order)?,
+                order,
+                &sampling_options,
+            ))
+        })
+        .collect()
+}
+
+/// Render an image of the observed flux from the samples of each image order, indexed by order.
+pub(super) fn render_orders(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    samples: &mut [Vec<Sample>],
+    image_width: u32,
+    image_height: u32,
+    options: &FluxImageOptions,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    let interpolator = FluxInterpolator::with_orders(
+        blackhole,
+        inclination,
+        samples.iter_mut().map(Vec::as_mut_slice).collect(),
+        options.max_impact_parameter,
+    )?;
+    let mut img = Luma16Image::new(image_width, image_height);
+    render_into(&interpolator, &mut img, image_width, image_height, options)?;
+    Ok(img)
+}
+
+/// Render the paper's simulated photograph of the black hole from
+/// [`BlackHole::luminet_1979_preset`] at [`LUMINET_1979_INCLINATION`].
+///
+/// The number of samples scales with the size of the image, and they're generated from a fixed
+/// seed so the result is reproducible.
+pub fn render_luminet_1979(
+    image_width: u32,
+    image_height: u32,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    let blackhole = BlackHole::luminet_1979_preset();
+    let inclination = Rad::from(LUMINET_1979_INCLINATION);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let sample_count = ((f64::from(image_width)
+        * f64::from(image_height)
+        * LUMINET_1979_SAMPLES_PER_PIXEL) as usize)
+        .max(LUMINET_1979_MIN_SAMPLES);
+    generate_flux_image_from_samples(
+        &blackhole,
+        inclination,
+        &mut blackhole.sample_flux_at_points_seeded(inclination, sample_count, 0, 0),
+        &mut blackhole.sample_flux_at_points_seeded(inclination, sample_count, 1, 1),
+        image_width,
+        image_height,
+        &FluxImageOptions::default(),
     )
 }
 
@@ -94,205 +334,785 @@ pub fn generate_flux_image_from_samples(
     ghost_samples: &mut [Sample],
     image_width: u32,
     image_height: u32,
-    flux_range: Option<RangeInclusive<f64>>,
+    options: &FluxImageOptions,
 ) -> Result<Luma16Image, Box<dyn std::error::Error>> {
-    // Rotate points by -90 deg
-    let rotation_angle = Rad::from(Deg(-90.0));
-    for sample in direct_samples.iter_mut() {
-        sample.alpha += rotation_angle;
+    let interpolator = FluxInterpolator::with_max_impact_parameter(
+        blackhole,
+        inclination,
+        direct_samples,
+        ghost_samples,
+        options.max_impact_parameter,
+    )?;
+    let mut img = Luma16Image::new(image_width, image_height);
+    render_into(&interpolator, &mut img, image_width, image_height, options)?;
+    Ok(img)
+}
+
+/// Render an image of the observed flux into a row-major buffer of luma values, which must be
+/// `image_width * image_height` long.
+pub fn render_into(
+    interpolator: &FluxInterpolator,
+    buffer: &mut [u16],
+    image_width: u32,
+    image_height: u32,
+    options: &FluxImageOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let pixel_count = image_width as usize * image_height as usize;
+    if buffer.len() != pixel_count {
+        return Err(format!(
+            "buffer of length {} does not match image size {image_width}x{image_height}",
+            buffer.len()
+        )
+        .into());
+    }
+    render_rows_into(interpolator, buffer, image_width, image_height, 0, options)
+}
+
+/// Render a horizontal strip of an image of the observed flux, starting at `first_row`, into a
+/// row-major buffer of luma values holding a whole number of rows.
+///
+/// The strips of an image can be rendered one at a time to bound memory use, and together match
+/// the image rendered by [`render_into`] pixel for pixel.
+pub fn render_rows_into(
+    interpolator: &FluxInterpolator,
+    buffer: &mut [u16],
+    image_width: u32,
+    image_height: u32,
+    first_row: u32,
+    options: &FluxImageOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+    if image_width == 0 || !buffer.len().is_multiple_of(image_width as usize) {
+        return Err(format!(
+            "buffer of length {} is not a whole number of rows of width {image_width}",
+            buffer.len()
+        )
+        .into());
     }
-    for sample in ghost_samples.iter_mut() {
-        sample.alpha += rotation_angle;
+    let row_count = buffer.len() / image_width as usize;
+    if first_row as usize + row_count > image_height as usize {
+        return Err(format!(
+            "rows {first_row} to {} are outside an image of height {image_height}",
+            first_row as usize + row_count
+        )
+        .into());
     }
+    let pixel_count = buffer.len();
 
-    let (min_point, max_point) = samples_range(direct_samples.iter().chain(ghost_samples.iter()));
-    let flux_range = flux_range.unwrap_or_else(|| {
-        let flux_max = direct_samples
-            .iter()
-            .chain(ghost_samples.iter())
-            .map(|s| s.observed_flux)
-            .max_by(|a, b| a.partial_cmp(b).expect("no NaNs"))
-            .expect("non-empty iter of samples");
-        0.0..=flux_max
-    });
+    let flux_range = options
+        .flux_range
+        .clone()
+        .unwrap_or(0.0..=interpolator.max_flux);
+
+    // The minimum flux, normalized to the flux range
+    let min_flux = (options.min_flux_fraction * flux_range.end() - flux_range.start())
+        / (flux_range.end() - flux_range.start());
 
     // Fit the sampled region into the full width of the image, and use that to define the number
     // of real-world units per pixel (to make sure the aspect ratio of each pixel is equal)
-    let units_per_pixel = (max_point.x - min_point.x) / f64::from(image_width);
-    let mut img = Luma16Image::new(image_width, image_height);
+    let units_per_pixel = interpolator.sampled_width / f64::from(image_width);
 
-    // Create Delaunay triangulation so we can linearly interpolate samples on the image pixel grid
-    let direct_triangulation = {
-        let mut t: DelaunayTriangulation<&Sample> = DelaunayTriangulation::new();
-        for sample in direct_samples.iter() {
-            t.insert(sample)?;
-        }
-        t
-    };
-    let ghost_triangulation = {
-        let mut t: DelaunayTriangulation<&Sample> = DelaunayTriangulation::new();
-        for sample in ghost_samples.iter() {
-            t.insert(sample)?;
-        }
-        t
-    };
+    let position_rotation = Basis2::from_angle(-options.position_angle);
+    let camera_inverse = options.camera_transform.invert().ok_or_else(|| {
+        format!(
+            "camera transform {:?} is not invertible",
+            options.camera_transform
+        )
+    })?;
 
     let progress_bar_style = indicatif::ProgressStyle::with_template(
         "{prefix} {bar:60.cyan/blue} {pos:>7}/{len:7} pixels",
     )
     .unwrap();
-    let progress_bar = ProgressBar::new(img.len() as u64)
+    let progress_bar = ProgressBar::new(pixel_count as u64)
         .with_prefix("Rendering image...")
         .with_style(progress_bar_style);
-    img.par_enumerate_pixels_mut()
+    buffer
+        .par_iter_mut()
+        .enumerate()
         .progress_with(progress_bar)
         .for_each_init(
-            || {
-                (
-                    direct_triangulation.barycentric(),
-                    ghost_triangulation.barycentric(),
-                )
-            },
-            |(direct_interpolater, ghost_interpolator), (col, row, pixel)| {
+            || interpolator.interpolators(),
+            |interpolators, (i, pixel)| {
+                let col = (i % image_width as usize) as u32;
+                let row = first_row + (i / image_width as usize) as u32;
                 let x = f64::from((col as i32) - ((image_width / 2) as i32)) * units_per_pixel;
                 let y = -f64::from((row as i32) - ((image_height / 2) as i32)) * units_per_pixel;
-
-                // Determine which zone we're in:
-                //   - Outside the apparent outer edge of the accretion disk -> show ghost image
-                //   - Inside the apparent inner edge of the accretion disk -> show ghost image
-                //   - Inside the apparent inner edge of the black hole -> set to black
-                //   - Otherwise -> show direct image
-                let impact_parameter = (x.powi(2) + y.powi(2)).sqrt();
-                let alpha = Rad(y.atan2(x) + PI / 2.0);
-                let order_to_show = if impact_parameter
-                    <= blackhole.apparent_inner_edge_radius(inclination, alpha)
-                    || impact_parameter > blackhole.apparent_outer_edge_radius(inclination, alpha)
-                {
-                    let apparent_inner_edge_impact_parameter = {
-                        blackhole
-                            .apparent_inner_edge_radius(inclination, alpha)
-                            .min(blackhole.critical_impact_parameter())
-                    };
-                    if impact_parameter < apparent_inner_edge_impact_parameter {
-                        OrderToShow::None
-                    } else {
-                        OrderToShow::Ghost
-                    }
-                } else {
-                    OrderToShow::Direct
+                // Undo the camera transform and position angle rotation to find the point on the
+                // unrotated disk
+                let point = position_rotation.rotate_vector(camera_inverse * Vector2::new(x, y));
+                let Some(flux) = interpolator.flux_at(
+                    interpolators,
+                    point,
+                    &flux_range,
+                    options.opacity,
+                    options.zone_priority,
+                ) else {
+                    *pixel = options.background;
+                    return;
                 };
+                let flux = if flux < min_flux { 0.0 } else { flux };
 
-                let point = spade::Point2 { x, y };
-                match order_to_show {
-                    OrderToShow::None => {
-                        *pixel = image::Luma([0]);
-                    }
-                    OrderToShow::Direct => {
-                        let flux = interpolate_and_normalize_flux(
-                            &point,
-                            direct_interpolater,
-                            &flux_range,
-                        );
-                        #[allow(clippy::cast_possible_truncation)]
-                        let luma = (flux * f64::from(u16::MAX)).round() as u16;
-                        *pixel = image::Luma([luma]);
-                    }
-                    OrderToShow::Ghost => {
-                        let flux =
-                            interpolate_and_normalize_flux(&point, ghost_interpolator, &flux_range);
-                        #[allow(clippy::cast_possible_truncation)]
-                        let luma = (flux * f64::from(u16::MAX)).round() as u16;
-                        *pixel = image::Luma([luma]);
-                    }
-                }
+                #[allow(clippy::cast_possible_truncation)]
+                let luma = (flux * f64::from(u16::MAX)).round() as u16;
+                *pixel = luma;
             },
         );
 
-    Ok(img)
+    Ok(())
 }
 
-fn interpolate_and_normalize_flux(
-    point: &spade::Point2<f64>,
-    interpolator: &mut Barycentric<'_, DelaunayTriangulation<&Sample>>,
-    flux_range: &RangeInclusive<f64>,
-) -> f64 {
-    if let Some(flux) = interpolator.interpolate(|v| v.data().observed_flux, *point) {
-        (flux - flux_range.start()) / (flux_range.end() - flux_range.start())
-    } else {
-        0.0
-    }
+/// Render a pyramid of images of the observed flux, ordered from coarse to fine, with the finest
+/// `base_width` by `base_height` and each coarser level half the size of the next.
+///
+/// Each level is rendered natively at its own resolution, rather than downsampled from the finest,
+/// so the coarse levels can be shown quickly while the finer ones are still rendering.
+pub fn render_pyramid(
+    interpolator: &FluxInterpolator,
+    base_width: u32,
+    base_height: u32,
+    levels: u32,
+    options: &FluxImageOptions,
+) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
+    (0..levels)
+        .rev()
+        .map(|level| {
+            let (width, height) = (
+                base_width.checked_shr(level).unwrap_or(0).max(1),
+                base_height.checked_shr(level).unwrap_or(0).max(1),
+            );
+            let mut img = Luma16Image::new(width, height);
+            render_into(interpolator, &mut img, width, height, options)?;
+            Ok(img)
+        })
+        .collect()
 }
 
-fn samples_range<'a, I>(samples: I) -> (Vector2<f64>, Vector2<f64>)
-where
-    I: Iterator<Item = &'a Sample>,
-{
-    let points = samples.map(Sample::observer_position);
-    let (min_x, min_y, max_x, max_y) = points.fold(
-        (f64::MAX, f64::MAX, f64::MIN, f64::MIN),
-        |(mut min_x, mut min_y, mut max_x, mut max_y), pt| {
-            if pt.x.partial_cmp(&min_x) == Some(Ordering::Less) {
-                min_x = pt.x;
-            }
-            if pt.x.partial_cmp(&max_x) == Some(Ordering::Greater) {
-                max_x = pt.x;
+#[cfg(test)]
+mod tests {
+    use super::{
+        generate_flux_image, generate_flux_image_from_samples,
+        generate_flux_images_position_angles, normalize_images, render_inclinations, render_into,
+        render_luminet_1979, render_pyramid, sample_orders, FluxImageOptions, Luma16Image,
+    };
+    use crate::{
+        interpolation::{
+            ghost_edges, interpolate_and_normalize_flux, DiskOpacity, FluxInterpolator,
+            ZonePriority,
+        },
+        plotting::image_units_per_pixel,
+        BlackHole, IsoRadial, Sample, LUMINET_1979_INCLINATION,
+    };
+    use cgmath::{assert_abs_diff_eq, Deg, Matrix2, Rad, SquareMatrix, Vector2};
+    use spade::{FloatTriangulation, Triangulation};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_transparent_far_side_above_shadow() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(60.0));
+        let (width, height) = (96, 52);
+        let options = FluxImageOptions {
+            opacity: DiskOpacity::Transparent,
+            ..Default::default()
+        };
+        let img =
+            generate_flux_image(&blackhole, inclination, 3000, width, height, &options).unwrap();
+
+        // The far side of the disk (alpha = pi) is lensed up over the top of the shadow
+        let units_per_pixel = image_units_per_pixel(&blackhole, inclination, width);
+        let far_side_impact_parameter = (blackhole
+            .apparent_inner_edge_radius(inclination, Rad(PI))
+            + blackhole.apparent_outer_edge_radius(inclination, Rad(PI)))
+            / 2.0;
+        let row = height / 2 - (far_side_impact_parameter / units_per_pixel).round() as u32;
+        assert!(img.get_pixel(width / 2, row).0[0] > 0);
+        assert_eq!(img.get_pixel(width / 2, height / 2).0[0], 0);
+    }
+
+    #[test]
+    fn test_render_into_matches_allocating_render() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(70.0));
+        let (width, height) = (40, 22);
+        let direct_samples = blackhole.sample_flux_at_points(inclination, 1000, 0);
+        let ghost_samples = blackhole.sample_flux_at_points(inclination, 1000, 1);
+        let options = FluxImageOptions::default();
+
+        let img = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut direct_samples.clone(),
+            &mut ghost_samples.clone(),
+            width,
+            height,
+            &options,
+        )
+        .unwrap();
+
+        let (mut direct_samples, mut ghost_samples) = (direct_samples, ghost_samples);
+        let interpolator = FluxInterpolator::new(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )
+        .unwrap();
+        let mut buffer = vec![0; (width * height) as usize];
+        render_into(&interpolator, &mut buffer, width, height, &options).unwrap();
+        assert_eq!(img.as_raw(), &buffer);
+
+        assert!(render_into(&interpolator, &mut buffer, width + 1, height, &options).is_err());
+    }
+
+    #[test]
+    fn test_render_pyramid() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(70.0));
+        let mut direct_samples = blackhole.sample_flux_at_points(inclination, 1000, 0);
+        let mut ghost_samples = blackhole.sample_flux_at_points(inclination, 1000, 1);
+        let interpolator = FluxInterpolator::new(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )
+        .unwrap();
+        let options = FluxImageOptions::default();
+
+        let pyramid = render_pyramid(&interpolator, 64, 36, 4, &options).unwrap();
+        let dimensions = pyramid
+            .iter()
+            .map(Luma16Image::dimensions)
+            .collect::<Vec<_>>();
+        assert_eq!(dimensions, [(8, 4), (16, 9), (32, 18), (64, 36)]);
+
+        let mut img = Luma16Image::new(64, 36);
+        render_into(&interpolator, &mut img, 64, 36, &options).unwrap();
+        assert_eq!(pyramid.last(), Some(&img));
+        assert!(pyramid
+            .iter()
+            .all(|level| level.pixels().any(|p| p.0[0] > 0)));
+
+        assert!(render_pyramid(&interpolator, 64, 36, 0, &options)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_face_on_is_rotationally_symmetric() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad(0.0);
+        let size = 40;
+        let img = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut blackhole.sample_flux_at_points_seeded(inclination, 3000, 0, 1),
+            &mut blackhole.sample_flux_at_points_seeded(inclination, 3000, 1, 2),
+            size,
+            size,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+
+        // Rotating by 90 deg about the image centre maps pixel (col, row) to
+        // (size - row, col), leaving only small differences from sampling noise
+        let mut mismatched_pixels = 0;
+        for row in 1..size {
+            for col in 1..size {
+                let a = img.get_pixel(col, row).0[0];
+                let b = img.get_pixel(size - row, col).0[0];
+                if a.abs_diff(b) > u16::MAX / 10 {
+                    mismatched_pixels += 1;
+                }
             }
-            if pt.y.partial_cmp(&min_y) == Some(Ordering::Less) {
-                min_y = pt.y;
+        }
+        assert!(mismatched_pixels <= size * size / 100);
+        assert_eq!(img.get_pixel(size / 2, size / 2).0[0], 0);
+
+        // Between the critical impact parameter and the inner edge of the ghost image there is
+        // no flux, even though the ghost image's triangulation covers it
+        let ghost_inner_edge = IsoRadial::new(&blackhole, blackhole.disk_inner_edge(), 1)
+            .get_impact_parameter_from_alpha(inclination, Rad(0.0));
+        let b = (ghost_inner_edge + blackhole.critical_impact_parameter()) / 2.0;
+        let fluxes = blackhole
+            .flux_at_observer_points(
+                inclination,
+                3000,
+                &[Vector2::new(b, 0.0), Vector2::new(0.0, b)],
+            )
+            .unwrap();
+        assert_eq!(fluxes, vec![None, None]);
+    }
+
+    #[test]
+    fn test_edge_on_render() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad(PI / 2.0);
+        let mut direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 3000, 0, 1);
+        let mut ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 3000, 1, 2);
+        assert!(direct_samples.iter().chain(ghost_samples.iter()).all(|s| {
+            s.impact_parameter.is_finite()
+                && s.redshift_factor.is_finite()
+                && s.observed_flux.is_finite()
+        }));
+
+        // The near side of the disk should appear the same width on both sides of the shadow
+        assert_abs_diff_eq!(
+            blackhole.apparent_outer_edge_radius(inclination, Rad(PI / 2.0)),
+            blackhole.apparent_outer_edge_radius(inclination, Rad(3.0 * PI / 2.0)),
+            epsilon = 1e-6
+        );
+
+        let (width, height) = (64, 32);
+        let img = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+            width,
+            height,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+        let lit_pixels = |rows: std::ops::Range<u32>| {
+            rows.flat_map(|row| (0..width).map(move |col| (col, row)))
+                .filter(|&(col, row)| img.get_pixel(col, row).0[0] > 0)
+                .count()
+        };
+        // Both the far side of the disk lensed over the top of the shadow and the ghost image
+        // below it are visible
+        assert!(lit_pixels(0..height / 2 - 1) > 0);
+        assert!(lit_pixels(height / 2 + 1..height) > 0);
+        assert_eq!(img.get_pixel(width / 2, height / 2).0[0], 0);
+    }
+
+    #[test]
+    fn test_zone_priority() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let mut direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 2000, 0, 1);
+        let mut ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 2000, 1, 2);
+        let interpolator = FluxInterpolator::new(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )
+        .unwrap();
+
+        // Below the shadow, the ghost image of the far side lies behind the near side of the disk
+        let ghost_inner_edge = IsoRadial::new(&blackhole, blackhole.disk_inner_edge(), 1)
+            .get_impact_parameter_from_alpha(inclination, Rad(PI));
+        let direct_outer_edge = blackhole.apparent_outer_edge_radius(inclination, Rad(0.0));
+        assert!(ghost_inner_edge < direct_outer_edge);
+        let b = (ghost_inner_edge + direct_outer_edge) / 2.0;
+        let point = Vector2::new(0.0, -b);
+        let flux_at = |priority| {
+            interpolator.flux_at(
+                &mut interpolator.interpolators(),
+                point,
+                &(0.0..=1.0),
+                DiskOpacity::Opaque,
+                priority,
+            )
+        };
+        let direct_flux = interpolate_and_normalize_flux(
+            &spade::Point2 { x: 0.0, y: -b },
+            &mut interpolator.triangulations[0].barycentric(),
+            &(0.0..=1.0),
+        );
+        let ghost_flux = interpolate_and_normalize_flux(
+            &spade::Point2 { x: 0.0, y: -b },
+            &mut interpolator.triangulations[1].barycentric(),
+            &(0.0..=1.0),
+        );
+        assert_ne!(direct_flux, ghost_flux);
+        assert_eq!(flux_at(ZonePriority::Direct), Some(direct_flux));
+        assert_eq!(flux_at(ZonePriority::Ghost), Some(ghost_flux));
+    }
+
+    #[test]
+    fn test_background() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let render = |background| {
+            generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 2000, 0, 1),
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 2000, 1, 2),
+                64,
+                32,
+                &FluxImageOptions {
+                    background,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let (black, grey) = (render(0), render(1000));
+
+        // The shadow and the corners beyond the ghost image show the background
+        for (col, row) in [(32, 16), (0, 0), (63, 31)] {
+            assert_eq!(black.get_pixel(col, row).0[0], 0);
+            assert_eq!(grey.get_pixel(col, row).0[0], 1000);
+        }
+        // and the disk itself is unchanged
+        for (black, grey) in black.pixels().zip(grey.pixels()) {
+            assert!(black == grey || grey.0[0] == 1000);
+        }
+        assert!(black
+            .pixels()
+            .zip(grey.pixels())
+            .any(|(b, g)| b == g && b.0[0] > 0));
+    }
+
+    #[test]
+    fn test_max_impact_parameter() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let sample =
+            |order, seed| blackhole.sample_flux_at_points_seeded(inclination, 2000, order, seed);
+        let (mut direct_samples, mut ghost_samples) = (sample(0, 1), sample(1, 2));
+        let (mut clamped_direct_samples, mut clamped_ghost_samples) = (sample(0, 1), sample(1, 2));
+        let interpolator = FluxInterpolator::new(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )
+        .unwrap();
+        let clamped_interpolator = FluxInterpolator::with_max_impact_parameter(
+            &blackhole,
+            inclination,
+            &mut clamped_direct_samples,
+            &mut clamped_ghost_samples,
+            Some(20.0),
+        )
+        .unwrap();
+
+        assert!(
+            clamped_interpolator.triangulations[0].num_vertices()
+                < interpolator.triangulations[0].num_vertices()
+        );
+        assert_eq!(
+            clamped_interpolator.sampled_width,
+            interpolator.sampled_width
+        );
+
+        let points = (-10..=10)
+            .flat_map(|x| (-5..=5).map(move |y| Vector2::new(f64::from(x), f64::from(y))))
+            .collect::<Vec<Vector2<f64>>>();
+        assert_eq!(
+            clamped_interpolator.observed_flux_at_points(&points, DiskOpacity::Opaque),
+            interpolator.observed_flux_at_points(&points, DiskOpacity::Opaque)
+        );
+    }
+
+    #[test]
+    fn test_min_flux_fraction() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let render = |min_flux_fraction| {
+            generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 2000, 0, 1),
+                &mut blackhole.sample_flux_at_points_seeded(inclination, 2000, 1, 2),
+                64,
+                32,
+                &FluxImageOptions {
+                    min_flux_fraction,
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        let img = render(0.0);
+        let clipped_img = render(0.1);
+
+        let threshold = (0.1 * f64::from(u16::MAX)) as u16;
+        let mut clipped_pixels = 0;
+        for (pixel, clipped_pixel) in img.pixels().zip(clipped_img.pixels()) {
+            if pixel.0[0] < threshold {
+                if pixel.0[0] > 0 {
+                    clipped_pixels += 1;
+                }
+                assert_eq!(clipped_pixel.0[0], 0);
+            } else if pixel.0[0] > threshold {
+                assert_eq!(clipped_pixel, pixel);
             }
-            if pt.y.partial_cmp(&max_y) == Some(Ordering::Greater) {
-                max_y = pt.y;
+        }
+        assert!(clipped_pixels > 0);
+        assert!(clipped_img.pixels().any(|p| p.0[0] > 0));
+    }
+
+    #[test]
+    fn test_luminet_1979() {
+        let blackhole = BlackHole::luminet_1979_preset();
+        assert_eq!(blackhole.mass, 1.0);
+        assert_eq!(blackhole.disk_inner_edge(), 6.0);
+        assert_eq!(LUMINET_1979_INCLINATION, Deg(80.0));
+
+        let img = render_luminet_1979(48, 24).unwrap();
+        assert_eq!(img.dimensions(), (48, 24));
+        assert!(img.pixels().any(|p| p.0[0] > 0));
+        assert_eq!(img, render_luminet_1979(48, 24).unwrap());
+    }
+
+    #[test]
+    fn test_non_finite_samples_are_dropped() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let mut direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 1000, 0, 1);
+        let mut ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 1000, 1, 2);
+        let img = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut direct_samples.clone(),
+            &mut ghost_samples.clone(),
+            32,
+            16,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+
+        direct_samples.push(Sample {
+            impact_parameter: f64::NAN,
+            ..direct_samples[0].clone()
+        });
+        ghost_samples.push(Sample {
+            observed_flux: f64::NAN,
+            ..ghost_samples[0].clone()
+        });
+        let img_with_nans = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+            32,
+            16,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(img_with_nans, img);
+    }
+
+    #[test]
+    fn test_position_angle_half_turn_is_point_reflection() {
+        let blackhole = BlackHole::default();
+        let (width, height) = (48, 26);
+        let images = generate_flux_images_position_angles(
+            &blackhole,
+            Deg(70.0),
+            &[Rad(0.0), Rad(PI)],
+            1500,
+            width,
+            height,
+        )
+        .unwrap();
+
+        // Pixel (col, row) is at (x, y) relative to the image centre (width / 2, height / 2), so
+        // its reflection is at (width - col, height - row)
+        let mut mismatched_pixels = 0;
+        for row in 1..height {
+            for col in 1..width {
+                let a = images[0].get_pixel(col, row).0[0];
+                let b = images[1].get_pixel(width - col, height - row).0[0];
+                if a.abs_diff(b) > u16::MAX / 100 {
+                    mismatched_pixels += 1;
+                }
             }
-            (min_x, min_y, max_x, max_y)
-        },
-    );
-    (Vector2::new(min_x, min_y), Vector2::new(max_x, max_y))
-}
+        }
+        assert!(mismatched_pixels <= 2);
+        assert_ne!(images[0], images[1]);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::samples_range;
-    use crate::Sample;
-    use cgmath::{assert_abs_diff_eq, Deg, Rad, Vector2};
+    #[test]
+    fn test_ghost_sample_multiplier() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let options = FluxImageOptions {
+            ghost_sample_multiplier: 3.0,
+            ..Default::default()
+        };
+        let mut samples = sample_orders(&blackhole, inclination, 1000, &options).unwrap();
+        assert_eq!(samples[0].len(), 1000);
+        assert_eq!(samples[1].len(), 3000);
+
+        let interpolator = FluxInterpolator::with_orders(
+            &blackhole,
+            inclination,
+            samples.iter_mut().map(Vec::as_mut_slice).collect(),
+            None,
+        )
+        .unwrap();
+        let vertices = interpolator
+            .triangulations
+            .iter()
+            .map(|triangulation| triangulation.num_vertices() as f64)
+            .collect::<Vec<f64>>();
+        assert!((vertices[1] / vertices[0] - 3.0).abs() < 0.1);
+
+        for ghost_sample_multiplier in [-1.0, f64::NAN, f64::INFINITY] {
+            let options = FluxImageOptions {
+                ghost_sample_multiplier,
+                ..Default::default()
+            };
+            assert!(options.order_sample_count(1000, 1).is_err());
+            assert!(sample_orders(&blackhole, inclination, 1000, &options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_camera_transform() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(70.0));
+        let direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 1500, 0, 0);
+        let ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 1500, 1, 1);
+        let render = |options: &FluxImageOptions| {
+            generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                &mut direct_samples.clone(),
+                &mut ghost_samples.clone(),
+                48,
+                48,
+                options,
+            )
+        };
+
+        let default = render(&FluxImageOptions::default()).unwrap();
+        let identity = render(&FluxImageOptions {
+            camera_transform: Matrix2::identity(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(identity, default);
+
+        // A rotation matrix rotates the image like the position angle does
+        let angle = Rad::from(Deg(30.0));
+        let rotated = render(&FluxImageOptions {
+            camera_transform: Matrix2::from_angle(angle),
+            ..Default::default()
+        })
+        .unwrap();
+        let position_angle = render(&FluxImageOptions {
+            position_angle: angle,
+            ..Default::default()
+        })
+        .unwrap();
+        assert_ne!(rotated, default);
+        assert!(rotated
+            .pixels()
+            .zip(position_angle.pixels())
+            .all(|(a, b)| a.0[0].abs_diff(b.0[0]) <= 1));
+
+        assert!(render(&FluxImageOptions {
+            camera_transform: Matrix2::new(1.0, 2.0, 2.0, 4.0),
+            ..Default::default()
+        })
+        .is_err());
+    }
 
     #[test]
-    fn test_samples_range() {
+    fn test_render_inclinations() {
+        let blackhole = BlackHole::default();
+        let inclinations = [10.0, 45.0, 80.0].map(|i| Rad::from(Deg(i)));
+        let all_samples = inclinations
+            .iter()
+            .map(|&inclination| {
+                (
+                    blackhole.sample_flux_at_points_seeded(inclination, 1000, 0, 0),
+                    blackhole.sample_flux_at_points_seeded(inclination, 1000, 1, 1),
+                )
+            })
+            .collect::<Vec<_>>();
+        let parallel =
+            render_inclinations(&blackhole, &inclinations, &mut all_samples.clone(), 48, 24)
+                .unwrap();
+
+        let max_flux = all_samples
+            .iter()
+            .flat_map(|(direct, ghost)| direct.iter().chain(ghost))
+            .map(|s| s.observed_flux)
+            .fold(0.0, f64::max);
+        let options = FluxImageOptions {
+            flux_range: Some(0.0..=max_flux),
+            ..Default::default()
+        };
+        for ((&inclination, (direct, ghost)), img) in
+            inclinations.iter().zip(all_samples).zip(parallel)
         {
-            let samples = vec![Sample {
-                radius: 1.0,
-                alpha: Rad(0.0),
-                impact_parameter: 1.0,
-                order: 0,
-                redshift_factor: 0.0,
-                observed_flux: 0.0,
-            }];
-            let (min_pt, max_pt) = samples_range(samples.iter());
-            assert_eq!(min_pt, Vector2::new(1.0, 0.0));
-            assert_eq!(max_pt, Vector2::new(1.0, 0.0));
+            let sequential = generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                &mut direct.clone(),
+                &mut ghost.clone(),
+                48,
+                24,
+                &options,
+            )
+            .unwrap();
+            assert_eq!(img, sequential);
         }
+    }
 
-        {
-            let samples = vec![
-                Sample {
-                    radius: 1.0,
-                    alpha: Rad(0.0),
-                    impact_parameter: 1.0,
-                    order: 0,
-                    redshift_factor: 0.0,
-                    observed_flux: 0.0,
-                },
-                Sample {
-                    radius: 1.0,
-                    alpha: Rad::from(Deg(-180.0)),
-                    impact_parameter: 1.0,
-                    order: 0,
-                    redshift_factor: 0.0,
-                    observed_flux: 0.0,
-                },
-            ];
-            let (min_pt, max_pt) = samples_range(samples.iter());
-            assert_abs_diff_eq!(min_pt, Vector2::new(-1.0, 0.0));
-            assert_abs_diff_eq!(max_pt, Vector2::new(1.0, 0.0));
+    #[test]
+    fn test_normalize_images() {
+        let mut images = vec![
+            Luma16Image::from_pixel(4, 4, image::Luma([100])),
+            Luma16Image::from_pixel(4, 4, image::Luma([200])),
+        ];
+        images[1].put_pixel(0, 0, image::Luma([1000]));
+        normalize_images(&mut images);
+
+        let images_with_max = images
+            .iter()
+            .filter(|img| img.pixels().any(|p| p.0[0] == u16::MAX))
+            .count();
+        assert_eq!(images_with_max, 1);
+        assert_eq!(images[1].get_pixel(0, 0).0[0], u16::MAX);
+        assert_eq!(images[0].get_pixel(0, 0).0[0], 6554);
+    }
+
+    #[test]
+    fn test_higher_order_ring() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let samples = (0..=2)
+            .map(|order| {
+                blackhole.sample_flux_at_points_seeded(inclination, 3000, order, u64::from(order))
+            })
+            .collect::<Vec<Vec<Sample>>>();
+
+        // Above the shadow, the order 2 image is a thin ring inside the ghost image's inner edge
+        let (inner_edge, outer_edge) = ghost_edges(&blackhole, 2);
+        let b = (inner_edge.get_impact_parameter_from_alpha(inclination, Rad(0.0))
+            + outer_edge.get_impact_parameter_from_alpha(inclination, Rad(0.0)))
+            / 2.0;
+        assert!(b > blackhole.critical_impact_parameter());
+        assert!(
+            b < IsoRadial::new(&blackhole, blackhole.disk_inner_edge(), 1)
+                .get_impact_parameter_from_alpha(inclination, Rad(0.0))
+        );
+        let flux_at = |max_order: usize, opacity| {
+            let mut samples = samples[..=max_order].to_vec();
+            FluxInterpolator::with_orders(
+                &blackhole,
+                inclination,
+                samples.iter_mut().map(Vec::as_mut_slice).collect(),
+                None,
+            )
+            .unwrap()
+            .observed_flux_at_points(&[Vector2::new(0.0, b)], opacity)[0]
+        };
+        for opacity in [DiskOpacity::Opaque, DiskOpacity::Transparent] {
+            assert_eq!(flux_at(1, opacity), None);
+            assert!(flux_at(2, opacity).is_some_and(|flux| flux > 0.0));
         }
     }
 }
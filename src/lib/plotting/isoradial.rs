@@ -1,69 +1,299 @@
-use crate::{BlackHole, IsoRadial};
+use crate::{BlackHole, IsoRadial, SolverConfig};
 use cgmath::{Basis2, Deg, Rad, Rotation, Rotation2};
+use image::RgbImage;
 use plotters::prelude::*;
 use std::f64::consts::PI;
 
 const IMAGE_RESOLUTION: (u32, u32) = (1024, 1024);
 const ANGLE_COUNT: usize = 360;
+/// Half-width of the square region of the observer's frame shown in isoradial plots.
+const PLOT_EXTENT: f64 = 35.0;
+/// Duration each inclination is shown for in animated isoradial plots, in seconds.
+const ANIMATION_SECONDS_PER_INCLINATION: f64 = 0.5;
 
 /// Plot a set of isoradial curves for the given black hole.
+///
+/// The plot is drawn at `supersample` times the output resolution in each dimension, then
+/// downsampled to antialias the curves. A `supersample` of 1 disables antialiasing.
 pub fn plot_isoradials<P: AsRef<std::path::Path>, A: Into<Rad<f64>>>(
     blackhole: &BlackHole,
     inclination: A,
     radii: &[(f64, u32)],
+    supersample: u32,
     path: P,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let inclination: Rad<f64> = inclination.into();
-
-    let root = BitMapBackend::new(&path, IMAGE_RESOLUTION).into_drawing_area();
-    root.fill(&WHITE)?;
-    let mut chart =
-        ChartBuilder::on(&root).build_cartesian_2d(-35.0_f32..35.0_f32, -35.0_f32..35.0_f32)?;
-
-    // Plot apparent black hole radius
-    let angles = (0..u32::try_from(ANGLE_COUNT)?).map(|i| f64::from(i) / 360_f64 * 2.0 * PI);
-    #[allow(clippy::cast_possible_truncation)]
-    chart.draw_series(LineSeries::new(
-        angles.clone().map(|a| {
-            let apparent_inner_edge_impact_parameter = blackhole
-                .apparent_inner_edge_radius(inclination, Rad(a + PI / 2.0))
-                .min(blackhole.critical_impact_parameter());
-            (
-                (apparent_inner_edge_impact_parameter * a.cos()) as f32,
-                (apparent_inner_edge_impact_parameter * a.sin()) as f32,
-            )
-        }),
-        ShapeStyle {
-            color: BLACK.mix(1.0),
-            filled: false,
-            stroke_width: 2,
-        },
-    ))?;
-
-    // Plot isoradials
+    render_isoradials(
+        blackhole,
+        inclination.into(),
+        radii,
+        IMAGE_RESOLUTION,
+        supersample,
+    )?
+    .save(path)?;
+    Ok(())
+}
+
+/// Plot a set of isoradial curves for the given black hole as an SVG, animated to cycle through
+/// the given inclinations.
+///
+/// Each curve is a single path whose shape is animated with SMIL, with one keyframe per
+/// inclination, so the file can be viewed directly in a web browser.
+///
+/// # Errors
+///
+/// Returns an error if no inclinations are given or the file can't be written.
+pub fn animate_isoradials_svg<P: AsRef<std::path::Path>>(
+    blackhole: &BlackHole,
+    inclinations: &[Rad<f64>],
+    radii: &[(f64, u32)],
+    path: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::write(path, isoradials_svg(blackhole, inclinations, radii)?)?;
+    Ok(())
+}
+
+/// Generate the contents of an SVG animating isoradial curves through the given inclinations.
+fn isoradials_svg(
+    blackhole: &BlackHole,
+    inclinations: &[Rad<f64>],
+    radii: &[(f64, u32)],
+) -> Result<String, Box<dyn std::error::Error>> {
+    if inclinations.is_empty() {
+        return Err("at least one inclination is needed to animate isoradials".into());
+    }
+
+    let duration = ANIMATION_SECONDS_PER_INCLINATION * inclinations.len() as f64;
+    let key_times = (0..inclinations.len())
+        .map(|i| format!("{:.4}", i as f64 / inclinations.len() as f64))
+        .collect::<Vec<String>>()
+        .join(";");
+    let animated_path = |frames: Vec<String>, stroke_opacity: f64| {
+        format!(
+            "  <path d=\"{}\" fill=\"none\" stroke=\"black\" stroke-opacity=\"{stroke_opacity}\" stroke-width=\"0.2\">\n    <animate attributeName=\"d\" values=\"{}\" keyTimes=\"{key_times}\" calcMode=\"discrete\" dur=\"{duration}s\" repeatCount=\"indefinite\"/>\n  </path>\n",
+            frames[0],
+            frames.join(";")
+        )
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n  <rect x=\"{}\" y=\"{}\" width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        -PLOT_EXTENT,
+        -PLOT_EXTENT,
+        2.0 * PLOT_EXTENT,
+        2.0 * PLOT_EXTENT,
+        IMAGE_RESOLUTION.0,
+        IMAGE_RESOLUTION.1,
+        -PLOT_EXTENT,
+        -PLOT_EXTENT,
+    );
+
+    // Apparent black hole radius
+    let shadow_frames = inclinations
+        .iter()
+        .map(|&inclination| {
+            svg_path_data((0..ANGLE_COUNT).map(|i| {
+                let a = i as f64 / ANGLE_COUNT as f64 * 2.0 * PI;
+                let impact_parameter = blackhole
+                    .apparent_inner_edge_radius(inclination, Rad(a + PI / 2.0))
+                    .min(blackhole.critical_impact_parameter());
+                (impact_parameter * a.cos(), impact_parameter * a.sin())
+            }))
+        })
+        .collect();
+    svg.push_str(&animated_path(shadow_frames, 1.0));
+
+    // Isoradials
     let rotation = Basis2::from_angle(Deg(-90.0));
     for (radius, order) in radii {
         let isoradial = IsoRadial::new(blackhole, *radius, *order);
-        let coords = isoradial.calculate_coordinates(inclination, ANGLE_COUNT);
+        let frames = inclinations
+            .iter()
+            .map(|&inclination| {
+                svg_path_data(
+                    isoradial
+                        .calculate_coordinates(inclination, ANGLE_COUNT, &SolverConfig::default())
+                        .into_iter()
+                        .map(|pt| {
+                            // Rotate points by -90 deg, and vertically flip ghost image points
+                            let pt = rotation.rotate_vector(pt);
+                            (pt.x, if *order > 0 { -pt.y } else { pt.y })
+                        }),
+                )
+            })
+            .collect();
+        svg.push_str(&animated_path(frames, if *order > 0 { 0.25 } else { 0.5 }));
+    }
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Format a closed curve through the given points in the observer's frame as SVG path data,
+/// flipping the y axis to match SVG's downwards y axis.
+fn svg_path_data<I: Iterator<Item = (f64, f64)>>(points: I) -> String {
+    let commands = points
+        .enumerate()
+        .map(|(i, (x, y))| format!("{}{x:.3},{:.3}", if i == 0 { 'M' } else { 'L' }, -y))
+        .collect::<Vec<String>>();
+    format!("{}Z", commands.join(""))
+}
+
+/// Render a plot of isoradial curves to an image of the given resolution, drawing at
+/// `supersample` times that resolution and downsampling.
+fn render_isoradials(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    radii: &[(f64, u32)],
+    resolution: (u32, u32),
+    supersample: u32,
+) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let supersample = supersample.max(1);
+    let (width, height) = (resolution.0 * supersample, resolution.1 * supersample);
+    let stroke_width = 2 * supersample;
+
+    let mut buffer = vec![0; width as usize * height as usize * 3];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area();
+        root.fill(&WHITE)?;
+        #[allow(clippy::cast_possible_truncation)]
+        let extent = PLOT_EXTENT as f32;
+        let mut chart =
+            ChartBuilder::on(&root).build_cartesian_2d(-extent..extent, -extent..extent)?;
+
+        // Plot apparent black hole radius
+        let angles = (0..u32::try_from(ANGLE_COUNT)?).map(|i| f64::from(i) / 360_f64 * 2.0 * PI);
         #[allow(clippy::cast_possible_truncation)]
         chart.draw_series(LineSeries::new(
-            coords
-                .iter()
-                .map(|&pt| {
-                    // Rotate points by -90 deg, and vertically flip ghost image points
-                    let pt = rotation.rotate_vector(pt);
-                    let y = if *order > 0 { -pt.y } else { pt.y };
-                    (pt.x as f32, y as f32)
-                })
-                .collect::<Vec<(f32, f32)>>(),
+            angles.clone().map(|a| {
+                let apparent_inner_edge_impact_parameter = blackhole
+                    .apparent_inner_edge_radius(inclination, Rad(a + PI / 2.0))
+                    .min(blackhole.critical_impact_parameter());
+                (
+                    (apparent_inner_edge_impact_parameter * a.cos()) as f32,
+                    (apparent_inner_edge_impact_parameter * a.sin()) as f32,
+                )
+            }),
             ShapeStyle {
-                color: BLACK.mix(if *order > 0 { 0.25 } else { 0.5 }),
+                color: BLACK.mix(1.0),
                 filled: false,
-                stroke_width: 2,
+                stroke_width,
             },
         ))?;
+
+        // Plot isoradials
+        let rotation = Basis2::from_angle(Deg(-90.0));
+        for (radius, order) in radii {
+            let isoradial = IsoRadial::new(blackhole, *radius, *order);
+            let coords =
+                isoradial.calculate_coordinates(inclination, ANGLE_COUNT, &SolverConfig::default());
+            #[allow(clippy::cast_possible_truncation)]
+            chart.draw_series(LineSeries::new(
+                coords
+                    .iter()
+                    .map(|&pt| {
+                        // Rotate points by -90 deg, and vertically flip ghost image points
+                        let pt = rotation.rotate_vector(pt);
+                        let y = if *order > 0 { -pt.y } else { pt.y };
+                        (pt.x as f32, y as f32)
+                    })
+                    .collect::<Vec<(f32, f32)>>(),
+                ShapeStyle {
+                    color: BLACK.mix(if *order > 0 { 0.25 } else { 0.5 }),
+                    filled: false,
+                    stroke_width,
+                },
+            ))?;
+        }
+
+        root.present()?;
     }
 
-    root.present()?;
-    Ok(())
+    let img = RgbImage::from_raw(width, height, buffer).expect("buffer matches image size");
+    Ok(downsample(&img, supersample))
+}
+
+/// Downsample an image by an integer factor in each dimension, averaging each block of
+/// `factor` x `factor` pixels.
+fn downsample(img: &RgbImage, factor: u32) -> RgbImage {
+    if factor <= 1 {
+        return img.clone();
+    }
+    let block_size = factor * factor;
+    RgbImage::from_fn(img.width() / factor, img.height() / factor, |col, row| {
+        let mut totals = [0_u32; 3];
+        for y in row * factor..(row + 1) * factor {
+            for x in col * factor..(col + 1) * factor {
+                for (total, channel) in totals.iter_mut().zip(img.get_pixel(x, y).0) {
+                    *total += u32::from(channel);
+                }
+            }
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        image::Rgb(totals.map(|total| ((total + block_size / 2) / block_size) as u8))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{downsample, isoradials_svg, render_isoradials};
+    use crate::BlackHole;
+    use cgmath::{Deg, Rad};
+    use image::RgbImage;
+
+    /// Sum of squared differences between horizontally and vertically adjacent pixels.
+    fn high_frequency_energy(img: &RgbImage) -> f64 {
+        let luma = |x, y| f64::from(img.get_pixel(x, y).0[0]);
+        let mut energy = 0.0;
+        for y in 0..img.height() - 1 {
+            for x in 0..img.width() - 1 {
+                energy +=
+                    (luma(x + 1, y) - luma(x, y)).powi(2) + (luma(x, y + 1) - luma(x, y)).powi(2);
+            }
+        }
+        energy
+    }
+
+    #[test]
+    fn test_animated_isoradials_svg() {
+        let blackhole = BlackHole::default();
+        let inclinations = [10.0, 40.0, 70.0, 85.0].map(|i| Rad::from(Deg(i)));
+        let radii = [(10.0, 0), (30.0, 0), (10.0, 1)];
+        let svg = isoradials_svg(&blackhole, &inclinations, &radii).unwrap();
+
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        // One animated path for the black hole's shadow and one for each isoradial
+        let animations = svg
+            .lines()
+            .filter(|line| line.trim_start().starts_with("<animate "))
+            .collect::<Vec<&str>>();
+        assert_eq!(animations.len(), radii.len() + 1);
+        for animation in animations {
+            let attribute = |name: &str| {
+                let start = animation.find(&format!("{name}=\"")).unwrap() + name.len() + 2;
+                let end = start + animation[start..].find('"').unwrap();
+                animation[start..end].to_string()
+            };
+            assert_eq!(attribute("values").split(';').count(), inclinations.len());
+            assert_eq!(attribute("keyTimes").split(';').count(), inclinations.len());
+        }
+
+        assert!(isoradials_svg(&blackhole, &[], &radii).is_err());
+    }
+
+    #[test]
+    fn test_supersampled_isoradials_are_smoother() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let radii = [(10.0, 0), (30.0, 0), (10.0, 1)];
+        let aliased = render_isoradials(&blackhole, inclination, &radii, (128, 128), 1).unwrap();
+        let antialiased =
+            render_isoradials(&blackhole, inclination, &radii, (128, 128), 2).unwrap();
+        assert_eq!(antialiased.dimensions(), (128, 128));
+        assert!(high_frequency_energy(&antialiased) < high_frequency_energy(&aliased));
+
+        let img = RgbImage::from_fn(4, 2, |x, _| {
+            image::Rgb([if x % 2 == 0 { 0 } else { 255 }; 3])
+        });
+        assert_eq!(downsample(&img, 2).into_raw(), vec![128; 2 * 3]);
+    }
 }
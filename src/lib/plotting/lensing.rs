@@ -0,0 +1,317 @@
+use super::{image_units_per_pixel, render_into, FluxImageOptions, Luma16Image};
+use crate::{
+    equations::{
+        calc_cos_gamma, calc_doppler_factor, calc_gravitational_redshift, calc_observed_flux,
+        ellipse,
+    },
+    plotting::{DiskOpacity, FluxInterpolator},
+    BlackHole, Sample,
+};
+use cgmath::{Rad, Vector2};
+use image::Luma;
+use std::f64::consts::PI;
+
+pub type Luma32FImage = image::ImageBuffer<Luma<f32>, Vec<f32>>;
+
+/// Generate an image of the ratio between the observed flux with and without gravitational
+/// lensing, to show where lensing matters most.
+///
+/// The lensed flux is interpolated from samples of the direct and ghost images, as for
+/// [`generate_flux_image`](super::generate_flux_image). The unlensed flux is that of the Newtonian
+/// projection of the disk, where each point appears at the impact parameter given by an ellipse,
+/// with the same redshift. Pixels with lensed flux but no unlensed flux, such as the photon ring,
+/// are infinite, and pixels with neither have a ratio of one.
+pub fn generate_lensing_ratio_image<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+) -> Result<Luma32FImage, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let mut direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let mut ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    let interpolator = FluxInterpolator::new(
+        blackhole,
+        inclination,
+        &mut direct_samples,
+        &mut ghost_samples,
+    )?;
+
+    let units_per_pixel = image_units_per_pixel(blackhole, inclination, image_width);
+    let points = (0..image_height)
+        .flat_map(|row| {
+            (0..image_width).map(move |col| {
+                Vector2::new(
+                    (f64::from(col) - f64::from(image_width / 2)) * units_per_pixel,
+                    -(f64::from(row) - f64::from(image_height / 2)) * units_per_pixel,
+                )
+            })
+        })
+        .collect::<Vec<Vector2<f64>>>();
+    let lensed_fluxes = interpolator.observed_flux_at_points(&points, DiskOpacity::Opaque);
+
+    let ratios = points
+        .iter()
+        .zip(lensed_fluxes)
+        .map(|(&point, lensed_flux)| {
+            let lensed_flux = lensed_flux.unwrap_or(0.0);
+            let unlensed_flux = unlensed_flux_at(blackhole, inclination, point);
+            #[allow(clippy::cast_possible_truncation)]
+            if unlensed_flux > 0.0 {
+                (lensed_flux / unlensed_flux) as f32
+            } else if lensed_flux > 0.0 {
+                f32::INFINITY
+            } else {
+                1.0
+            }
+        })
+        .collect();
+    Ok(Luma32FImage::from_raw(image_width, image_height, ratios).expect("one ratio per pixel"))
+}
+
+/// Generate a series of `steps` images of the observed flux morphing from the Newtonian projection
+/// of the disk to its full lensed appearance.
+///
+/// Each direct image sample's impact parameter is blended linearly from the ellipse
+/// `b = r sin(gamma)` to its lensed value, with its redshift and flux recalculated to match, and
+/// the apparent edges of the disk are blended in the same way. The ghost image and the black hole's
+/// shadow fade in as the blend goes from zero to one. Every image is rendered at the scale, and
+/// normalized to the maximum flux, of the fully lensed render, so the last image matches
+/// [`generate_flux_image_from_samples`](super::generate_flux_image_from_samples).
+pub fn generate_lensing_morph<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+    steps: usize,
+) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    lensing_morph_from_samples(
+        blackhole,
+        inclination,
+        &direct_samples,
+        &ghost_samples,
+        image_width,
+        image_height,
+        steps,
+    )
+}
+
+/// Generate the images of [`generate_lensing_morph`] from the supplied samples.
+fn lensing_morph_from_samples(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    direct_samples: &[Sample],
+    ghost_samples: &[Sample],
+    image_width: u32,
+    image_height: u32,
+    steps: usize,
+) -> Result<Vec<Luma16Image>, Box<dyn std::error::Error>> {
+    let (mut lensed_direct_samples, mut lensed_ghost_samples) =
+        (direct_samples.to_vec(), ghost_samples.to_vec());
+    let lensed = FluxInterpolator::new(
+        blackhole,
+        inclination,
+        &mut lensed_direct_samples,
+        &mut lensed_ghost_samples,
+    )?;
+
+    (0..steps)
+        .map(|step| {
+            #[allow(clippy::cast_precision_loss)]
+            let lensing = if steps > 1 {
+                step as f64 / (steps - 1) as f64
+            } else {
+                1.0
+            };
+            let mut blended_samples = direct_samples
+                .iter()
+                .map(|sample| {
+                    let impact_parameter = (1.0 - lensing)
+                        * ellipse(sample.radius, sample.alpha, inclination)
+                        + lensing * sample.impact_parameter;
+                    let gravitational_redshift = calc_gravitational_redshift(
+                        sample.radius,
+                        blackhole.mass,
+                        blackhole.spin(),
+                    );
+                    let doppler_factor = calc_doppler_factor(
+                        sample.radius,
+                        sample.alpha,
+                        inclination,
+                        blackhole.mass,
+                        blackhole.spin(),
+                        impact_parameter,
+                    );
+                    Sample {
+                        impact_parameter,
+                        redshift_factor: doppler_factor * gravitational_redshift,
+                        observed_flux: calc_observed_flux(
+                            sample.radius,
+                            blackhole.accretion_rate,
+                            blackhole.mass,
+                            blackhole.spin(),
+                            gravitational_redshift,
+                            doppler_factor,
+                        ),
+                        ..sample.clone()
+                    }
+                })
+                .collect::<Vec<Sample>>();
+            let mut frame_ghost_samples = ghost_samples.to_vec();
+            let interpolator = FluxInterpolator::new(
+                blackhole,
+                inclination,
+                &mut blended_samples,
+                &mut frame_ghost_samples,
+            )?
+            .with_lensing(lensing, &lensed);
+            let mut img = Luma16Image::new(image_width, image_height);
+            render_into(
+                &interpolator,
+                &mut img,
+                image_width,
+                image_height,
+                &FluxImageOptions::default(),
+            )?;
+            Ok(img)
+        })
+        .collect()
+}
+
+/// The observed flux at a point in the observer's frame, in the same orientation as the flux
+/// images, for the Newtonian projection of the disk.
+fn unlensed_flux_at(blackhole: &BlackHole, inclination: Rad<f64>, point: Vector2<f64>) -> f64 {
+    let impact_parameter = (point.x.powi(2) + point.y.powi(2)).sqrt();
+    let alpha = Rad(point.y.atan2(point.x) + PI / 2.0);
+    // Invert the ellipse `b = r sin(gamma)`
+    let sin_gamma = (1.0 - calc_cos_gamma(alpha, inclination).powi(2)).sqrt();
+    let radius = impact_parameter / sin_gamma;
+    if !(blackhole.disk_inner_edge()..=blackhole.disk_outer_edge()).contains(&radius) {
+        return 0.0;
+    }
+    calc_observed_flux(
+        radius,
+        blackhole.accretion_rate,
+        blackhole.mass,
+        blackhole.spin(),
+        calc_gravitational_redshift(radius, blackhole.mass, blackhole.spin()),
+        calc_doppler_factor(
+            radius,
+            alpha,
+            inclination,
+            blackhole.mass,
+            blackhole.spin(),
+            impact_parameter,
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_lensing_ratio_image, lensing_morph_from_samples, unlensed_flux_at};
+    use crate::{
+        plotting::{generate_flux_image_from_samples, image_units_per_pixel, FluxImageOptions},
+        BlackHole,
+    };
+    use cgmath::{Deg, Rad, Vector2};
+
+    #[test]
+    fn test_lensing_morph() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(60.0));
+        let direct_samples = blackhole.sample_flux_at_points_seeded(inclination, 3000, 0, 0);
+        let ghost_samples = blackhole.sample_flux_at_points_seeded(inclination, 3000, 1, 1);
+        let (width, height) = (96, 48);
+        let frames = lensing_morph_from_samples(
+            &blackhole,
+            inclination,
+            &direct_samples,
+            &ghost_samples,
+            width,
+            height,
+            4,
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 4);
+
+        let lensed = generate_flux_image_from_samples(
+            &blackhole,
+            inclination,
+            &mut direct_samples.clone(),
+            &mut ghost_samples.clone(),
+            width,
+            height,
+            &FluxImageOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(frames[3], lensed);
+
+        // The first image is the Newtonian projection of the disk, apart from interpolation error
+        // around its edges
+        let max_flux = direct_samples
+            .iter()
+            .chain(&ghost_samples)
+            .map(|sample| sample.observed_flux)
+            .fold(0.0, f64::max);
+        let units_per_pixel = image_units_per_pixel(&blackhole, inclination, width);
+        let (mut disk_pixels, mut matching_pixels) = (0, 0);
+        for (col, row, pixel) in frames[0].enumerate_pixels() {
+            let point = Vector2::new(
+                (f64::from(col) - f64::from(width / 2)) * units_per_pixel,
+                -(f64::from(row) - f64::from(height / 2)) * units_per_pixel,
+            );
+            let newtonian_luma =
+                unlensed_flux_at(&blackhole, inclination, point) / max_flux * f64::from(u16::MAX);
+            if newtonian_luma > 0.0 {
+                disk_pixels += 1;
+                if (f64::from(pixel.0[0]) - newtonian_luma).abs() < 0.1 * newtonian_luma {
+                    matching_pixels += 1;
+                }
+            }
+        }
+        assert!(
+            f64::from(matching_pixels) > 0.95 * f64::from(disk_pixels),
+            "{matching_pixels} of {disk_pixels} pixels match"
+        );
+    }
+
+    #[test]
+    fn test_lensing_ratio_image() {
+        let blackhole = BlackHole::default();
+        let (width, height) = (96, 48);
+        let img = generate_lensing_ratio_image(&blackhole, Deg(60.0), 2000, width, height).unwrap();
+        assert_eq!(img.dimensions(), (width, height));
+
+        let units_per_pixel = image_units_per_pixel(&blackhole, Deg(60.0), width);
+        let ratio_at = |x: f64| {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let col = (f64::from(width / 2) + x / units_per_pixel).round() as u32;
+            img.get_pixel(col, height / 2).0[0]
+        };
+
+        // The outer disk, along the major axis, is barely affected by lensing
+        for x in [-40.0, -35.0, 35.0, 40.0] {
+            assert!(
+                (ratio_at(x) - 1.0).abs() < 0.2,
+                "ratio {} at {x}",
+                ratio_at(x)
+            );
+        }
+        // while the photon ring, inside the Newtonian inner edge, only appears with lensing
+        let ring_ratio = img
+            .enumerate_pixels()
+            .filter(|(col, row, _)| {
+                let x = (f64::from(*col) - f64::from(width / 2)) * units_per_pixel;
+                let y = (f64::from(*row) - f64::from(height / 2)) * units_per_pixel;
+                x.hypot(y) < 1.5 * blackhole.critical_impact_parameter()
+            })
+            .map(|(_, _, ratio)| ratio.0[0])
+            .fold(0.0, f32::max);
+        assert!(ring_ratio > 10.0);
+    }
+}
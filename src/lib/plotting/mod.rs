@@ -1,8 +1,48 @@
-pub use dither::{dither, DitherAlgorithm};
-pub use flux::{generate_flux_image, generate_flux_images_inclinations, Luma16Image};
-pub use isoradial::plot_isoradials;
+pub use crate::interpolation::{DiskOpacity, FluxInterpolator, ZonePriority};
+pub use accumulator::Accumulator;
+pub use bands::{generate_two_band_image, Rgb16Image};
+pub use blur::gaussian_blur;
+pub use colorbar::generate_flux_image_with_colorbar;
+pub use colormap::{generate_flux_image_rgb, Colormap, FluxColormap};
+pub use contact_sheet::contact_sheet;
+pub use difference::generate_inclination_difference;
+pub use dither::{
+    blue_noise_with_mask, dither, dither_contact_sheet, dither_with_error_map, DitherAlgorithm,
+};
+pub use fits::save_fits;
+pub use flux::{
+    generate_flux_image, generate_flux_image_from_samples, generate_flux_images_inclinations,
+    generate_flux_images_position_angles, normalize_images, render_into, render_luminet_1979,
+    render_pyramid, render_rows_into, FluxImageOptions, Luma16Image,
+};
+pub use isoradial::{animate_isoradials_svg, plot_isoradials};
+pub use lensing::{generate_lensing_morph, generate_lensing_ratio_image, Luma32FImage};
+pub use moments::{image_moments, ImageMoments};
+pub use multiple::render_multiple;
+pub use overlay::{draw_polyline, image_units_per_pixel, overlay_isoradials, overlay_photon_ring};
+pub use photons::{flux_to_photon_counts, flux_to_photon_counts_with_noise, Luma32Image};
+pub use polar::generate_flux_image_polar;
+pub use starfield::{composite_over_background, generate_starfield};
+pub use temperature::generate_color_temperature_image;
+pub(crate) use temperature::{planckian_rgb, PEAK_TEMPERATURE_KELVIN};
 
+mod accumulator;
+mod bands;
+mod blur;
+mod colorbar;
+mod colormap;
+mod contact_sheet;
+mod difference;
 mod dither;
+mod fits;
 mod flux;
 mod gilbert;
 mod isoradial;
+mod lensing;
+mod moments;
+mod multiple;
+mod overlay;
+mod photons;
+mod polar;
+mod starfield;
+mod temperature;
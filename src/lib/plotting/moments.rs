@@ -0,0 +1,126 @@
+use super::Luma16Image;
+use cgmath::{Matrix2, Rad, Vector2};
+
+/// The flux-weighted moments of an image, up to second order, from which the orientation and
+/// elongation of an ellipse fitted to it follow.
+///
+/// Positions are in pixels, with `x` increasing along rows and `y` down columns, measured from the
+/// top left corner of the image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageMoments {
+    /// The zeroth moment, i.e. the total of all pixel values.
+    pub m00: f64,
+    /// The flux-weighted mean position.
+    pub centroid: Vector2<f64>,
+    /// The flux-weighted covariance of position about the centroid, with columns
+    /// `[var(x), cov(x, y)]` and `[cov(x, y), var(y)]`.
+    pub covariance: Matrix2<f64>,
+}
+
+impl ImageMoments {
+    /// The angle of the major axis of the fitted ellipse, from the `x` axis towards the `y` axis,
+    /// in `(-pi/2, pi/2]`.
+    #[must_use]
+    pub fn orientation(&self) -> Rad<f64> {
+        let (var_x, var_y, cov_xy) = (
+            self.covariance.x.x,
+            self.covariance.y.y,
+            self.covariance.x.y,
+        );
+        Rad(0.5 * (2.0 * cov_xy).atan2(var_x - var_y))
+    }
+
+    /// The ratio of the fitted ellipse's major to minor axis, which is one for a circularly
+    /// symmetric image, or infinite for a line.
+    #[must_use]
+    pub fn elongation(&self) -> f64 {
+        let (major, minor) = self.principal_variances();
+        (major / minor).sqrt()
+    }
+
+    /// The variances along the major and minor axes, i.e. the eigenvalues of the covariance.
+    fn principal_variances(&self) -> (f64, f64) {
+        let (var_x, var_y, cov_xy) = (
+            self.covariance.x.x,
+            self.covariance.y.y,
+            self.covariance.x.y,
+        );
+        let mean = (var_x + var_y) / 2.0;
+        let half_difference = ((var_x - var_y) / 2.0).hypot(cov_xy);
+        (mean + half_difference, (mean - half_difference).max(0.0))
+    }
+}
+
+/// Calculate the zeroth, first and second flux-weighted moments of an image, taking each pixel's
+/// value at its centre.
+///
+/// The centroid and covariance are NaN for an image which is entirely black.
+#[must_use]
+pub fn image_moments(img: &Luma16Image) -> ImageMoments {
+    let (mut m00, mut m10, mut m01) = (0.0, 0.0, 0.0);
+    for (col, row, pixel) in img.enumerate_pixels() {
+        let value = f64::from(pixel.0[0]);
+        m00 += value;
+        m10 += value * f64::from(col);
+        m01 += value * f64::from(row);
+    }
+    let centroid = Vector2::new(m10 / m00, m01 / m00);
+
+    // Accumulate about the centroid, rather than from the raw second moments, to avoid
+    // cancellation for small images far from the origin
+    let (mut mu20, mut mu02, mut mu11) = (0.0, 0.0, 0.0);
+    for (col, row, pixel) in img.enumerate_pixels() {
+        let value = f64::from(pixel.0[0]);
+        let (dx, dy) = (f64::from(col) - centroid.x, f64::from(row) - centroid.y);
+        mu20 += value * dx * dx;
+        mu02 += value * dy * dy;
+        mu11 += value * dx * dy;
+    }
+    let covariance = Matrix2::new(mu20, mu11, mu11, mu02) / m00;
+
+    ImageMoments {
+        m00,
+        centroid,
+        covariance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::image_moments;
+    use crate::plotting::Luma16Image;
+    use cgmath::{assert_relative_eq, Angle, Deg, Rad};
+    use image::Luma;
+
+    #[test]
+    fn test_elongated_gaussian() {
+        let (center_x, center_y) = (70.0, 50.0);
+        let (sigma_major, sigma_minor) = (12.0, 4.0);
+        let angle = Rad::from(Deg(30.0));
+        let img = Luma16Image::from_fn(140, 100, |col, row| {
+            let (dx, dy) = (f64::from(col) - center_x, f64::from(row) - center_y);
+            let along = dx * angle.cos() + dy * angle.sin();
+            let across = -dx * angle.sin() + dy * angle.cos();
+            let value = (-0.5 * ((along / sigma_major).powi(2) + (across / sigma_minor).powi(2)))
+                .exp()
+                * f64::from(u16::MAX);
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Luma([value.round() as u16])
+        });
+
+        let moments = image_moments(&img);
+        assert_relative_eq!(moments.centroid.x, center_x, epsilon = 1e-3);
+        assert_relative_eq!(moments.centroid.y, center_y, epsilon = 1e-3);
+        assert_relative_eq!(moments.orientation().0, angle.0, epsilon = 1e-3);
+        assert_relative_eq!(
+            moments.elongation(),
+            sigma_major / sigma_minor,
+            epsilon = 1e-2
+        );
+        let (major, minor) = moments.principal_variances();
+        assert_relative_eq!(major.sqrt(), sigma_major, epsilon = 1e-2);
+        assert_relative_eq!(minor.sqrt(), sigma_minor, epsilon = 1e-2);
+
+        assert!(image_moments(&Luma16Image::new(4, 4)).centroid.x.is_nan());
+    }
+}
@@ -0,0 +1,137 @@
+use super::{image_units_per_pixel, DiskOpacity, FluxInterpolator, Luma16Image};
+use crate::BlackHole;
+use cgmath::{Rad, Vector2};
+use rayon::{iter::ParallelIterator, slice::ParallelSlice};
+
+/// Number of pixels interpolated together by each parallel task.
+const PIXELS_PER_TASK: usize = 4096;
+
+/// Render an image of several black holes, each given with its offset in the image plane and the
+/// viewer's inclination to its disk.
+///
+/// Each hole is lensed independently, ignoring any interaction between them, and where their images
+/// overlap the brighter is shown. The image is scaled to fit the apparent disks of all the holes
+/// across its width, centred between them, and normalized to the maximum flux of all samples.
+pub fn render_multiple(
+    holes: &[(BlackHole, Vector2<f64>, Rad<f64>)],
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    if holes.is_empty() {
+        return Err("no black holes to render".into());
+    }
+
+    // Fit the apparent outer edge of each disk, about its offset, into the width of the image
+    let (min_x, max_x, min_y, max_y) = holes.iter().fold(
+        (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+        |(min_x, max_x, min_y, max_y), (blackhole, offset, inclination)| {
+            let half_width = image_units_per_pixel(blackhole, *inclination, image_width)
+                * f64::from(image_width)
+                / 2.0;
+            (
+                min_x.min(offset.x - half_width),
+                max_x.max(offset.x + half_width),
+                min_y.min(offset.y),
+                max_y.max(offset.y),
+            )
+        },
+    );
+    let units_per_pixel = (max_x - min_x) / f64::from(image_width);
+    let centre = Vector2::new((min_x + max_x) / 2.0, (min_y + max_y) / 2.0);
+    let points = (0..image_height)
+        .flat_map(|row| {
+            (0..image_width).map(move |col| {
+                centre
+                    + Vector2::new(
+                        (f64::from(col) - f64::from(image_width / 2)) * units_per_pixel,
+                        -(f64::from(row) - f64::from(image_height / 2)) * units_per_pixel,
+                    )
+            })
+        })
+        .collect::<Vec<Vector2<f64>>>();
+
+    let mut fluxes = vec![0.0; points.len()];
+    let mut max_flux: f64 = 0.0;
+    for (blackhole, offset, inclination) in holes {
+        let mut direct_samples = blackhole.sample_flux_at_points(*inclination, sample_count, 0);
+        let mut ghost_samples = blackhole.sample_flux_at_points(*inclination, sample_count, 1);
+        max_flux = direct_samples
+            .iter()
+            .chain(&ghost_samples)
+            .filter(|sample| sample.is_finite())
+            .map(|sample| sample.observed_flux)
+            .fold(max_flux, f64::max);
+        let interpolator = FluxInterpolator::new(
+            blackhole,
+            *inclination,
+            &mut direct_samples,
+            &mut ghost_samples,
+        )?;
+
+        let hole_points = points
+            .iter()
+            .map(|point| point - offset)
+            .collect::<Vec<Vector2<f64>>>();
+        let hole_fluxes = hole_points
+            .par_chunks(PIXELS_PER_TASK)
+            .flat_map_iter(|points| {
+                interpolator.observed_flux_at_points(points, DiskOpacity::Opaque)
+            })
+            .collect::<Vec<Option<f64>>>();
+        for (flux, hole_flux) in fluxes.iter_mut().zip(hole_fluxes) {
+            *flux = hole_flux.map_or(*flux, |hole_flux| hole_flux.max(*flux));
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let lumas = fluxes
+        .iter()
+        .map(|flux| ((flux / max_flux).clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16)
+        .collect();
+    Ok(Luma16Image::from_raw(image_width, image_height, lumas).expect("one flux per pixel"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_multiple;
+    use crate::{plotting::image_units_per_pixel, BlackHole};
+    use cgmath::{Deg, Rad, Vector2};
+
+    #[test]
+    fn test_two_offset_holes() {
+        let inclination = Rad::from(Deg(60.0));
+        let separation = 60.0;
+        let holes = [
+            (
+                BlackHole::default(),
+                Vector2::new(-separation / 2.0, 0.0),
+                inclination,
+            ),
+            (
+                BlackHole::default(),
+                Vector2::new(separation / 2.0, 0.0),
+                inclination,
+            ),
+        ];
+        let (width, height) = (160, 60);
+        let img = render_multiple(&holes, 2000, width, height).unwrap();
+        assert_eq!(img.dimensions(), (width, height));
+
+        // Each half of the image holds one hole, with its disk lit and its shadow dark
+        let half_width =
+            image_units_per_pixel(&holes[0].0, inclination, width) * f64::from(width) / 2.0;
+        let units_per_pixel = (separation + 2.0 * half_width) / f64::from(width);
+        for (start, end, offset) in [(0, width / 2, -1.0), (width / 2, width, 1.0)] {
+            let lit_pixels = (start..end)
+                .flat_map(|col| (0..height).map(move |row| (col, row)))
+                .filter(|&(col, row)| img.get_pixel(col, row).0[0] > 0)
+                .count();
+            assert!(lit_pixels > 100, "{lit_pixels} lit pixels");
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let centre =
+                (f64::from(width / 2) + offset * separation / 2.0 / units_per_pixel).round() as u32;
+            assert_eq!(img.get_pixel(centre, height / 2).0[0], 0);
+        }
+    }
+}
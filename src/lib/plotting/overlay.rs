@@ -0,0 +1,303 @@
+use super::Luma16Image;
+use crate::{BlackHole, IsoRadial, SolverConfig};
+use cgmath::{Basis2, Deg, Rad, Rotation, Rotation2, Vector2};
+
+const ANGLE_COUNT: usize = 360;
+
+/// Draw a set of isoradial curves over an image of the observed flux.
+///
+/// The curves are rotated and flipped in the same way as the flux image, so `units_per_pixel`
+/// should match the scale the image was rendered at (see [`image_units_per_pixel`]).
+pub fn overlay_isoradials<A: Into<Rad<f64>>>(
+    img: &mut Luma16Image,
+    blackhole: &BlackHole,
+    inclination: A,
+    radii: &[(f64, u32)],
+    units_per_pixel: f64,
+    color: u16,
+) {
+    let inclination: Rad<f64> = inclination.into();
+
+    let rotation = Basis2::from_angle(Deg(-90.0));
+    for (radius, order) in radii {
+        let isoradial = IsoRadial::new(blackhole, *radius, *order);
+        let points = isoradial
+            .calculate_coordinates(inclination, ANGLE_COUNT, &SolverConfig::default())
+            .iter()
+            .map(|&pt| {
+                // Rotate points by -90 deg, and vertically flip ghost image points
+                let pt = rotation.rotate_vector(pt);
+                let y = if *order > 0 { -pt.y } else { pt.y };
+                to_pixel(img, Vector2::new(pt.x, y), units_per_pixel)
+            })
+            .collect::<Vec<(i32, i32)>>();
+        draw_closed_curve(img, points, color);
+    }
+}
+
+/// Draw the photon ring, the boundary of the black hole's shadow, over an image of the observed
+/// flux, with `units_per_pixel` matching the scale the image was rendered at.
+pub fn overlay_photon_ring(
+    img: &mut Luma16Image,
+    blackhole: &BlackHole,
+    units_per_pixel: f64,
+    color: u16,
+) {
+    let points = blackhole
+        .photon_ring_coordinates(ANGLE_COUNT)
+        .iter()
+        .map(|&pt| to_pixel(img, pt, units_per_pixel))
+        .collect::<Vec<(i32, i32)>>();
+    draw_closed_curve(img, points, color);
+}
+
+/// Calculate the number of real-world units per pixel for an image of the observed flux of the
+/// given width.
+///
+/// This is based on the apparent outer edge of the accretion disk, so will closely match the
+/// scale used by `generate_flux_image` for reasonable sample counts.
+#[must_use]
+pub fn image_units_per_pixel<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    image_width: u32,
+) -> f64 {
+    let inclination: Rad<f64> = inclination.into();
+
+    // After rotating by -90 deg, the x-coordinate of each point is `b * sin(alpha)`
+    let (min_x, max_x) = blackhole
+        .apparent_outer_disk_edge()
+        .calculate_coordinates(inclination, ANGLE_COUNT, &SolverConfig::default())
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min_x, max_x), pt| {
+            (min_x.min(pt.y), max_x.max(pt.y))
+        });
+    (max_x - min_x) / f64::from(image_width)
+}
+
+/// Convert a point in the observer's frame to image pixel coordinates.
+#[allow(clippy::cast_possible_truncation)]
+fn to_pixel(img: &Luma16Image, point: Vector2<f64>, units_per_pixel: f64) -> (i32, i32) {
+    let col = (point.x / units_per_pixel).round() as i32 + (img.width() / 2) as i32;
+    let row = (-point.y / units_per_pixel).round() as i32 + (img.height() / 2) as i32;
+    (col, row)
+}
+
+/// Draw a closed curve through the given pixels, one pixel wide.
+fn draw_closed_curve(img: &mut Luma16Image, mut points: Vec<(i32, i32)>, color: u16) {
+    if let Some(&first) = points.first() {
+        points.push(first);
+    }
+    draw_polyline(img, &points, color, 1);
+}
+
+/// Draw straight line segments between consecutive pixels using Bresenham's algorithm, skipping
+/// any pixels outside the image.
+///
+/// Each segment is clipped to the image before it is drawn, so segments far outside the image
+/// cost nothing to draw.
+///
+/// Lines `width` pixels wide are drawn by stamping a square of that size centred on each pixel of
+/// the line, and a width of zero draws nothing. A single point draws just that pixel.
+pub fn draw_polyline(img: &mut Luma16Image, points: &[(i32, i32)], color: u16, width: u32) {
+    if width == 0 {
+        return;
+    }
+    let width = i64::from(width);
+    // Any pixel further than this outside the image can't stamp onto it
+    let min = (-(width / 2), -(width / 2));
+    let max = (
+        i64::from(img.width()) - 1 + (width - 1) / 2,
+        i64::from(img.height()) - 1 + (width - 1) / 2,
+    );
+    let mut stamp = |(x, y): (i64, i64)| {
+        for dy in -(width - 1) / 2..=width / 2 {
+            for dx in -(width - 1) / 2..=width / 2 {
+                if let (Ok(col), Ok(row)) = (u32::try_from(x + dx), u32::try_from(y + dy)) {
+                    if col < img.width() && row < img.height() {
+                        img.put_pixel(col, row, image::Luma([color]));
+                    }
+                }
+            }
+        }
+    };
+    if let [point] = points {
+        stamp((i64::from(point.0), i64::from(point.1)));
+    }
+    for segment in points.windows(2) {
+        let start = (i64::from(segment[0].0), i64::from(segment[0].1));
+        let end = (i64::from(segment[1].0), i64::from(segment[1].1));
+        let Some(((mut x, mut y), end)) = clip_segment(start, end, min, max) else {
+            continue;
+        };
+        let dx = (end.0 - x).abs();
+        let dy = -(end.1 - y).abs();
+        let step_x = if x < end.0 { 1 } else { -1 };
+        let step_y = if y < end.1 { 1 } else { -1 };
+        let mut err = dx + dy;
+        loop {
+            stamp((x, y));
+            if (x, y) == end {
+                break;
+            }
+            let err2 = 2 * err;
+            if err2 >= dy {
+                err += dy;
+                x += step_x;
+            }
+            if err2 <= dx {
+                err += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+/// Clip the segment from `start` to `end` to the rectangle between `min` and `max` inclusive
+/// using the Liang-Barsky algorithm, returning `None` if it lies entirely outside.
+///
+/// Endpoints inside the rectangle are returned unchanged, and clipped endpoints are rounded to
+/// the nearest pixel.
+fn clip_segment(
+    start: (i64, i64),
+    end: (i64, i64),
+    min: (i64, i64),
+    max: (i64, i64),
+) -> Option<((i64, i64), (i64, i64))> {
+    let delta = ((end.0 - start.0) as f64, (end.1 - start.1) as f64);
+    let (mut t_start, mut t_end) = (0.0_f64, 1.0_f64);
+    for (p, q) in [
+        (-delta.0, (start.0 - min.0) as f64),
+        (delta.0, (max.0 - start.0) as f64),
+        (-delta.1, (start.1 - min.1) as f64),
+        (delta.1, (max.1 - start.1) as f64),
+    ] {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t_start = t_start.max(q / p);
+        } else {
+            t_end = t_end.min(q / p);
+        }
+    }
+    if t_start > t_end {
+        return None;
+    }
+    let point_at = |t: f64| {
+        (
+            (start.0 as f64 + t * delta.0).round() as i64,
+            (start.1 as f64 + t * delta.1).round() as i64,
+        )
+    };
+    let clipped_start = if t_start > 0.0 {
+        point_at(t_start)
+    } else {
+        start
+    };
+    let clipped_end = if t_end < 1.0 { point_at(t_end) } else { end };
+    Some((clipped_start, clipped_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_segment, draw_polyline, overlay_isoradials, overlay_photon_ring, to_pixel};
+    use crate::{plotting::Luma16Image, BlackHole, IsoRadial, SolverConfig};
+    use cgmath::{Basis2, Deg, Rad, Rotation, Rotation2};
+
+    #[test]
+    fn test_overlay_isoradials() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(30.0));
+        let mut img = Luma16Image::new(128, 128);
+        overlay_isoradials(&mut img, &blackhole, inclination, &[(20.0, 0)], 0.5, 1000);
+
+        let rotation = Basis2::from_angle(Deg(-90.0));
+        let coords = IsoRadial::new(&blackhole, 20.0, 0).calculate_coordinates(
+            inclination,
+            12,
+            &SolverConfig::default(),
+        );
+        for pt in coords {
+            let (col, row) = to_pixel(&img, rotation.rotate_vector(pt), 0.5);
+            assert_eq!(img.get_pixel(col as u32, row as u32).0[0], 1000);
+        }
+        assert_eq!(img.get_pixel(64, 64).0[0], 0);
+    }
+
+    #[test]
+    fn test_overlay_photon_ring() {
+        let blackhole = BlackHole::default();
+        let mut img = Luma16Image::new(128, 128);
+        overlay_photon_ring(&mut img, &blackhole, 0.25, 1000);
+
+        for pt in blackhole.photon_ring_coordinates(8) {
+            let (col, row) = to_pixel(&img, pt, 0.25);
+            assert_eq!(img.get_pixel(col as u32, row as u32).0[0], 1000);
+        }
+        assert_eq!(img.get_pixel(64, 64).0[0], 0);
+    }
+
+    #[test]
+    fn test_draw_polyline() {
+        let mut img = Luma16Image::new(8, 8);
+        draw_polyline(&mut img, &[(0, 0), (5, 5), (5, 9)], 1000, 1);
+        let set_pixels = img
+            .enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel.0[0] == 1000)
+            .map(|(col, row, _)| (col, row))
+            .collect::<Vec<(u32, u32)>>();
+        let mut expected = (0..=5).map(|i| (i, i)).collect::<Vec<(u32, u32)>>();
+        expected.extend([(5, 6), (5, 7)]);
+        expected.sort_by_key(|&(col, row)| (row, col));
+        assert_eq!(set_pixels, expected);
+
+        // Wider lines cover the pixels either side of the line
+        let mut img = Luma16Image::new(8, 8);
+        draw_polyline(&mut img, &[(1, 1), (6, 6)], 1000, 3);
+        for i in 1..=6 {
+            assert_eq!(img.get_pixel(i, i).0[0], 1000);
+            assert_eq!(img.get_pixel(i - 1, i).0[0], 1000);
+            assert_eq!(img.get_pixel(i + 1, i).0[0], 1000);
+        }
+        assert_eq!(img.get_pixel(0, 7).0[0], 0);
+    }
+    #[test]
+    fn test_draw_polyline_clips_to_image() {
+        // Segments far outside the image draw nothing, and don't step through every pixel
+        let mut img = Luma16Image::new(8, 8);
+        let start = std::time::Instant::now();
+        draw_polyline(
+            &mut img,
+            &[
+                (i32::MIN, i32::MIN),
+                (i32::MAX, i32::MIN),
+                (-1_000_000, i32::MAX),
+            ],
+            1000,
+            3,
+        );
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert!(img.pixels().all(|pixel| pixel.0[0] == 0));
+
+        // Segments crossing the image still draw the part inside it
+        let mut img = Luma16Image::new(8, 8);
+        draw_polyline(&mut img, &[(-1_000_000, 4), (1_000_000, 4)], 1000, 1);
+        for col in 0..8 {
+            assert_eq!(img.get_pixel(col, 4).0[0], 1000);
+            assert_eq!(img.get_pixel(col, 3).0[0], 0);
+        }
+        let mut img = Luma16Image::new(8, 8);
+        draw_polyline(&mut img, &[(-100, -100), (100, 100)], 1000, 1);
+        for i in 0..8 {
+            assert_eq!(img.get_pixel(i, i).0[0], 1000);
+        }
+
+        assert_eq!(clip_segment((-10, 0), (-5, 0), (0, 0), (7, 7)), None);
+        assert_eq!(
+            clip_segment((1, 2), (3, 4), (0, 0), (7, 7)),
+            Some(((1, 2), (3, 4)))
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use super::Luma16Image;
+use image::Luma;
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Poisson};
+
+pub type Luma32Image = image::ImageBuffer<Luma<u32>, Vec<u32>>;
+
+/// Convert an image of the observed flux to the expected number of photons collected in each
+/// pixel over the given exposure time.
+///
+/// Pixel values are treated as flux in units of the image's full scale, so a white pixel collects
+/// `exposure * pixel_solid_angle` photons.
+#[must_use]
+pub fn flux_to_photon_counts(
+    img: &Luma16Image,
+    exposure: f64,
+    pixel_solid_angle: f64,
+) -> Luma32Image {
+    Luma32Image::from_fn(img.width(), img.height(), |col, row| {
+        let expected = expected_photon_count(img, col, row, exposure, pixel_solid_angle);
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Luma([expected.round() as u32])
+    })
+}
+
+/// Convert an image of the observed flux to a simulated number of photons collected in each
+/// pixel, as per `flux_to_photon_counts` but with Poisson shot noise drawn from a generator with
+/// the given seed.
+#[must_use]
+pub fn flux_to_photon_counts_with_noise(
+    img: &Luma16Image,
+    exposure: f64,
+    pixel_solid_angle: f64,
+    seed: u64,
+) -> Luma32Image {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Luma32Image::from_fn(img.width(), img.height(), |col, row| {
+        let expected = expected_photon_count(img, col, row, exposure, pixel_solid_angle);
+        let count = Poisson::new(expected).map_or(0.0, |poisson| poisson.sample(&mut rng));
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Luma([count as u32])
+    })
+}
+
+fn expected_photon_count(
+    img: &Luma16Image,
+    col: u32,
+    row: u32,
+    exposure: f64,
+    pixel_solid_angle: f64,
+) -> f64 {
+    let flux = f64::from(img.get_pixel(col, row).0[0]) / f64::from(u16::MAX);
+    flux * exposure * pixel_solid_angle
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flux_to_photon_counts, flux_to_photon_counts_with_noise, Luma32Image};
+    use crate::plotting::Luma16Image;
+
+    #[test]
+    fn test_photon_counts_scale_with_exposure() {
+        let img = Luma16Image::from_fn(16, 16, |col, row| {
+            image::Luma([(col * 16 + row) as u16 * 256])
+        });
+        let counts = flux_to_photon_counts(&img, 1000.0, 2.0);
+        let double_counts = flux_to_photon_counts(&img, 2000.0, 2.0);
+        for (count, double_count) in counts.pixels().zip(double_counts.pixels()) {
+            assert!(double_count.0[0].abs_diff(2 * count.0[0]) <= 1);
+        }
+        assert_eq!(counts.get_pixel(0, 0).0[0], 0);
+
+        // Shot noise averages out to the expected counts
+        let noisy_counts = flux_to_photon_counts_with_noise(&img, 1000.0, 2.0, 0);
+        let total = |img: &Luma32Image| img.pixels().map(|p| f64::from(p.0[0])).sum::<f64>();
+        let relative_error = (total(&noisy_counts) - total(&counts)).abs() / total(&counts);
+        assert!(relative_error < 0.01);
+        assert_ne!(noisy_counts, counts);
+    }
+}
@@ -0,0 +1,113 @@
+use super::{DiskOpacity, FluxInterpolator, Luma16Image};
+use crate::BlackHole;
+use cgmath::{Rad, Vector2};
+use std::f64::consts::PI;
+
+/// Generate an "unwrapped" image of the observed flux on a polar grid, with the impact parameter
+/// increasing down the rows and the image angle increasing along the columns, so that rings around
+/// the black hole become horizontal bands.
+///
+/// Rows run from the centre of the image out to the furthest point of the apparent outer edge of
+/// the disk, and columns cover a full turn anticlockwise from the `+x` axis of the flux images.
+/// The flux is interpolated from samples of the direct and ghost images of an opaque disk, and
+/// normalized to the maximum flux of the samples.
+pub fn generate_flux_image_polar<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    radial_bins: u32,
+    angular_bins: u32,
+) -> Result<Luma16Image, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let mut direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let mut ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    let max_flux = direct_samples
+        .iter()
+        .chain(ghost_samples.iter())
+        .map(|sample| sample.observed_flux)
+        .filter(|flux| flux.is_finite())
+        .fold(0.0, f64::max);
+    let interpolator = FluxInterpolator::new(
+        blackhole,
+        inclination,
+        &mut direct_samples,
+        &mut ghost_samples,
+    )?;
+
+    let outer_edge = blackhole.apparent_outer_disk_edge();
+    let (max_alpha, _) = outer_edge.extremal_alphas(inclination);
+    let max_impact_parameter = outer_edge.get_impact_parameter_from_alpha(inclination, max_alpha);
+
+    // Sample at the centre of each bin
+    let points = (0..radial_bins)
+        .flat_map(|row| {
+            let impact_parameter =
+                (f64::from(row) + 0.5) / f64::from(radial_bins) * max_impact_parameter;
+            (0..angular_bins).map(move |col| {
+                let angle = (f64::from(col) + 0.5) / f64::from(angular_bins) * 2.0 * PI;
+                Vector2::new(
+                    impact_parameter * angle.cos(),
+                    impact_parameter * angle.sin(),
+                )
+            })
+        })
+        .collect::<Vec<Vector2<f64>>>();
+    let fluxes = interpolator.observed_flux_at_points(&points, DiskOpacity::Opaque);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let luma = fluxes
+        .into_iter()
+        .map(|flux| {
+            let flux = flux.unwrap_or(0.0);
+            if max_flux > 0.0 {
+                ((flux / max_flux).clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16
+            } else {
+                0
+            }
+        })
+        .collect();
+    Ok(Luma16Image::from_raw(angular_bins, radial_bins, luma).expect("one luma per bin"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_flux_image_polar;
+    use crate::BlackHole;
+    use cgmath::{Deg, Rad};
+
+    #[test]
+    fn test_generate_flux_image_polar() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(10.0));
+        let (radial_bins, angular_bins) = (120, 90);
+        let img =
+            generate_flux_image_polar(&blackhole, inclination, 20_000, radial_bins, angular_bins)
+                .unwrap();
+        assert_eq!(img.dimensions(), (angular_bins, radial_bins));
+
+        // The black hole's shadow is dark at every angle
+        let outer_edge = blackhole.apparent_outer_disk_edge();
+        let max_impact_parameter = outer_edge.get_impact_parameter_from_alpha(
+            inclination,
+            outer_edge.extremal_alphas(inclination).0,
+        );
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let shadow_rows = (0.9 * blackhole.critical_impact_parameter() / max_impact_parameter
+            * f64::from(radial_bins)) as u32;
+        assert!((0..shadow_rows)
+            .all(|row| (0..angular_bins).all(|col| img.get_pixel(col, row).0[0] == 0)));
+
+        // Seen nearly face on, the bright inner ring of the disk is a band of near constant row
+        let brightest_rows = (0..angular_bins)
+            .map(|col| {
+                (0..radial_bins)
+                    .max_by_key(|&row| img.get_pixel(col, row).0[0])
+                    .unwrap()
+            })
+            .collect::<Vec<u32>>();
+        let min_row = *brightest_rows.iter().min().unwrap();
+        let max_row = *brightest_rows.iter().max().unwrap();
+        assert!(min_row > shadow_rows);
+        assert!(max_row - min_row < radial_bins / 10);
+    }
+}
@@ -0,0 +1,147 @@
+use super::Luma16Image;
+use image::{Rgb, RgbImage};
+use rand::{distributions::Uniform, rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Pareto, Poisson};
+
+/// Number of standard deviations each star's point spread function extends to.
+const PSF_RADIUS_SIGMAS: f64 = 3.0;
+
+/// Range of point spread function standard deviations, in pixels.
+const PSF_SIGMA_RANGE: (f64, f64) = (0.4, 1.2);
+
+/// Shape parameter of the power law the star brightnesses are drawn from. Smaller values give
+/// relatively more bright stars.
+const BRIGHTNESS_SHAPE: f64 = 2.0;
+
+/// The peak brightness of the faintest stars, as a fraction of full scale.
+const MIN_BRIGHTNESS: f64 = 0.08;
+
+/// A single star in a procedural starfield.
+#[derive(Debug, Clone, Copy)]
+struct Star {
+    x: f64,
+    y: f64,
+    sigma: f64,
+    brightness: f64,
+    /// Multiplier for each of the red, green and blue channels, to give slightly varied colours.
+    tint: [f64; 3],
+}
+
+/// Generate a procedural starfield, with on average `density` stars per pixel.
+///
+/// Each star is drawn as a Gaussian point spread function of random width, with a brightness drawn
+/// from a power law so that most stars are faint and a few are bright.
+#[must_use]
+pub fn generate_starfield(width: u32, height: u32, density: f64, seed: u64) -> RgbImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let stars = generate_stars(width, height, density, &mut rng);
+
+    let mut channels = vec![[0.0; 3]; width as usize * height as usize];
+    for star in &stars {
+        let radius = PSF_RADIUS_SIGMAS * star.sigma;
+        #[allow(clippy::cast_possible_truncation)]
+        let (min_col, max_col, min_row, max_row) = (
+            (star.x - radius).floor().max(0.0) as u32,
+            (star.x + radius).ceil().min(f64::from(width) - 1.0) as u32,
+            (star.y - radius).floor().max(0.0) as u32,
+            (star.y + radius).ceil().min(f64::from(height) - 1.0) as u32,
+        );
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let distance_squared =
+                    (f64::from(col) - star.x).powi(2) + (f64::from(row) - star.y).powi(2);
+                let value =
+                    star.brightness * (-distance_squared / (2.0 * star.sigma.powi(2))).exp();
+                let pixel = &mut channels[row as usize * width as usize + col as usize];
+                for (channel, tint) in pixel.iter_mut().zip(star.tint) {
+                    *channel += value * tint;
+                }
+            }
+        }
+    }
+
+    RgbImage::from_fn(width, height, |col, row| {
+        let pixel = channels[row as usize * width as usize + col as usize];
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Rgb(pixel.map(|channel| (channel.min(1.0) * f64::from(u8::MAX)).round() as u8))
+    })
+}
+
+/// Composite an image of the observed flux over a background image, such as one generated by
+/// [`generate_starfield`].
+///
+/// The disk is treated as emitting light on top of the background, so the flux is added to each
+/// channel, saturating at full scale.
+///
+/// # Panics
+///
+/// Panics if the images are not the same size.
+#[must_use]
+pub fn composite_over_background(flux: &Luma16Image, background: &RgbImage) -> RgbImage {
+    assert_eq!(
+        flux.dimensions(),
+        background.dimensions(),
+        "flux image and background must be the same size"
+    );
+    RgbImage::from_fn(flux.width(), flux.height(), |col, row| {
+        #[allow(clippy::cast_possible_truncation)]
+        let luma = (flux.get_pixel(col, row).0[0] >> 8) as u8;
+        Rgb(background
+            .get_pixel(col, row)
+            .0
+            .map(|channel| channel.saturating_add(luma)))
+    })
+}
+
+/// Randomly place stars over an image of the given size, with on average `density` stars per
+/// pixel.
+fn generate_stars(width: u32, height: u32, density: f64, rng: &mut StdRng) -> Vec<Star> {
+    let expected_count = density * f64::from(width) * f64::from(height);
+    let count = Poisson::new(expected_count).map_or(0.0, |poisson| poisson.sample(rng));
+    let x_dist = Uniform::new(0.0, f64::from(width));
+    let y_dist = Uniform::new(0.0, f64::from(height));
+    let sigma_dist = Uniform::new(PSF_SIGMA_RANGE.0, PSF_SIGMA_RANGE.1);
+    let brightness_dist = Pareto::new(MIN_BRIGHTNESS, BRIGHTNESS_SHAPE).expect("valid parameters");
+    let tint_dist = Uniform::new(0.85, 1.0);
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    (0..count as usize)
+        .map(|_| Star {
+            x: rng.sample(x_dist),
+            y: rng.sample(y_dist),
+            sigma: rng.sample(sigma_dist),
+            brightness: rng.sample(brightness_dist),
+            tint: [
+                rng.sample(tint_dist),
+                rng.sample(tint_dist),
+                rng.sample(tint_dist),
+            ],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{composite_over_background, generate_starfield, generate_stars};
+    use crate::plotting::Luma16Image;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_starfield() {
+        let (width, height, density) = (200, 100, 0.01);
+        let expected = density * f64::from(width * height);
+        let stars = generate_stars(width, height, density, &mut StdRng::seed_from_u64(0));
+        assert!((stars.len() as f64 - expected).abs() < 4.0 * expected.sqrt());
+
+        let starfield = generate_starfield(width, height, density, 0);
+        assert_eq!(starfield, generate_starfield(width, height, density, 0));
+        let lit_pixels = starfield.pixels().filter(|p| p.0 != [0, 0, 0]).count();
+        assert!(lit_pixels >= stars.len());
+
+        let mut flux = Luma16Image::new(width, height);
+        flux.put_pixel(0, 0, image::Luma([u16::MAX]));
+        let composite = composite_over_background(&flux, &starfield);
+        assert_eq!(composite.get_pixel(0, 0).0, [255, 255, 255]);
+        assert_eq!(composite.get_pixel(100, 50), starfield.get_pixel(100, 50));
+    }
+}
@@ -0,0 +1,146 @@
+use super::{generate_flux_image_from_samples, FluxImageOptions, Rgb16Image};
+use crate::{BlackHole, Sample};
+use cgmath::Rad;
+use image::Rgb;
+
+/// The black body temperature, in Kelvin, that the disk's peak temperature is displayed as.
+///
+/// Real accretion disks are far hotter than any displayable color, so this is chosen to spread
+/// the disk's temperatures across the visible part of the Planckian locus.
+pub(crate) const PEAK_TEMPERATURE_KELVIN: f64 = 10_000.0;
+/// The range of temperatures, in Kelvin, over which the Planckian locus approximation is valid.
+const PLANCKIAN_LOCUS_RANGE_KELVIN: std::ops::RangeInclusive<f64> = 1667.0..=25_000.0;
+/// The maximum observed temperature, in units of the disk's peak temperature, that can be
+/// represented. Blueshift raises the observed temperature above the peak on the approaching side.
+const MAX_OBSERVED_TEMPERATURE: f64 = 4.0;
+
+/// Generate an image of the accretion disk colored by its observed (redshifted) color
+/// temperature, and with brightness given by the observed flux.
+///
+/// The observed temperature is interpolated from the samples in the same way as the flux, and
+/// mapped to a color along the Planckian locus, with the disk's peak temperature shown as
+/// 10,000 K. Color channels are linear, like the values of the flux images.
+pub fn generate_color_temperature_image<A: Into<Rad<f64>>>(
+    blackhole: &BlackHole,
+    inclination: A,
+    sample_count: usize,
+    image_width: u32,
+    image_height: u32,
+) -> Result<Rgb16Image, Box<dyn std::error::Error>> {
+    let inclination: Rad<f64> = inclination.into();
+    let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+
+    let temperature_samples = |samples: &[Sample]| {
+        samples
+            .iter()
+            .map(|sample| Sample {
+                observed_flux: blackhole.observed_color_temperature(sample),
+                ..sample.clone()
+            })
+            .collect::<Vec<Sample>>()
+    };
+    let temperature = generate_flux_image_from_samples(
+        blackhole,
+        inclination,
+        &mut temperature_samples(&direct_samples),
+        &mut temperature_samples(&ghost_samples),
+        image_width,
+        image_height,
+        &FluxImageOptions {
+            flux_range: Some(0.0..=MAX_OBSERVED_TEMPERATURE),
+            ..Default::default()
+        },
+    )?;
+    let flux = generate_flux_image_from_samples(
+        blackhole,
+        inclination,
+        &mut direct_samples.clone(),
+        &mut ghost_samples.clone(),
+        image_width,
+        image_height,
+        &FluxImageOptions::default(),
+    )?;
+
+    Ok(Rgb16Image::from_fn(
+        image_width,
+        image_height,
+        |col, row| {
+            let temperature = f64::from(temperature.get_pixel(col, row).0[0]) / f64::from(u16::MAX)
+                * MAX_OBSERVED_TEMPERATURE;
+            let brightness = f64::from(flux.get_pixel(col, row).0[0]);
+            let color = planckian_rgb(temperature * PEAK_TEMPERATURE_KELVIN);
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            Rgb(color.map(|channel| (channel * brightness).round() as u16))
+        },
+    ))
+}
+
+/// The linear sRGB color of a black body at the given temperature in Kelvin, scaled so that its
+/// brightest channel is one.
+///
+/// The chromaticity is from the cubic spline approximation of the Planckian locus by Kim et al.
+/// (2002), and temperatures outside its range are clamped.
+pub(crate) fn planckian_rgb(temperature: f64) -> [f64; 3] {
+    let t = temperature.clamp(
+        *PLANCKIAN_LOCUS_RANGE_KELVIN.start(),
+        *PLANCKIAN_LOCUS_RANGE_KELVIN.end(),
+    );
+    let (t1, t2, t3) = (1e3 / t, 1e6 / t.powi(2), 1e9 / t.powi(3));
+    let x = if t <= 4000.0 {
+        -0.266_123_9 * t3 - 0.234_358_9 * t2 + 0.877_695_6 * t1 + 0.179_910
+    } else {
+        -3.025_846_9 * t3 + 2.107_037_9 * t2 + 0.222_634_7 * t1 + 0.240_390
+    };
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x.powi(3) - 1.348_110_20 * x.powi(2) + 2.185_558_32 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x.powi(3) - 1.374_185_93 * x.powi(2) + 2.091_370_15 * x - 0.167_488_67
+    } else {
+        3.081_758_0 * x.powi(3) - 5.873_386_70 * x.powi(2) + 3.751_129_97 * x - 0.370_014_83
+    };
+
+    // Convert from xyY chromaticity, with unit luminance, to linear sRGB
+    let (cx, cy, cz) = (x / y, 1.0, (1.0 - x - y) / y);
+    let rgb = [
+        3.240_6 * cx - 1.537_2 * cy - 0.498_6 * cz,
+        -0.968_9 * cx + 1.875_8 * cy + 0.041_5 * cz,
+        0.055_7 * cx - 0.204_0 * cy + 1.057_0 * cz,
+    ]
+    .map(|channel: f64| channel.max(0.0));
+    let max_channel = rgb.iter().copied().fold(0.0, f64::max);
+    rgb.map(|channel| channel / max_channel)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_color_temperature_image, planckian_rgb};
+    use crate::{plotting::image_units_per_pixel, BlackHole};
+    use cgmath::Deg;
+
+    #[test]
+    fn test_inner_disk_is_hotter() {
+        // Cool black bodies are red and hot ones blue
+        let [r, _, b] = planckian_rgb(2000.0);
+        assert!(r > b);
+        let [r, _, b] = planckian_rgb(20_000.0);
+        assert!(b > r);
+
+        let blackhole = BlackHole::default();
+        let inclination = Deg(10.0);
+        let (width, height) = (64, 64);
+        let img =
+            generate_color_temperature_image(&blackhole, inclination, 2000, width, height).unwrap();
+
+        // Compare the blue to red ratio near the inner and outer edges of the disk, along the
+        // horizontal axis through the black hole
+        let units_per_pixel = image_units_per_pixel(&blackhole, inclination, width);
+        let blueness = |radius: f64| {
+            let col = width / 2 + (radius / units_per_pixel).round() as u32;
+            let pixel = img.get_pixel(col, height / 2).0;
+            assert!(pixel[0] > 0);
+            f64::from(pixel[2]) / f64::from(pixel[0])
+        };
+        assert!(blueness(10.0) > 2.0 * blueness(40.0));
+    }
+}
@@ -1,25 +1,88 @@
-use crate::BlackHole;
+use crate::{
+    plotting::{planckian_rgb, PEAK_TEMPERATURE_KELVIN},
+    BlackHole, ImpactParameterSource, IsoRadial,
+};
 use cgmath::{Angle, Deg, Rad, Vector2};
-use std::io::Write;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use spade::{DelaunayTriangulation, Point2, Triangulation};
+use std::io::{BufRead, Write};
+
+/// The header of the CSV files that samples are saved to, naming their columns.
+const SAMPLES_CSV_HEADER: &str = "x,y,r,b,alpha,order,flux,redshift,temperature,source,weight";
+/// The columns of the header of CSV files saved by earlier versions, which every samples CSV file
+/// must have.
+const LEGACY_SAMPLES_CSV_HEADER: &str = "x,y,r,b,alpha,order,flux";
 
 /// A sample of the observed flux from a black hole's accretion disk.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Sample {
     /// The radius of the emitting photon's position in the black hole's frame.
     pub radius: f64,
     /// The angle of the emitting photon's position in the black hole and observer's frames.
+    #[cfg_attr(feature = "serde", serde(with = "radians"))]
     pub alpha: Rad<f64>,
     /// The radial location of the sample on the observer's photographic plate.
     pub impact_parameter: f64,
+    /// How the impact parameter was calculated, so that approximate samples from the ellipse
+    /// fallback can be excluded.
+    pub impact_parameter_source: ImpactParameterSource,
     /// The image order of the sample (0 = direct, 1+ = ghost).
     pub order: u32,
     /// The redshift factor `1 + z` of the sample.
     pub redshift_factor: f64,
     /// The observed flux `F_O` of the sample.
     pub observed_flux: f64,
+    /// The local black body temperature of the disk at the sample, in units of the disk's peak
+    /// temperature, from its intrinsic flux (`F_s ∝ T^4`).
+    pub temperature: f64,
+    /// The weight of the sample when interpolating flux, e.g. to correct for non-uniform sampling
+    /// of the accretion disk. Uniformly sampled points have a weight of 1.
+    pub weight: f64,
+}
+
+/// (De)serialize an angle as a plain number of radians.
+#[cfg(feature = "serde")]
+mod radians {
+    use cgmath::Rad;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(angle: &Rad<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(angle.0)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Rad<f64>, D::Error> {
+        f64::deserialize(deserializer).map(Rad)
+    }
 }
 
 impl Sample {
+    /// Whether all of this sample's values are finite, i.e. it can be triangulated and
+    /// interpolated.
+    #[must_use]
+    pub fn is_finite(&self) -> bool {
+        self.radius.is_finite()
+            && self.alpha.0.is_finite()
+            && self.impact_parameter.is_finite()
+            && self.redshift_factor.is_finite()
+            && self.observed_flux.is_finite()
+            && self.temperature.is_finite()
+            && self.weight.is_finite()
+    }
+
+    /// The linear sRGB color of the black body radiation observed from this sample, with its
+    /// brightest channel scaled to one.
+    ///
+    /// The observed temperature is shifted from the disk's local temperature by the redshift
+    /// factor, and the disk's peak temperature is shown as 10,000 K, as in
+    /// [`generate_color_temperature_image`](crate::plotting::generate_color_temperature_image).
+    #[must_use]
+    pub fn blackbody_rgb(&self) -> [f64; 3] {
+        planckian_rgb(self.temperature / self.redshift_factor * PEAK_TEMPERATURE_KELVIN)
+    }
+
     /// Get the position of this sample in the black hole's reference frame.
     #[must_use]
     pub fn black_hole_position(&self) -> Vector2<f64> {
@@ -54,43 +117,482 @@ impl spade::HasPosition for &Sample {
     }
 }
 
+/// Collect the observer's frame positions of a set of samples into a flat buffer of interleaved
+/// coordinates, `[x0, y0, x1, y1, ...]`, for passing across FFI boundaries without marshaling each
+/// position.
+///
+/// As with [`Sample::observer_position`], the y-coordinates of order > 0 samples are flipped.
+#[must_use]
+pub fn samples_to_xy_buffer(samples: &[Sample]) -> Vec<f64> {
+    samples
+        .iter()
+        .flat_map(|sample| {
+            let position = sample.observer_position();
+            [position.x, position.y]
+        })
+        .collect()
+}
+
+/// Calculate the area of the observer's photographic plate represented by each of a set of samples
+/// of the same image order, for integrating quantities over the image of the disk.
+///
+/// The samples are triangulated, and each triangle lying within the image of the disk (between
+/// the apparent inner and outer edges for that order) contributes a third of its area to each of
+/// its vertices. This is the barycentric dual of the Voronoi cell, which unlike the Voronoi cell
+/// is bounded at the edges of the image. The weights therefore sum to the area of the image
+/// covered by the samples, which approaches the full area of the image as more samples are taken.
+///
+/// # Errors
+///
+/// Returns an error if any of the samples has a non-finite position.
+pub fn observer_area_weights(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    samples: &[Sample],
+) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
+    // Triangulate unflipped positions, so the angle of each point is the sample's alpha
+    let mut triangulation: DelaunayTriangulation<Point2<f64>> = DelaunayTriangulation::new();
+    let vertices = samples
+        .iter()
+        .map(|sample| {
+            triangulation.insert(Point2::new(
+                sample.impact_parameter * sample.alpha.cos(),
+                sample.impact_parameter * sample.alpha.sin(),
+            ))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut vertex_areas = vec![0.0; triangulation.num_vertices()];
+    if let Some(sample) = samples.first() {
+        let inner_edge = IsoRadial::new(blackhole, blackhole.disk_inner_edge(), sample.order);
+        let outer_edge = IsoRadial::new(blackhole, blackhole.disk_outer_edge(), sample.order);
+        for face in triangulation.inner_faces() {
+            let centroid = face.center();
+            let impact_parameter = centroid.x.hypot(centroid.y);
+            let alpha = Rad(centroid.y.atan2(centroid.x));
+            let mut inner_impact_parameter =
+                inner_edge.get_impact_parameter_from_alpha(inclination, alpha);
+            if sample.order > 0 {
+                // Higher order images can never appear inside the critical impact parameter
+                inner_impact_parameter =
+                    inner_impact_parameter.max(blackhole.critical_impact_parameter());
+            }
+            if impact_parameter < inner_impact_parameter
+                || impact_parameter > outer_edge.get_impact_parameter_from_alpha(inclination, alpha)
+            {
+                continue;
+            }
+            for vertex in face.vertices() {
+                vertex_areas[vertex.fix().index()] += face.area() / 3.0;
+            }
+        }
+    }
+
+    // Coincident samples share a single vertex, so split its area between them
+    let mut vertex_sample_counts = vec![0_u32; triangulation.num_vertices()];
+    for vertex in &vertices {
+        vertex_sample_counts[vertex.index()] += 1;
+    }
+    Ok(vertices
+        .iter()
+        .map(|vertex| {
+            vertex_areas[vertex.index()] / f64::from(vertex_sample_counts[vertex.index()])
+        })
+        .collect())
+}
+
 /// Save a number of flux samples to a CSV file.
-#[allow(dead_code)]
 pub fn save_samples<P: AsRef<std::path::Path>>(
     blackhole: &BlackHole,
     inclination: Rad<f64>,
     sample_count: usize,
     path: P,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
-    let mut ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    let direct_samples = blackhole.sample_flux_at_points(inclination, sample_count, 0);
+    let ghost_samples = blackhole.sample_flux_at_points(inclination, sample_count, 1);
+    write_samples(
+        direct_samples.into_iter().chain(ghost_samples),
+        std::fs::File::create(path)?,
+    )
+}
 
-    // Rotate points by -90 deg
-    let rotation_angle = Rad::from(Deg(-90.0));
-    for sample in &mut direct_samples {
-        sample.alpha += rotation_angle;
-    }
-    for sample in &mut ghost_samples {
-        sample.alpha += rotation_angle;
+/// Save a number of flux samples for each image order up to `max_order` to separate CSV files,
+/// named `{prefix}_order{order}.csv` in the given directory.
+pub fn save_samples_by_order<P: AsRef<std::path::Path>>(
+    blackhole: &BlackHole,
+    inclination: Rad<f64>,
+    sample_count: usize,
+    max_order: u32,
+    directory: P,
+    prefix: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for order in 0..=max_order {
+        let samples = blackhole.sample_flux_at_points(inclination, sample_count, order);
+        let path = directory
+            .as_ref()
+            .join(format!("{prefix}_order{order}.csv"));
+        write_samples(samples.into_iter(), std::fs::File::create(path)?)?;
     }
+    Ok(())
+}
+
+/// Load flux samples from a CSV file written by [`save_samples`] or [`save_samples_by_order`],
+/// e.g. to render flux images from them again without resampling.
+///
+/// Files saved by earlier versions only have the columns of `LEGACY_SAMPLES_CSV_HEADER`. Their
+/// samples are loaded as solved from their periastron with a weight of 1, as sampled by
+/// [`BlackHole::sample_flux_at_points`], and with a redshift factor and temperature of 1.
+pub fn load_samples<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+    read_samples(std::io::BufReader::new(std::fs::File::open(path)?))
+}
 
-    // Write samples to file
-    let mut file = std::fs::File::create(path)?;
-    writeln!(file, "x,y,r,b,alpha,order,flux")?;
-    for sample in direct_samples.iter().chain(ghost_samples.iter()) {
+/// Write samples in CSV format, rotated by -90 deg to match the flux images.
+fn write_samples<I: Iterator<Item = Sample>, W: Write>(
+    samples: I,
+    mut writer: W,
+) -> Result<(), Box<dyn std::error::Error>> {
+    writeln!(writer, "{SAMPLES_CSV_HEADER}")?;
+    for mut sample in samples {
+        // Rotate points by -90 deg
+        sample.alpha += Rad::from(Deg(-90.0));
         let observer_point = sample.observer_position();
         writeln!(
-            file,
-            "{},{},{},{},{},{},{}",
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{}",
             observer_point.x,
             observer_point.y,
             sample.radius,
             sample.impact_parameter,
             sample.alpha.0,
             sample.order,
-            sample.observed_flux
+            sample.observed_flux,
+            sample.redshift_factor,
+            sample.temperature,
+            source_name(sample.impact_parameter_source),
+            sample.weight
         )?;
     }
-
     Ok(())
 }
+
+/// Read samples in the CSV format of [`write_samples`], undoing their rotation.
+fn read_samples<R: BufRead>(reader: R) -> Result<Vec<Sample>, Box<dyn std::error::Error>> {
+    let mut lines = reader.lines();
+    let header = lines.next().transpose()?.unwrap_or_default();
+    let columns = header.trim_end().split(',').collect::<Vec<&str>>();
+    if columns != LEGACY_SAMPLES_CSV_HEADER.split(',').collect::<Vec<&str>>()
+        && columns != SAMPLES_CSV_HEADER.split(',').collect::<Vec<&str>>()
+    {
+        return Err(format!(
+            "unexpected samples CSV header \"{header}\", expected \"{SAMPLES_CSV_HEADER}\""
+        )
+        .into());
+    }
+
+    let mut samples = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // Rows are numbered from 2, after the header
+        let row = index + 2;
+        let fields = line.trim_end().split(',').collect::<Vec<&str>>();
+        if fields.len() != columns.len() {
+            return Err(format!(
+                "malformed samples CSV row {row}: expected {} fields, found {}",
+                columns.len(),
+                fields.len()
+            )
+            .into());
+        }
+        // The field in the named column, if the file has it
+        let field = |name: &str| {
+            columns
+                .iter()
+                .position(|&column| column == name)
+                .map(|column| fields[column])
+        };
+        let invalid = |name: &str, field: &str, err: &dyn std::fmt::Display| {
+            format!("malformed samples CSV row {row}: invalid {name} \"{field}\": {err}")
+        };
+        let value = |name: &str, default: f64| {
+            field(name).map_or(Ok(default), |field| {
+                field
+                    .parse::<f64>()
+                    .map_err(|err| invalid(name, field, &err))
+            })
+        };
+        let order = field("order").unwrap_or_default();
+        let order = order
+            .parse::<u32>()
+            .map_err(|err| invalid("order", order, &err))?;
+        let impact_parameter_source = match field("source") {
+            Some(source) => parse_source(source)
+                .ok_or_else(|| invalid("source", source, &"unknown impact parameter source"))?,
+            None => ImpactParameterSource::Periastron,
+        };
+        samples.push(Sample {
+            radius: value("r", f64::NAN)?,
+            // Undo the rotation by -90 deg
+            alpha: Rad(value("alpha", f64::NAN)?) + Rad::from(Deg(90.0)),
+            impact_parameter: value("b", f64::NAN)?,
+            impact_parameter_source,
+            order,
+            redshift_factor: value("redshift", 1.0)?,
+            observed_flux: value("flux", f64::NAN)?,
+            temperature: value("temperature", 1.0)?,
+            weight: value("weight", 1.0)?,
+        });
+    }
+    Ok(samples)
+}
+
+/// The name of an impact parameter source in a samples CSV file.
+fn source_name(source: ImpactParameterSource) -> &'static str {
+    match source {
+        ImpactParameterSource::Periastron => "periastron",
+        ImpactParameterSource::EllipseFallback => "ellipse_fallback",
+    }
+}
+
+/// Parse the name of an impact parameter source in a samples CSV file.
+fn parse_source(name: &str) -> Option<ImpactParameterSource> {
+    [
+        ImpactParameterSource::Periastron,
+        ImpactParameterSource::EllipseFallback,
+    ]
+    .into_iter()
+    .find(|&source| source_name(source) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        load_samples, observer_area_weights, read_samples, samples_to_xy_buffer,
+        save_samples_by_order, write_samples, Sample, SAMPLES_CSV_HEADER,
+    };
+    use crate::{
+        plotting::{generate_flux_image_from_samples, FluxImageOptions},
+        BlackHole, ImpactParameterSource,
+    };
+    use cgmath::{Deg, Rad};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_samples_to_xy_buffer() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let samples = blackhole.sample_flux_at_points_seeded(inclination, 10, 1, 0);
+        let buffer = samples_to_xy_buffer(&samples);
+        assert_eq!(buffer.len(), 2 * samples.len());
+        for (sample, xy) in samples.iter().zip(buffer.chunks_exact(2)) {
+            let position = sample.observer_position();
+            assert_eq!(xy, [position.x, position.y]);
+        }
+        assert!(samples_to_xy_buffer(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_observer_area_weights() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(30.0));
+        let samples = blackhole.sample_flux_at_points_seeded(inclination, 4000, 0, 0);
+        let weights = observer_area_weights(&blackhole, inclination, &samples).unwrap();
+        assert_eq!(weights.len(), samples.len());
+        assert!(weights.iter().all(|&w| w >= 0.0));
+
+        // Area between the apparent inner and outer edges of the disk
+        let angle_count = 720;
+        let disk_area = (0..angle_count)
+            .map(|i| {
+                let alpha = Rad(2.0 * PI * f64::from(i) / f64::from(angle_count));
+                let inner = blackhole.apparent_inner_edge_radius(inclination, alpha);
+                let outer = blackhole.apparent_outer_edge_radius(inclination, alpha);
+                0.5 * (outer.powi(2) - inner.powi(2)) * 2.0 * PI / f64::from(angle_count)
+            })
+            .sum::<f64>();
+        // The samples don't quite reach the edges of the image, so slightly underestimate its area
+        let total_weight = weights.iter().sum::<f64>();
+        assert!(total_weight < disk_area);
+        assert!((disk_area - total_weight) / disk_area < 0.05);
+    }
+
+    #[test]
+    fn test_blackbody_rgb() {
+        let blackhole = BlackHole::default();
+        let samples = blackhole.sample_flux_at_points_seeded(Rad::from(Deg(80.0)), 200, 0, 0);
+        for sample in &samples {
+            assert!(sample.temperature > 0.0 && sample.temperature <= 1.0 + 1e-9);
+            let rgb = sample.blackbody_rgb();
+            assert!(rgb.iter().all(|&c| (0.0..=1.0).contains(&c)));
+            assert!((rgb.iter().copied().fold(0.0, f64::max) - 1.0).abs() < 1e-9);
+        }
+
+        // Blueshifted samples on the approaching side are bluer than redshifted ones
+        let bluest = samples
+            .iter()
+            .min_by(|a, b| a.redshift_factor.total_cmp(&b.redshift_factor))
+            .unwrap();
+        let reddest = samples
+            .iter()
+            .max_by(|a, b| a.redshift_factor.total_cmp(&b.redshift_factor))
+            .unwrap();
+        let blue = bluest.blackbody_rgb();
+        let red = reddest.blackbody_rgb();
+        assert!(blue[2] / blue[0] > red[2] / red[0]);
+    }
+
+    #[test]
+    fn test_save_samples_by_order() {
+        let blackhole = BlackHole::default();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let directory = temp_dir.path();
+        save_samples_by_order(&blackhole, Rad::from(Deg(80.0)), 50, 2, directory, "test").unwrap();
+
+        let mut files = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<String>>();
+        files.sort();
+        assert_eq!(
+            files,
+            ["test_order0.csv", "test_order1.csv", "test_order2.csv"]
+        );
+        for (order, file) in files.iter().enumerate() {
+            let contents = std::fs::read_to_string(directory.join(file)).unwrap();
+            let rows = contents.lines().skip(1).collect::<Vec<&str>>();
+            assert_eq!(rows.len(), 50);
+            for row in rows {
+                assert_eq!(row.split(',').nth(5).unwrap(), order.to_string());
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let samples = [0, 1].map(|order| {
+            blackhole.sample_flux_at_points_seeded(inclination, 500, order, u64::from(order))
+        });
+        let json = serde_json::to_string(&samples).unwrap();
+        let mut deserialized: [Vec<Sample>; 2] = serde_json::from_str(&json).unwrap();
+        assert!(serde_json::to_value(&samples[0][0]).unwrap()["alpha"].is_f64());
+
+        let render = |[direct_samples, ghost_samples]: &mut [Vec<Sample>; 2]| {
+            generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                direct_samples,
+                ghost_samples,
+                64,
+                48,
+                &FluxImageOptions::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(render(&mut samples.clone()), render(&mut deserialized));
+    }
+
+    #[test]
+    fn test_load_samples() {
+        let blackhole = BlackHole::default();
+        let inclination = Rad::from(Deg(80.0));
+        let mut samples = [0, 1].map(|order| {
+            blackhole.sample_flux_at_points_seeded(inclination, 500, order, u64::from(order))
+        });
+        // Samples from the ellipse fallback, and non-uniform weights, are kept
+        samples[0][0].impact_parameter_source = ImpactParameterSource::EllipseFallback;
+        for (index, sample) in samples[1].iter_mut().enumerate() {
+            sample.weight = 0.5 + index as f64 / 1000.0;
+        }
+        let directory = tempfile::tempdir().unwrap();
+        let path = directory.path().join("samples.csv");
+        write_samples(
+            samples.iter().flatten().cloned(),
+            std::fs::File::create(&path).unwrap(),
+        )
+        .unwrap();
+        let loaded = load_samples(&path).unwrap();
+
+        assert_eq!(loaded.len(), 1000);
+        for (sample, loaded) in samples.iter().flatten().zip(&loaded) {
+            assert_eq!(loaded.radius, sample.radius);
+            assert!((loaded.alpha - sample.alpha).0.abs() < 1e-12);
+            assert_eq!(loaded.impact_parameter, sample.impact_parameter);
+            assert_eq!(loaded.order, sample.order);
+            assert_eq!(loaded.redshift_factor, sample.redshift_factor);
+            assert_eq!(loaded.observed_flux, sample.observed_flux);
+            assert_eq!(loaded.temperature, sample.temperature);
+            assert_eq!(
+                loaded.impact_parameter_source,
+                sample.impact_parameter_source
+            );
+            assert_eq!(loaded.weight, sample.weight);
+        }
+
+        let render = |direct_samples: &mut [Sample], ghost_samples: &mut [Sample]| {
+            generate_flux_image_from_samples(
+                &blackhole,
+                inclination,
+                direct_samples,
+                ghost_samples,
+                64,
+                48,
+                &FluxImageOptions::default(),
+            )
+            .unwrap()
+        };
+        let [mut direct_samples, mut ghost_samples] = samples;
+        let (mut loaded_direct, mut loaded_ghost): (Vec<Sample>, Vec<Sample>) =
+            loaded.into_iter().partition(|sample| sample.order == 0);
+        let expected = render(&mut direct_samples, &mut ghost_samples);
+        let image = render(&mut loaded_direct, &mut loaded_ghost);
+        assert!(image
+            .pixels()
+            .zip(expected.pixels())
+            .all(|(a, b)| a.0[0].abs_diff(b.0[0]) <= 1));
+    }
+
+    #[test]
+    fn test_load_legacy_samples() {
+        let samples =
+            read_samples("x,y,r,b,alpha,order,flux\n1,2,10,11,0.5,1,0.25\n".as_bytes()).unwrap();
+        assert_eq!(samples.len(), 1);
+        let sample = &samples[0];
+        assert_eq!(sample.radius, 10.0);
+        assert_eq!(sample.impact_parameter, 11.0);
+        assert!((sample.alpha - (Rad(0.5) + Rad::from(Deg(90.0)))).0.abs() < 1e-12);
+        assert_eq!(sample.order, 1);
+        assert_eq!(sample.observed_flux, 0.25);
+        assert_eq!(
+            sample.impact_parameter_source,
+            ImpactParameterSource::Periastron
+        );
+        assert_eq!(sample.weight, 1.0);
+        assert!(sample.is_finite());
+    }
+
+    #[test]
+    fn test_load_samples_rejects_malformed_csv() {
+        let error = read_samples("x,y,r,b\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("header"));
+        let error = read_samples("x,y,r,b,alpha,order,flux\n1,2,3\n".as_bytes()).unwrap_err();
+        assert!(error.to_string().contains("row 2"));
+        let error =
+            read_samples("x,y,r,b,alpha,order,flux\n0,0,6,7,0,0,1\n0,0,6,7,0,0,abc\n".as_bytes())
+                .unwrap_err();
+        assert!(error.to_string().contains("row 3: invalid flux"));
+        let error =
+            read_samples(format!("{SAMPLES_CSV_HEADER}\n0,0,6,7,0,0,1,1,1,guess,1\n").as_bytes())
+                .unwrap_err();
+        assert!(error.to_string().contains("row 2: invalid source"));
+        assert!(read_samples(format!("{SAMPLES_CSV_HEADER}\n").as_bytes())
+            .unwrap()
+            .is_empty());
+    }
+}
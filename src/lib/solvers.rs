@@ -2,33 +2,142 @@
 //! from the black hole's accretion disk.
 
 use crate::equations::{
-    calc_impact_parameter_from_periastron, calc_one_over_radius_minus_one_over_radius, ellipse,
+    calc_cos_gamma, calc_impact_parameter_from_periastron,
+    calc_one_over_radius_minus_one_over_radius, calc_one_over_radius_with_derivative,
+    calc_periastron_from_impact_parameter, calc_sweep_angle, ellipse,
 };
 use cgmath::Rad;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, f64::consts::PI, ops::RangeInclusive, sync::Mutex};
 
-/// Solution tolerance to use when solving for the periastron.
+/// Default solution tolerance to use when solving for the periastron.
 const PERIASTRON_TOLERANCE: f64 = 1e-6;
-/// The maximum number of iteration of the bisection method to run.
+/// The default maximum number of solver iterations to run.
 const MAX_BISECTION_ITERS: usize = 100;
 /// The minumum periastron value to solve for, in units of black hole mass.
 const MIN_PERIASTRON: f64 = 3.001;
 /// The maximum periastron value to solve for, in units of black hole radius.
 const MAX_PERIASTRON: f64 = 3.0;
+/// The initial periastron for Newton-Raphson iterations for higher order images, whose photons
+/// pass close to the photon sphere, in units of black hole mass.
+const HIGHER_ORDER_INITIAL_PERIASTRON: f64 = 3.5;
+/// Tolerance, in units of black hole mass, to find the largest periastron of photons which sweep
+/// far enough to form a higher order image. This only needs to separate the solution from the
+/// spurious ones beyond it, so can be much coarser than the solver's tolerance.
+const SWEEP_LIMIT_TOLERANCE: f64 = 1e-3;
+/// Resolution to quantize inputs to when caching impact parameter solutions.
+const CACHE_QUANTUM: f64 = 1e-9;
+/// Default maximum number of solutions held by an [`ImpactParameterCache`].
+const DEFAULT_CACHE_CAPACITY: usize = 1 << 20;
+
+/// Parameters of the periastron solver, so that its convergence can be studied without
+/// recompiling.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(default, deny_unknown_fields)
+)]
+pub struct SolverConfig {
+    /// Solution tolerance for the periastron, in units of black hole mass.
+    pub tolerance: f64,
+    /// The maximum number of iterations to run.
+    pub max_iters: usize,
+    /// The minimum periastron value to solve for, in units of black hole mass.
+    pub min_periastron: f64,
+    /// The maximum periastron value to solve for, in units of the emission radius.
+    pub max_periastron: f64,
+    /// The method used to solve for the periastron.
+    pub method: SolverMethod,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            tolerance: PERIASTRON_TOLERANCE,
+            max_iters: MAX_BISECTION_ITERS,
+            min_periastron: MIN_PERIASTRON,
+            max_periastron: MAX_PERIASTRON,
+            method: SolverMethod::default(),
+        }
+    }
+}
+
+impl SolverConfig {
+    /// The range of periastron values searched for a solution for a photon emitted at `radius`,
+    /// [`min_periastron` * mass, `max_periastron` * radius].
+    #[must_use]
+    pub fn periastron_range(&self, radius: f64, mass: f64) -> RangeInclusive<f64> {
+        (self.min_periastron * mass)..=(self.max_periastron * radius)
+    }
+}
+
+/// The range of periastron values searched for a solution for a photon emitted at `radius` with
+/// the default solver configuration, [`MIN_PERIASTRON` * mass, `MAX_PERIASTRON` * radius].
+#[must_use]
+pub fn periastron_search_range(radius: f64, mass: f64) -> RangeInclusive<f64> {
+    SolverConfig::default().periastron_range(radius, mass)
+}
+
+/// The range of periastron values searched for a solution for a photon emitted at `radius` and
+/// `alpha` forming the image of the given order.
+///
+/// The elliptic sine in eqn 13 is periodic, so continues on past the photon escaping back out to
+/// infinity. The photon forming a higher order image sweeps `2 pi n - gamma` around the black hole,
+/// more than the `pi` of an undeflected photon, so the range is capped at the periastron of the
+/// photon which sweeps just that far, to exclude spurious solutions beyond it.
+fn image_search_range(
+    radius: f64,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    mass: f64,
+    order: u32,
+    config: &SolverConfig,
+) -> RangeInclusive<f64> {
+    let periastron_range = config.periastron_range(radius, mass);
+    let (start, mut end) = (*periastron_range.start(), *periastron_range.end());
+    if order == 0 {
+        return periastron_range;
+    }
+    let sweep = 2.0 * f64::from(order) * PI - calc_cos_gamma(alpha, inclination).acos();
+    if calc_sweep_angle(end, mass) >= sweep {
+        return periastron_range;
+    }
+    if calc_sweep_angle(start, mass) < sweep {
+        // Even the photon closest to the photon sphere doesn't sweep far enough
+        return start..=start;
+    }
+
+    // The swept angle decreases with the periastron, so bisect for where it's just too small
+    let mut low = start;
+    while end - low > SWEEP_LIMIT_TOLERANCE * mass {
+        let mid = (low + end) / 2.0;
+        if calc_sweep_angle(mid, mass) >= sweep {
+            low = mid;
+        } else {
+            end = mid;
+        }
+    }
+    start..=end
+}
 
 /// For a given black hole reference frame `radius` and angle in the observer's frame `alpha`,
 /// calculate the periastron for a photon emitted at `radius`.
 ///
 /// This is done by finding a zero of the function `1.0 / calc_one_over_radius - radius` in terms
 /// the periastron via the bisection method. Will fail and return None if no solution can be found
-/// in the range [`MIN_PERIASTRON` * mass, `MAX_PERIASTRON` * radius].
+/// in the range given by [`SolverConfig::periastron_range`], or for higher order images, the part
+/// of it where the photon sweeps far enough around the black hole.
 pub fn calc_periastron(
     radius: f64,
     inclination: Rad<f64>,
     alpha: Rad<f64>,
     mass: f64,
     order: u32,
+    config: &SolverConfig,
 ) -> Option<f64> {
-    let periastron_range = (MIN_PERIASTRON * mass)..=(MAX_PERIASTRON * radius);
+    let periastron_range = image_search_range(radius, inclination, alpha, mass, order, config);
 
     // First determine if a solution exists in the valid range
     let val_at_min_periastron = calc_one_over_radius_minus_one_over_radius(
@@ -59,9 +168,7 @@ pub fn calc_periastron(
     let mut val_at_periastron_b = val_at_max_periastron;
     debug_assert!(val_at_periastron_a.signum() != val_at_periastron_b.signum());
     let mut iter_count = 0;
-    while (periastron_b - periastron_a).abs() > PERIASTRON_TOLERANCE
-        && iter_count < MAX_BISECTION_ITERS
-    {
+    while (periastron_b - periastron_a).abs() > config.tolerance && iter_count < config.max_iters {
         let periastron_c = (periastron_a + periastron_b) / 2.0;
         let val_at_periastron_c = calc_one_over_radius_minus_one_over_radius(
             radius,
@@ -89,10 +196,162 @@ pub fn calc_periastron(
     }
 }
 
+/// For a given black hole reference frame `radius` and angle in the observer's frame `alpha`,
+/// calculate the periastron for a photon emitted at `radius`, as per [`calc_periastron`] but with
+/// Newton-Raphson iterations.
+///
+/// Each iteration takes a Newton step using the analytic derivative of `1/r` with respect to the
+/// periastron, while keeping track of the bracket around the solution. Steps which would leave the
+/// bracket are replaced by bisection, so this converges wherever [`calc_periastron`] does, and
+/// returns None in the same cases. Both search the same range, capped for higher order images as
+/// per [`image_search_range`] so that it excludes the spurious roots of the residual close to the
+/// photon sphere, so the two converge to the same solution.
+pub fn calc_periastron_newton(
+    radius: f64,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    mass: f64,
+    order: u32,
+    config: &SolverConfig,
+) -> Option<f64> {
+    let periastron_range = image_search_range(radius, inclination, alpha, mass, order, config);
+    let residual = |periastron: f64| {
+        calc_one_over_radius_minus_one_over_radius(
+            radius,
+            periastron,
+            alpha,
+            mass,
+            inclination,
+            order,
+        )
+    };
+
+    // First determine if a solution exists in the valid range
+    let val_at_min_periastron = residual(*periastron_range.start());
+    let val_at_max_periastron = residual(*periastron_range.end());
+    if val_at_min_periastron.signum() == val_at_max_periastron.signum() {
+        // No solution in the valid range
+        return None;
+    }
+
+    // Keep the bracket ordered by the sign of the residual rather than by periastron
+    let (mut periastron_negative, mut periastron_positive) = if val_at_min_periastron < 0.0 {
+        (*periastron_range.start(), *periastron_range.end())
+    } else {
+        (*periastron_range.end(), *periastron_range.start())
+    };
+    // Start the direct image from the Newtonian ellipse, which is close for all but the innermost
+    // orbits, and higher order images just outside the photon sphere
+    let newtonian_periastron =
+        calc_periastron_from_impact_parameter(ellipse(radius, alpha, inclination), mass);
+    let mut periastron = if order == 0 && periastron_range.contains(&newtonian_periastron) {
+        newtonian_periastron
+    } else {
+        (HIGHER_ORDER_INITIAL_PERIASTRON * mass)
+            .clamp(*periastron_range.start(), *periastron_range.end())
+    };
+    for _ in 0..config.max_iters {
+        let (one_over_radius, derivative) =
+            calc_one_over_radius_with_derivative(periastron, alpha, mass, inclination, order);
+        let value = 1.0 - radius * one_over_radius;
+        if value < 0.0 {
+            periastron_negative = periastron;
+        } else {
+            periastron_positive = periastron;
+        }
+        let newton_step = periastron + value / (radius * derivative);
+        let next_periastron = if newton_step.is_finite()
+            && newton_step > periastron_negative.min(periastron_positive)
+            && newton_step < periastron_negative.max(periastron_positive)
+        {
+            newton_step
+        } else {
+            (periastron_negative + periastron_positive) / 2.0
+        };
+        let step = (next_periastron - periastron).abs();
+        periastron = next_periastron;
+        if step <= config.tolerance {
+            break;
+        }
+    }
+
+    if periastron.is_nan() {
+        None
+    } else {
+        Some(periastron)
+    }
+}
+
+/// The method used to solve for a photon's periastron.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum SolverMethod {
+    /// Bisection, with [`calc_periastron`].
+    #[default]
+    Bisection,
+    /// Newton-Raphson iterations safeguarded by bisection, with [`calc_periastron_newton`]. This
+    /// typically needs a handful of iterations rather than a few dozen, each a little more
+    /// expensive.
+    Newton,
+}
+
+/// What to do when no periastron solution can be found for an emitted photon.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum FallbackMode {
+    /// Fall back to the equation for an ellipse, i.e. the Newtonian isoradial.
+    #[default]
+    Ellipse,
+    /// Don't fall back, leaving the impact parameter unsolved.
+    None,
+}
+
+/// How an impact parameter was calculated.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum ImpactParameterSource {
+    /// From a solution for the photon's periastron.
+    #[default]
+    Periastron,
+    /// From the equation for an ellipse, as no periastron solution could be found. This is only
+    /// approximate, and visibly wrong close to the inner edge of the disk.
+    EllipseFallback,
+}
+
+impl FallbackMode {
+    /// The impact parameter from a periastron solution if there is one, otherwise from this
+    /// fallback.
+    fn apply(
+        self,
+        solution: Option<f64>,
+        radius: f64,
+        inclination: Rad<f64>,
+        alpha: Rad<f64>,
+    ) -> Option<(f64, ImpactParameterSource)> {
+        match (solution, self) {
+            (Some(impact_parameter), _) => {
+                Some((impact_parameter, ImpactParameterSource::Periastron))
+            }
+            (None, FallbackMode::Ellipse) => Some((
+                ellipse(radius, alpha, inclination),
+                ImpactParameterSource::EllipseFallback,
+            )),
+            (None, FallbackMode::None) => None,
+        }
+    }
+}
+
 /// For a given black hole reference frame `radius` and angle in the observer's frame `alpha`,
 /// calculate the impact parameter for a photon emitted at `radius`.
 ///
-/// First solves for the photon's periastron using `calc_periastron`, then converts that to a value
+/// First solves for the photon's periastron with the given solver, then converts that to a value
 /// for the impact parameter. If no solution for the periastron can be found, it will fallback
 /// to using the equation for an ellipse.
 pub fn calc_impact_parameter(
@@ -101,10 +360,641 @@ pub fn calc_impact_parameter(
     alpha: Rad<f64>,
     mass: f64,
     order: u32,
+    config: &SolverConfig,
 ) -> f64 {
-    if let Some(periastron) = calc_periastron(radius, inclination, alpha, mass, order) {
-        calc_impact_parameter_from_periastron(periastron, mass)
-    } else {
-        ellipse(radius, alpha, inclination)
+    calc_impact_parameter_checked(radius, inclination, alpha, mass, order, config).0
+}
+
+/// Calculate the impact parameter as per `calc_impact_parameter`, along with whether it came from
+/// a periastron solution or fell back to the ellipse.
+pub fn calc_impact_parameter_checked(
+    radius: f64,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    mass: f64,
+    order: u32,
+    config: &SolverConfig,
+) -> (f64, ImpactParameterSource) {
+    try_calc_impact_parameter_checked(
+        radius,
+        inclination,
+        alpha,
+        mass,
+        order,
+        FallbackMode::Ellipse,
+        config,
+    )
+    .expect("ellipse fallback always has a solution")
+}
+
+/// Calculate the impact parameter as per `calc_impact_parameter`, using the given fallback when no
+/// solution for the periastron can be found. Returns None if there is no solution and no fallback.
+pub fn try_calc_impact_parameter(
+    radius: f64,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    mass: f64,
+    order: u32,
+    fallback: FallbackMode,
+    config: &SolverConfig,
+) -> Option<f64> {
+    try_calc_impact_parameter_checked(radius, inclination, alpha, mass, order, fallback, config)
+        .map(|(impact_parameter, _)| impact_parameter)
+}
+
+/// Calculate the impact parameter as per `try_calc_impact_parameter`, along with whether it came
+/// from a periastron solution or the fallback.
+pub fn try_calc_impact_parameter_checked(
+    radius: f64,
+    inclination: Rad<f64>,
+    alpha: Rad<f64>,
+    mass: f64,
+    order: u32,
+    fallback: FallbackMode,
+    config: &SolverConfig,
+) -> Option<(f64, ImpactParameterSource)> {
+    let periastron = match config.method {
+        SolverMethod::Bisection => calc_periastron(radius, inclination, alpha, mass, order, config),
+        SolverMethod::Newton => {
+            calc_periastron_newton(radius, inclination, alpha, mass, order, config)
+        }
+    };
+    fallback.apply(
+        periastron.map(|periastron| calc_impact_parameter_from_periastron(periastron, mass)),
+        radius,
+        inclination,
+        alpha,
+    )
+}
+
+/// Cache key of quantized radius, inclination, alpha and mass values, the image order, and the
+/// solver configuration the solution was found with.
+type CacheKey = (i64, i64, i64, i64, u32, SolverConfigKey);
+
+/// The bits of each parameter of a solver configuration, so that it can be hashed.
+type SolverConfigKey = (u64, usize, u64, u64, SolverMethod);
+
+impl SolverConfig {
+    /// The key of this configuration in an [`ImpactParameterCache`].
+    fn cache_key(&self) -> SolverConfigKey {
+        (
+            self.tolerance.to_bits(),
+            self.max_iters,
+            self.min_periastron.to_bits(),
+            self.max_periastron.to_bits(),
+            self.method,
+        )
+    }
+}
+
+/// A thread-safe cache of impact parameter solutions, for when the same emission points are
+/// solved for repeatedly (e.g. when rendering from a fixed grid of sample points).
+///
+/// Inputs are quantized to a resolution of `CACHE_QUANTUM`, trading memory for not having to
+/// re-run the periastron solver. Solutions are cached separately for each solver configuration.
+/// Randomly sampled points are practically never repeated, so should not be solved through a
+/// cache.
+///
+/// Once the cache holds its capacity of solutions, further inputs are solved without caching.
+pub struct ImpactParameterCache {
+    solutions: Mutex<HashMap<CacheKey, Option<f64>>>,
+    capacity: usize,
+}
+
+impl Default for ImpactParameterCache {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl ImpactParameterCache {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a cache holding at most `capacity` solutions. A capacity of zero disables caching.
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            solutions: Mutex::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    /// Calculate the impact parameter as per `calc_impact_parameter`, returning a cached
+    /// solution if one exists for the given inputs.
+    pub fn calc_impact_parameter(
+        &self,
+        radius: f64,
+        inclination: Rad<f64>,
+        alpha: Rad<f64>,
+        mass: f64,
+        order: u32,
+        config: &SolverConfig,
+    ) -> f64 {
+        self.try_calc_impact_parameter(
+            radius,
+            inclination,
+            alpha,
+            mass,
+            order,
+            FallbackMode::Ellipse,
+            config,
+        )
+        .expect("ellipse fallback always has a solution")
+    }
+
+    /// Calculate the impact parameter as per `try_calc_impact_parameter`, returning a cached
+    /// solution if one exists for the given inputs.
+    ///
+    /// Only solutions for the periastron are cached, the fallback is applied afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_calc_impact_parameter(
+        &self,
+        radius: f64,
+        inclination: Rad<f64>,
+        alpha: Rad<f64>,
+        mass: f64,
+        order: u32,
+        fallback: FallbackMode,
+        config: &SolverConfig,
+    ) -> Option<f64> {
+        self.try_calc_impact_parameter_checked(
+            radius,
+            inclination,
+            alpha,
+            mass,
+            order,
+            fallback,
+            config,
+        )
+        .map(|(impact_parameter, _)| impact_parameter)
+    }
+
+    /// Calculate the impact parameter as per `try_calc_impact_parameter_checked`, returning a
+    /// cached solution if one exists for the given inputs.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_calc_impact_parameter_checked(
+        &self,
+        radius: f64,
+        inclination: Rad<f64>,
+        alpha: Rad<f64>,
+        mass: f64,
+        order: u32,
+        fallback: FallbackMode,
+        config: &SolverConfig,
+    ) -> Option<(f64, ImpactParameterSource)> {
+        #[allow(clippy::cast_possible_truncation)]
+        let quantize = |value: f64| (value / CACHE_QUANTUM).round() as i64;
+        let key = (
+            quantize(radius),
+            quantize(inclination.0),
+            quantize(alpha.0),
+            quantize(mass),
+            order,
+            config.cache_key(),
+        );
+        let cached = self.solutions.lock().unwrap().get(&key).copied();
+        let impact_parameter = cached.unwrap_or_else(|| {
+            // Don't hold the lock while solving, so other threads can use the cache
+            let impact_parameter = try_calc_impact_parameter(
+                radius,
+                inclination,
+                alpha,
+                mass,
+                order,
+                FallbackMode::None,
+                config,
+            );
+            let mut solutions = self.solutions.lock().unwrap();
+            if solutions.len() < self.capacity {
+                solutions.insert(key, impact_parameter);
+            }
+            impact_parameter
+        });
+        fallback.apply(impact_parameter, radius, inclination, alpha)
+    }
+
+    /// The number of cached solutions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.solutions.lock().unwrap().len()
+    }
+
+    /// Whether the cache is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        calc_impact_parameter, calc_impact_parameter_checked, calc_periastron,
+        calc_periastron_newton, ellipse, periastron_search_range, try_calc_impact_parameter,
+        FallbackMode, ImpactParameterCache, ImpactParameterSource, SolverConfig, SolverMethod,
+        MAX_PERIASTRON, MIN_PERIASTRON, PERIASTRON_TOLERANCE,
+    };
+    use crate::equations::{
+        calc_cos_gamma, calc_one_over_radius, calc_one_over_radius_with_derivative,
+    };
+    use cgmath::{assert_relative_eq, Deg, Rad};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use std::f64::consts::PI;
+
+    /// Number of RK4 steps used to integrate a photon's orbit over the angle it sweeps.
+    const REFERENCE_STEPS: usize = 4000;
+    /// Number of bisection iterations used to find the reference impact parameter.
+    const REFERENCE_ITERATIONS: usize = 60;
+
+    /// Integrate the orbit equation `d^2u/dphi^2 = 3 M u^2 - u`, for `u = 1/r`, with RK4, tracing a
+    /// photon with the given impact parameter back from the observer at infinity through the given
+    /// polar angle. Returns the radius it reaches, which is infinite if the photon escapes back to
+    /// infinity first and zero if it falls into the black hole.
+    fn reference_radius(impact_parameter: f64, sweep: f64, mass: f64) -> f64 {
+        let step = sweep / REFERENCE_STEPS as f64;
+        let derivative = |(u, du): (f64, f64)| (du, 3.0 * mass * u.powi(2) - u);
+        let (mut u, mut du) = (0.0, 1.0 / impact_parameter);
+        for _ in 0..REFERENCE_STEPS {
+            let k1 = derivative((u, du));
+            let k2 = derivative((u + step / 2.0 * k1.0, du + step / 2.0 * k1.1));
+            let k3 = derivative((u + step / 2.0 * k2.0, du + step / 2.0 * k2.1));
+            let k4 = derivative((u + step * k3.0, du + step * k3.1));
+            u += step / 6.0 * (k1.0 + 2.0 * k2.0 + 2.0 * k3.0 + k4.0);
+            du += step / 6.0 * (k1.1 + 2.0 * k2.1 + 2.0 * k3.1 + k4.1);
+            if u <= 0.0 {
+                return f64::INFINITY;
+            }
+            if u >= 1.0 / (2.0 * mass) {
+                return 0.0;
+            }
+        }
+        1.0 / u
+    }
+
+    /// Find the impact parameter of a photon emitted at `radius` and `alpha` by bisecting on the
+    /// numerically integrated orbit, independently of the elliptic integral solution.
+    ///
+    /// The photon sweeps `gamma` on its way to the observer for the direct image, and
+    /// `2 pi n - gamma` for the image of order `n`. The radius reached after a fixed sweep
+    /// increases with the impact parameter, from capture just below the critical value.
+    fn reference_impact_parameter(
+        radius: f64,
+        inclination: Rad<f64>,
+        alpha: Rad<f64>,
+        mass: f64,
+        order: u32,
+    ) -> f64 {
+        let gamma = calc_cos_gamma(alpha, inclination).acos();
+        let sweep = if order == 0 {
+            gamma
+        } else {
+            2.0 * f64::from(order) * PI - gamma
+        };
+        let (mut low, mut high) = (3.0 * 3.0_f64.sqrt() * mass, 10.0 * radius);
+        for _ in 0..REFERENCE_ITERATIONS {
+            let mid = (low + high) / 2.0;
+            if reference_radius(mid, sweep, mass) < radius {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + high) / 2.0
+    }
+
+    #[test]
+    fn test_periastron_search_range() {
+        let (radius, mass) = (20.0, 1.0);
+        let range = periastron_search_range(radius, mass);
+        assert_eq!(*range.start(), MIN_PERIASTRON * mass);
+        assert_eq!(*range.end(), MAX_PERIASTRON * radius);
+
+        let periastron = calc_periastron(
+            radius,
+            Rad::from(Deg(80.0)),
+            Rad(1.0),
+            mass,
+            0,
+            &SolverConfig::default(),
+        )
+        .unwrap();
+        assert!(range.contains(&periastron));
+    }
+
+    #[test]
+    fn test_impact_parameter_cache() {
+        let cache = ImpactParameterCache::new();
+        let inclination = Rad::from(Deg(80.0));
+        let alpha = Rad(1.0);
+        let uncached =
+            calc_impact_parameter(20.0, inclination, alpha, 1.0, 0, &SolverConfig::default());
+
+        let config = SolverConfig::default();
+        let first = cache.calc_impact_parameter(20.0, inclination, alpha, 1.0, 0, &config);
+        let second = cache.calc_impact_parameter(20.0, inclination, alpha, 1.0, 0, &config);
+        assert_eq!(first.to_bits(), uncached.to_bits());
+        assert_eq!(second.to_bits(), uncached.to_bits());
+        assert_eq!(cache.len(), 1);
+
+        let _ = cache.calc_impact_parameter(20.0, inclination, alpha, 1.0, 1, &config);
+        assert_eq!(cache.len(), 2);
+
+        // Solutions with another solver configuration are solved with it, and cached separately
+        let coarse = SolverConfig {
+            tolerance: 1e-2,
+            ..Default::default()
+        };
+        let coarse_uncached = calc_impact_parameter(20.0, inclination, alpha, 1.0, 0, &coarse);
+        assert_ne!(coarse_uncached.to_bits(), uncached.to_bits());
+        let coarse_cached = cache.calc_impact_parameter(20.0, inclination, alpha, 1.0, 0, &coarse);
+        assert_eq!(coarse_cached.to_bits(), coarse_uncached.to_bits());
+        assert_eq!(cache.len(), 3);
+    }
+
+    #[test]
+    fn test_impact_parameter_cache_capacity() {
+        let inclination = Rad::from(Deg(80.0));
+        let config = SolverConfig::default();
+        let cache = ImpactParameterCache::with_capacity(2);
+        for radius in [10.0, 20.0, 30.0] {
+            assert_eq!(
+                cache.calc_impact_parameter(radius, inclination, Rad(1.0), 1.0, 0, &config),
+                calc_impact_parameter(radius, inclination, Rad(1.0), 1.0, 0, &config)
+            );
+        }
+        assert_eq!(cache.len(), 2);
+
+        let disabled = ImpactParameterCache::with_capacity(0);
+        let _ = disabled.calc_impact_parameter(10.0, inclination, Rad(1.0), 1.0, 0, &config);
+        assert!(disabled.is_empty());
+    }
+
+    #[test]
+    fn test_no_fallback() {
+        // The near side of the inner edge of the disk has no periastron solution when the disk is
+        // close to edge-on
+        let inclination = Rad::from(Deg(80.0));
+        let alpha = Rad(0.0);
+        assert_eq!(
+            try_calc_impact_parameter(
+                6.0,
+                inclination,
+                alpha,
+                1.0,
+                0,
+                FallbackMode::None,
+                &SolverConfig::default()
+            ),
+            None
+        );
+        assert!(try_calc_impact_parameter(
+            6.0,
+            inclination,
+            alpha,
+            1.0,
+            0,
+            FallbackMode::Ellipse,
+            &SolverConfig::default()
+        )
+        .is_some());
+        assert_eq!(
+            calc_impact_parameter_checked(
+                6.0,
+                inclination,
+                alpha,
+                1.0,
+                0,
+                &SolverConfig::default()
+            ),
+            (
+                ellipse(6.0, alpha, inclination),
+                ImpactParameterSource::EllipseFallback
+            )
+        );
+        // while the far side does
+        assert_eq!(
+            calc_impact_parameter_checked(
+                6.0,
+                inclination,
+                Rad(PI),
+                1.0,
+                0,
+                &SolverConfig::default()
+            )
+            .1,
+            ImpactParameterSource::Periastron
+        );
+
+        let cache = ImpactParameterCache::new();
+        assert_eq!(
+            cache.try_calc_impact_parameter(
+                6.0,
+                inclination,
+                alpha,
+                1.0,
+                0,
+                FallbackMode::None,
+                &SolverConfig::default()
+            ),
+            None
+        );
+        assert_eq!(
+            cache.calc_impact_parameter(6.0, inclination, alpha, 1.0, 0, &SolverConfig::default()),
+            calc_impact_parameter(6.0, inclination, alpha, 1.0, 0, &SolverConfig::default())
+        );
+        assert_eq!(
+            cache
+                .try_calc_impact_parameter_checked(
+                    6.0,
+                    inclination,
+                    alpha,
+                    1.0,
+                    0,
+                    FallbackMode::Ellipse,
+                    &SolverConfig::default()
+                )
+                .map(|(_, source)| source),
+            Some(ImpactParameterSource::EllipseFallback)
+        );
+    }
+
+    #[test]
+    fn test_impact_parameter_matches_reference_integrator() {
+        let mass = 1.0;
+        let critical_impact_parameter = 3.0 * 3.0_f64.sqrt() * mass;
+        for inclination in [Deg(30.0), Deg(80.0)] {
+            let inclination = Rad::from(inclination);
+            for order in [0, 1, 2] {
+                for radius in [6.0, 10.0, 30.0] {
+                    for i in 0..8 {
+                        let alpha = Rad(f64::from(i) * PI / 4.0 + 0.1);
+                        let reference =
+                            reference_impact_parameter(radius, inclination, alpha, mass, order);
+                        match try_calc_impact_parameter(
+                            radius,
+                            inclination,
+                            alpha,
+                            mass,
+                            order,
+                            FallbackMode::None,
+                            &SolverConfig::default(),
+                        ) {
+                            Some(solved) => assert!(
+                                (solved - reference).abs() < 1e-6 * reference,
+                                "solved {solved}, reference {reference} for radius {radius}, alpha {alpha:?}, order {order}"
+                            ),
+                            // With no solution, no escaping photon reaches the emission point
+                            None => assert!(
+                                (reference - critical_impact_parameter).abs() < 1e-6,
+                                "no solution, reference {reference} for radius {radius}, alpha {alpha:?}"
+                            ),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_one_over_radius_derivative() {
+        let (mass, inclination) = (1.0, Rad::from(Deg(80.0)));
+        let step = 1e-6;
+        for order in [0, 1] {
+            for periastron in [3.5, 6.0, 20.0, 80.0] {
+                for alpha in [0.3, 2.0, 4.5] {
+                    let one_over_radius =
+                        |p: f64| calc_one_over_radius(p, Rad(alpha), mass, inclination, order);
+                    let numerical = (one_over_radius(periastron + step)
+                        - one_over_radius(periastron - step))
+                        / (2.0 * step);
+                    let (value, analytic) = calc_one_over_radius_with_derivative(
+                        periastron,
+                        Rad(alpha),
+                        mass,
+                        inclination,
+                        order,
+                    );
+                    assert_relative_eq!(value, one_over_radius(periastron), epsilon = 1e-12);
+                    assert!(
+                        (analytic - numerical).abs() < 1e-6 * numerical.abs().max(1e-3),
+                        "analytic {analytic}, numerical {numerical} at {periastron}, {alpha}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_newton_matches_bisection() {
+        let mass = 1.0;
+        let (bisection_config, newton_config) = (
+            SolverConfig::default(),
+            SolverConfig {
+                method: SolverMethod::Newton,
+                ..Default::default()
+            },
+        );
+        let mut rng = StdRng::seed_from_u64(0);
+        for inclination in [Deg(10.0), Deg(60.0), Deg(89.0)] {
+            let inclination = Rad::from(inclination);
+            for order in [0, 1, 2] {
+                for _ in 0..300 {
+                    let radius = rng.gen_range(6.0..50.0);
+                    let alpha = Rad(rng.gen_range(0.0..2.0 * PI));
+                    let bisection =
+                        calc_periastron(radius, inclination, alpha, mass, order, &bisection_config);
+                    let newton = calc_periastron_newton(
+                        radius,
+                        inclination,
+                        alpha,
+                        mass,
+                        order,
+                        &newton_config,
+                    );
+                    match (bisection, newton) {
+                        // Each is only converged to within the tolerance
+                        (Some(bisection), Some(newton)) => assert!(
+                            (bisection - newton).abs() <= 2.0 * PERIASTRON_TOLERANCE,
+                            "{bisection} != {newton} at radius {radius}, {alpha:?}, order {order}"
+                        ),
+                        (None, None) => {}
+                        _ => panic!("{bisection:?} != {newton:?} at radius {radius}, {alpha:?}"),
+                    }
+                }
+            }
+        }
+
+        // The near side of the inner edge of the disk has no solution when close to edge-on
+        let inclination = Rad::from(Deg(80.0));
+        assert_eq!(
+            calc_periastron_newton(6.0, inclination, Rad(0.0), mass, 0, &newton_config),
+            None
+        );
+        assert_eq!(
+            try_calc_impact_parameter(
+                6.0,
+                inclination,
+                Rad(0.0),
+                mass,
+                0,
+                FallbackMode::None,
+                &newton_config
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_solver_config() {
+        let (radius, inclination, alpha, mass) = (20.0, Rad::from(Deg(80.0)), Rad(1.0), 1.0);
+        let default = calc_periastron(
+            radius,
+            inclination,
+            alpha,
+            mass,
+            0,
+            &SolverConfig::default(),
+        )
+        .unwrap();
+        let coarse_config = SolverConfig {
+            tolerance: 1e-2,
+            ..Default::default()
+        };
+        let coarse = calc_periastron(radius, inclination, alpha, mass, 0, &coarse_config).unwrap();
+        assert_ne!(coarse, default);
+        assert!((coarse - default).abs() <= coarse_config.tolerance);
+
+        // Narrowing the search range excludes the solution
+        let narrow_config = SolverConfig {
+            min_periastron: (default + 1.0) / mass,
+            ..Default::default()
+        };
+        assert_eq!(
+            calc_periastron(radius, inclination, alpha, mass, 0, &narrow_config),
+            None
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            // Fields missing from a serialized config take their default values
+            let config: SolverConfig =
+                serde_json::from_str(r#"{"tolerance": 0.01, "method": "newton"}"#).unwrap();
+            assert_eq!(
+                config,
+                SolverConfig {
+                    method: SolverMethod::Newton,
+                    ..coarse_config
+                }
+            );
+            assert_eq!(
+                serde_json::from_str::<SolverConfig>(&serde_json::to_string(&config).unwrap())
+                    .unwrap(),
+                config
+            );
+            assert!(serde_json::from_str::<SolverConfig>(r#"{"tolerence": 0.01}"#).is_err());
+        }
     }
 }
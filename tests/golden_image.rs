@@ -0,0 +1,85 @@
+//! End-to-end regression test of the flux image pipeline against a checked-in golden image.
+//!
+//! Set the `UPDATE_GOLDEN` environment variable to regenerate the golden image after an
+//! intentional change to the rendered output.
+
+use cgmath::Deg;
+use luminet_blackhole_lib::{
+    plotting::{generate_flux_image_from_samples, FluxImageOptions, Luma16Image},
+    BlackHole,
+};
+use std::path::PathBuf;
+
+const GOLDEN_IMAGE_PATH: &str = "tests/golden/flux_80deg.png";
+
+/// Maximum mean absolute difference between the rendered and golden images, as a fraction of full
+/// scale.
+const MEAN_DIFFERENCE_TOLERANCE: f64 = 1e-3;
+/// Maximum fraction of pixels which may differ by more than `PIXEL_DIFFERENCE_TOLERANCE`.
+const DIFFERING_PIXELS_TOLERANCE: f64 = 1e-2;
+/// Difference between a pair of pixels, as a fraction of full scale, above which they're counted
+/// as differing.
+const PIXEL_DIFFERENCE_TOLERANCE: f64 = 1e-2;
+
+/// Summary of the differences between two images of the same size.
+struct ImageDifference {
+    /// Mean absolute difference, as a fraction of full scale.
+    mean: f64,
+    /// Fraction of pixels that differ by more than `PIXEL_DIFFERENCE_TOLERANCE`.
+    differing_pixels: f64,
+}
+
+fn image_difference(a: &Luma16Image, b: &Luma16Image) -> ImageDifference {
+    assert_eq!(a.dimensions(), b.dimensions(), "images differ in size");
+    let differences = a
+        .pixels()
+        .zip(b.pixels())
+        .map(|(a, b)| f64::from(a.0[0].abs_diff(b.0[0])) / f64::from(u16::MAX))
+        .collect::<Vec<f64>>();
+    let pixel_count = differences.len() as f64;
+    ImageDifference {
+        mean: differences.iter().sum::<f64>() / pixel_count,
+        differing_pixels: differences
+            .iter()
+            .filter(|&&d| d > PIXEL_DIFFERENCE_TOLERANCE)
+            .count() as f64
+            / pixel_count,
+    }
+}
+
+fn render() -> Luma16Image {
+    let blackhole = BlackHole::default();
+    let inclination = Deg(80.0).into();
+    generate_flux_image_from_samples(
+        &blackhole,
+        inclination,
+        &mut blackhole.sample_flux_at_points_seeded(inclination, 4000, 0, 1),
+        &mut blackhole.sample_flux_at_points_seeded(inclination, 4000, 1, 2),
+        96,
+        48,
+        &FluxImageOptions::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_flux_image_matches_golden() {
+    let img = render();
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_IMAGE_PATH);
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        img.save(&golden_path).unwrap();
+    }
+
+    let golden = image::open(&golden_path)
+        .expect("golden image exists, run with UPDATE_GOLDEN=1 to create it")
+        .into_luma16();
+    let difference = image_difference(&img, &golden);
+    assert!(
+        difference.mean <= MEAN_DIFFERENCE_TOLERANCE
+            && difference.differing_pixels <= DIFFERING_PIXELS_TOLERANCE,
+        "rendered image differs from golden image: mean difference {}, {}% of pixels differ",
+        difference.mean,
+        difference.differing_pixels * 100.0
+    );
+}